@@ -2,4 +2,4 @@ mod app;
 mod run;
 
 pub use app::command;
-pub use run::{run, GenerateRequest};
+pub use run::{run, GenerateRequest, GeneratedRecord};
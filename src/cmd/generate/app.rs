@@ -1,41 +1,89 @@
 use clap::{App, AppSettings, Arg, SubCommand};
+use lazy_static::lazy_static;
 
-use crate::datetime::{Hms, HmsError, Ymd, YmdError};
-use crate::delta::{DeltaItem, DeltaItemError};
-use crate::precision::{Precision, PrecisionError};
-use crate::preset::{Preset, PresetError};
-use crate::unit::{TimeUnit, TimeUnitError};
-use crate::validate::{validate_argv, validate_argv_by_name};
+use crate::radix::{Radix, RadixError};
+use crate::snap::{Snap, SnapError, SnapMode, SnapModeError};
+use ut_cli::datetime::{validate_ymd_with, Hms, HmsError};
+use ut_cli::delta::validate_delta;
+use ut_cli::find::{FindOptions, PossibleNames};
+use ut_cli::fold::{Fold, FoldError};
+use ut_cli::offset::{Offset, OffsetError};
+use ut_cli::precision::{Precision, PrecisionError};
+use ut_cli::preset::{Preset, PresetError};
+use ut_cli::unit::{TimeUnit, TimeUnitError};
+use ut_cli::validate::{
+    validate_argv, validate_argv_by_name, validate_timestamp, validate_weekday, ValidationMessage,
+};
+use ut_cli::week::{NthWeekday, NthWeekdayError};
 
-pub fn command(name: &str) -> App<'static, 'static> {
+lazy_static! {
+    static ref BASE_HELP: String = Preset::help_with_possible_names("Set base DATE from presets.");
+    static ref TRUNCATE_HELP: String = TimeUnit::help_with_possible_names(
+        "Set the UNIT to truncate the base DATE and TIME. Multiple units are applied in order."
+    );
+    static ref FOLD_HELP: String = Fold::help_with_possible_names(
+        "Set which instant to use when the TIME occurs twice in a DST transition. [default: earlier]"
+    );
+    static ref SNAP_MODE_HELP: String =
+        SnapMode::help_with_possible_names("Set the MODE to snap. [default: floor]");
+    static ref RADIX_HELP: String =
+        Radix::help_with_possible_names("Print the timestamp in the RADIX. [default: dec]");
+    static ref PRECISION_HELP: String =
+        Precision::help_with_possible_names("[Deprecated] Set the precision of output timestamp.");
+}
+
+pub fn command(name: &str, options: FindOptions) -> App<'static, 'static> {
     SubCommand::with_name(name)
         .about("Generate unix timestamp with given options.")
         .settings(&[AppSettings::AllowNegativeNumbers, AppSettings::ColoredHelp])
         .arg(
             Arg::with_name("BASE")
                 .value_name("DATE")
-                .help("Set base DATE from presets.")
+                .help(BASE_HELP.as_str())
                 .next_line_help(true)
                 .short("b")
                 .long("base")
                 .takes_value(true)
-                .validator(validate_argv_by_name::<Preset, PresetError>)
+                .validator(validate_argv_by_name::<Preset, PresetError>(options))
                 .conflicts_with_all(&["BASE_TIMESTAMP", "YMD"]),
         )
         .arg(
             Arg::with_name("BASE_TIMESTAMP")
                 .help("Set a base timestamp.")
-                .validator(|s| s.parse::<i64>().map(|_| ()).map_err(|e| format!("{:?}", e)))
+                .validator(validate_timestamp)
                 .allow_hyphen_values(true)
                 .conflicts_with_all(&["BASE", "YMD", "HMS"]),
         )
+        .arg(
+            Arg::with_name("STRICT_NUMERIC")
+                .help("Reject timestamps with leading zeros, like `00123`.")
+                .long("strict-numeric"),
+        )
+        .arg(
+            Arg::with_name("DETECT_PRECISION")
+                .help("Detect the precision of the base timestamp from its number of digits.")
+                .long("detect-precision")
+                .requires("BASE_TIMESTAMP"),
+        )
+        .arg(
+            Arg::with_name("ZERO_BASE")
+                .help("Use the unix epoch instead of the current time as a base.")
+                .long("zero-base")
+                .conflicts_with_all(&["BASE", "BASE_TIMESTAMP"]),
+        )
         .arg(
             Arg::with_name("YMD")
                 .value_name("DATE")
                 .help("Set the DATE in yyyyMMdd format.")
                 .long("ymd")
                 .takes_value(true)
-                .validator(validate_argv::<Ymd, YmdError>),
+                // two-digit years are rejected later, unless --wrap-year is given.
+                .validator(validate_ymd_with(true)),
+        )
+        .arg(
+            Arg::with_name("WRAP_YEAR")
+                .help("Read two-digit years of dates like `19-06-17`: 00-68 are 2000-2068, and 69-99 are 1969-1999.")
+                .long("wrap-year"),
         )
         .arg(
             Arg::with_name("HMS")
@@ -45,15 +93,132 @@ pub fn command(name: &str) -> App<'static, 'static> {
                 .takes_value(true)
                 .validator(validate_argv::<Hms, HmsError>),
         )
+        .arg(
+            Arg::with_name("FOLD")
+                .value_name("FOLD")
+                .help(FOLD_HELP.as_str())
+                .next_line_help(true)
+                .long("fold")
+                .takes_value(true)
+                .validator(validate_argv_by_name::<Fold, FoldError>(options)),
+        )
+        .arg(
+            Arg::with_name("INPUT_OFFSET")
+                .value_name("OFFSET")
+                .help("Interpret the DATE and TIME in given timezone offset, instead of the output one.")
+                .long("input-offset")
+                .alias("input-timezone")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_argv::<Offset, OffsetError>)
+                .conflicts_with_all(&["BASE_TIMESTAMP"]),
+        )
+        .arg(
+            Arg::with_name("STDIN")
+                .help("Read a DATE and an optional TIME per line from stdin, like `2019-06-17 11:22:33`.")
+                .long("stdin")
+                .conflicts_with_all(&[
+                    "BASE",
+                    "BASE_TIMESTAMP",
+                    "ZERO_BASE",
+                    "YMD",
+                    "HMS",
+                ]),
+        )
+        .arg(
+            Arg::with_name("SKIP_INVALID")
+                .help("Skip invalid lines with their line numbers on stderr, instead of failing.")
+                .long("skip-invalid")
+                .requires("STDIN"),
+        )
+        .arg(
+            Arg::with_name("START_OF_DAY")
+                .help("Set the TIME to the start of the day.")
+                .long("start-of-day")
+                .conflicts_with_all(&["HMS", "END_OF_DAY", "BASE_TIMESTAMP"]),
+        )
+        .arg(
+            Arg::with_name("END_OF_DAY")
+                .help("Set the TIME to the end of the day, like 23:59:59.999 in millisecond.")
+                .long("end-of-day")
+                .conflicts_with_all(&["HMS", "START_OF_DAY", "BASE_TIMESTAMP"]),
+        )
         .arg(
             Arg::with_name("TRUNCATE")
                 .value_name("UNIT")
-                .help("Set the UNIT to truncate the base DATE and TIME.")
+                .help(TRUNCATE_HELP.as_str())
                 .next_line_help(true)
                 .short("t")
                 .long("truncate")
                 .takes_value(true)
-                .validator(validate_argv_by_name::<TimeUnit, TimeUnitError>),
+                .multiple(true)
+                .number_of_values(1)
+                .validator(validate_argv_by_name::<TimeUnit, TimeUnitError>(options)),
+        )
+        .arg(
+            Arg::with_name("SNAP")
+                .value_name("INTERVAL")
+                .help("Snap the base DATE and TIME to a multiple of the INTERVAL.")
+                .long_help(
+                    "
+Multiples are measured from the start of the enclosing unit.
+Example:
+    --snap 15m   : snap to 00, 15, 30 or 45 minutes of the hour.
+    --snap 6h    : snap to 00, 06, 12 or 18 hours of the day.
+",
+                )
+                .next_line_help(true)
+                .long("snap")
+                .takes_value(true)
+                .validator(validate_argv::<Snap, SnapError>),
+        )
+        .arg(
+            Arg::with_name("SNAP_MODE")
+                .value_name("MODE")
+                .help(SNAP_MODE_HELP.as_str())
+                .next_line_help(true)
+                .long("snap-mode")
+                .takes_value(true)
+                .requires("SNAP")
+                .validator(validate_argv_by_name::<SnapMode, SnapModeError>(options)),
+        )
+        .arg(
+            Arg::with_name("ALIGN_TO")
+                .value_name("WEEKDAY")
+                .help("Move the base forward to the WEEKDAY, like `monday` or `mon`.")
+                .long_help(
+                    "
+Move the base forward to the WEEKDAY, keeping the time. a base on the WEEKDAY is kept.
+Deltas are applied after the alignment.
+Example:
+    --ymd 20190619 --align-to mon       : 2019-06-24, the next monday.
+    --ymd 20190619 --align-to mon -d 7d : 2019-07-01, the monday after.
+",
+                )
+                .next_line_help(true)
+                .long("align-to")
+                .takes_value(true)
+                .validator(validate_weekday),
+        )
+        .arg(
+            Arg::with_name("NTH_WEEKDAY")
+                .value_name("NWEEKDAY")
+                .help("Move the base date to the Nth WEEKDAY of its month, like `3monday`, or `-1friday` for the last one.")
+                .long_help(
+                    "
+Move the base date to the Nth WEEKDAY of its month, like `3monday`. negative numbers count from
+the end of the month, like `-1friday` for the last friday. the month is of --ymd, the preset or today.
+The time is the start of the day, unless --hms is given. it fails if the month does not have the date.
+Example:
+    --ymd 20190601 --nth-weekday 3mon   : 2019-06-17, the 3rd monday of June 2019.
+    --ymd 20190601 --nth-weekday -1fri  : 2019-06-28, the last friday of June 2019.
+    --ymd 20190201 --nth-weekday 5fri   : an error, February 2019 has 4 fridays.",
+                )
+                .long("nth-weekday")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_argv::<NthWeekday, NthWeekdayError>)
+                .conflicts_with_all(&["BASE_TIMESTAMP", "ZERO_BASE"]),
         )
         .arg(
             Arg::with_name("DELTA")
@@ -63,6 +228,8 @@ pub fn command(name: &str) -> App<'static, 'static> {
 Example:
     --delta=3day  :  3 days later.
     -d 1y -d -10h : 10 hours ago in next year.
+    -d +10h       : 10 hours later, the sign may be `+` or `-`.
+    -d P1DT12H    : 1 day and 12 hours later, in ISO 8601 duration.
 ",
                 )
                 .next_line_help(true)
@@ -72,15 +239,266 @@ Example:
                 .allow_hyphen_values(true)
                 .multiple(true)
                 .number_of_values(1)
-                .validator(validate_argv::<DeltaItem, DeltaItemError>),
+                .validator(validate_delta(options)),
+        )
+        .arg(
+            Arg::with_name("JITTER")
+                .value_name("DELTA")
+                .help("Add a random offset within plus or minus DELTA to each timestamp, like `30s` or `1h`.")
+                .long("jitter")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_delta(options)),
+        )
+        .arg(
+            Arg::with_name("SEED")
+                .help("Set the seed of --jitter, so that the same seed generates the same timestamps. [default: the current time]")
+                .long("seed")
+                .takes_value(true)
+                .requires("JITTER")
+                .validator(validate_seed),
+        )
+        .arg(
+            Arg::with_name("SATURATE")
+                .help("Clamp the result to the nearest valid datetime, instead of failing.")
+                .long_help(
+                    "
+Clamp the result to the nearest valid datetime, instead of failing.
+Example:
+    --ymd 20190131 -d 1mon --saturate : 2019-02-28, as 2019-02-31 does not exist.
+",
+                )
+                .next_line_help(true)
+                .long("saturate"),
+        )
+        .arg(
+            Arg::with_name("CLAMP_DAY")
+                .help("Clamp the day to the last day of the month, when a delta lands on a nonexistent day. Other invalid datetimes still fail.")
+                .long_help(
+                    "
+Clamp the day to the last day of the month, when a delta lands on a nonexistent day.
+Other invalid datetimes, like a local time skipped by DST, still fail. Use --saturate to resolve them too.
+Example:
+    --ymd 20190131 -d 1mon --clamp-day : 2019-02-28.
+    --ymd 20200131 -d 1mon --clamp-day : 2020-02-29, in a leap year.
+",
+                )
+                .next_line_help(true)
+                .long("clamp-day")
+                .conflicts_with("SATURATE"),
+        )
+        .arg(
+            Arg::with_name("TRIM")
+                .help("Drop the fraction of the base finer than the precision before applying deltas. [default]")
+                .long("trim")
+                .overrides_with("NO_TRIM"),
+        )
+        .arg(
+            Arg::with_name("NO_TRIM")
+                .help("Keep the fraction of the base finer than the precision, like the one of the current time.")
+                .long("no-trim")
+                .overrides_with("TRIM"),
+        )
+        .arg(
+            Arg::with_name("EPOCH_DAY")
+                .help("Print the number of days from 1970-01-01 to the date, instead of the timestamp.")
+                .long("epoch-day"),
+        )
+        .arg(
+            Arg::with_name("RADIX")
+                .help(RADIX_HELP.as_str())
+                .next_line_help(true)
+                .long("radix")
+                .takes_value(true)
+                .validator(validate_argv_by_name::<Radix, RadixError>(options)),
         )
         .arg(
             Arg::with_name("PRECISION")
-                .help("[Deprecated] Set the precision of output timestamp.")
+                .help(PRECISION_HELP.as_str())
                 .next_line_help(true)
                 .short("p")
                 .long("precision")
                 .takes_value(true)
-                .validator(validate_argv_by_name::<Precision, PrecisionError>),
+                .validator(validate_argv_by_name::<Precision, PrecisionError>(options)),
         )
 }
+
+fn validate_seed(s: String) -> Result<(), String> {
+    s.parse::<u64>().map(|_| ()).map_err(|_| {
+        ValidationMessage::new("Wrong seed: seed must be a non-negative integer.", s)
+            .accepted("an integer from 0 to 18446744073709551615, like `42`")
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::AppSettings;
+
+    use super::command;
+    use ut_cli::find::FindOptions;
+
+    #[test]
+    fn help_possible_values() {
+        let mut help = Vec::new();
+        command("generate", FindOptions::default())
+            .setting(AppSettings::ColorNever)
+            .write_help(&mut help)
+            .unwrap();
+        let help = String::from_utf8(help).unwrap();
+        let help = help.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let possible_values: Vec<&str> = help
+            .match_indices("[possible values: ")
+            .map(|(i, _)| &help[i..=i + help[i..].find(']').unwrap()])
+            .collect();
+        assert_eq!(
+            possible_values,
+            vec![
+                "[possible values: today, tomorrow, yesterday, start-of-week, end-of-week]",
+                "[possible values: earlier, later]",
+                "[possible values: second, millisecond, microsecond, nanosecond]",
+                "[possible values: dec, hex]",
+                "[possible values: floor, round, ceil]",
+                "[possible values: century, decade, year, month, day, hour, minute, second, millisecond]",
+            ]
+        );
+    }
+
+    #[test]
+    fn day_boundaries_conflict() {
+        let r = |args: Vec<&str>| {
+            command("generate", FindOptions::default()).get_matches_from_safe(args)
+        };
+        assert!(r(vec!["generate", "--start-of-day", "--end-of-day"]).is_err());
+        assert!(r(vec!["generate", "--end-of-day", "--hms", "120000"]).is_err());
+        assert!(r(vec!["generate", "--start-of-day", "0"]).is_err());
+        assert!(r(vec!["generate", "-b", "today", "--end-of-day"]).is_ok());
+    }
+
+    #[test]
+    fn strict_names() {
+        let strict = FindOptions { strict: true };
+        let args = |unit| vec!["generate", "-t", unit];
+
+        assert!(command("generate", strict)
+            .get_matches_from_safe(args("ms"))
+            .is_ok());
+        assert!(command("generate", strict)
+            .get_matches_from_safe(args("mil"))
+            .is_err());
+        assert!(command("generate", FindOptions::default())
+            .get_matches_from_safe(args("mil"))
+            .is_ok());
+        let e = command("generate", strict)
+            .setting(AppSettings::ColorNever)
+            .get_matches_from_safe(args("mil"))
+            .unwrap_err();
+        assert!(e
+            .message
+            .contains("accepted: a possible name, exactly as it is"));
+        assert!(!e.message.contains("prefix"));
+
+        let deltas = |delta| vec!["generate", "-d", delta];
+        assert!(command("generate", strict)
+            .get_matches_from_safe(deltas("1minute"))
+            .is_ok());
+        assert!(command("generate", strict)
+            .get_matches_from_safe(deltas("1m"))
+            .is_err());
+        assert!(command("generate", strict)
+            .get_matches_from_safe(deltas("1d"))
+            .is_err());
+        let e = command("generate", strict)
+            .setting(AppSettings::ColorNever)
+            .get_matches_from_safe(deltas("1y"))
+            .unwrap_err();
+        assert!(e.message.contains("did you mean 'year'?"));
+        assert!(e.message.contains("like `1day` or `-3hour`"));
+        assert!(command("generate", FindOptions::default())
+            .get_matches_from_safe(deltas("1min"))
+            .is_ok());
+    }
+
+    fn error_message(args: Vec<&str>) -> String {
+        let e = command("generate", FindOptions::default())
+            .setting(AppSettings::ColorNever)
+            .get_matches_from_safe(args)
+            .unwrap_err();
+        e.message.split("\n\n").next().unwrap().to_string()
+    }
+
+    #[test]
+    fn validation_messages() {
+        assert_eq!(
+            error_message(vec!["generate", "-b", "yesterdy"]),
+            "error: Invalid value for '--base <DATE>': Wrong preset: no matching name. did you mean 'yesterday'?
+    input: 'yesterdy'
+    accepted: a possible name, or its unique prefix
+    possible names: [today, tomorrow, yesterday, start-of-week, end-of-week]"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "1.5"]),
+            "error: Invalid value for '<BASE_TIMESTAMP>': Wrong timestamp: timestamp must be an integer.
+    input: '1.5'
+    accepted: seconds or milliseconds since the epoch, like `1561302000` or `0x5D0775D1`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "--ymd", "2019-13-01"]),
+            "error: Invalid value for '--ymd <DATE>': Wrong date: month must be between 1 and 12.
+    input: '2019-13-01'
+    accepted: `yyyyMMdd`, `yyyy-MM-dd` or `yyyy/MM/dd`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "--ymd", "2019-02-30"]),
+            "error: Invalid value for '--ymd <DATE>': Wrong date: the date does not exist.
+    input: '2019-02-30'
+    accepted: `yyyyMMdd`, `yyyy-MM-dd` or `yyyy/MM/dd`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "--hms", "1pm"]),
+            "error: Invalid value for '--hms <TIME>': Wrong time: text is not in a time format.
+    input: '1pm'
+    accepted: `HHmmss` or `HH:mm:ss`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "-t", "m"]),
+            "error: Invalid value for '--truncate <UNIT>...': Wrong unit: ambiguous name. it could be 'month', 'minute' or 'millisecond'.
+    input: 'm'
+    accepted: a possible name, or its unique prefix
+    possible names: [century, decade, year, month, day, hour, minute, second, millisecond]"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "--snap", "0min"]),
+            "error: Invalid value for '--snap <INTERVAL>': Wrong interval: interval must be greater than 0.
+    input: '0min'
+    accepted: a positive number followed by a unit, like `15m` or `6h`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "--snap", "15min", "--snap-mode", "x"]),
+            "error: Invalid value for '--snap-mode <MODE>': Wrong snap mode: no matching name.
+    input: 'x'
+    accepted: a possible name, or its unique prefix
+    possible names: [floor, round, ceil]"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "-d", "1.5d"]),
+            "error: Invalid value for '--delta <DELTA>...': Wrong delta: text is not in a delta format.
+    input: '1.5d'
+    accepted: a number followed by a unit, like `1d`, `-3h` or `+15min`, or an ISO 8601 duration like `P1DT12H`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "-d", "99999999999d"]),
+            "error: Invalid value for '--delta <DELTA>...': Wrong delta: value must be a 32-bit integer.
+    input: '99999999999d'
+    accepted: a number followed by a unit, like `1d`, `-3h` or `+15min`, or an ISO 8601 duration like `P1DT12H`"
+        );
+        assert_eq!(
+            error_message(vec!["generate", "-d", "3xx"]),
+            "error: Invalid value for '--delta <DELTA>...': Wrong delta unit: no matching name.
+    input: '3xx'
+    accepted: a number followed by a unit, like `1d`, `-3h` or `+15min`, or an ISO 8601 duration like `P1DT12H`
+    possible names: [century, decade, year, month, day, hour, minute, second, millisecond]"
+        );
+    }
+}
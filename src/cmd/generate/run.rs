@@ -1,82 +1,295 @@
-use std::convert::TryFrom;
+use std::cell::Cell;
+use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
+use std::io::{self, BufRead};
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use anyhow::Context;
 use chrono::prelude::*;
 use clap::ArgMatches;
+use serde::Serialize;
 
-use crate::datetime::{Hms, HmsError, Ymd, YmdError};
-use crate::delta::DeltaItem;
-use crate::find::FindByName;
-use crate::parse::parse_argv_opt;
-use crate::precision::Precision;
-use crate::preset::Preset;
-use crate::provider::DateTimeProvider;
-use crate::timedelta::{ApplyDateTime, TimeDeltaBuilder};
-use crate::unit::TimeUnit;
+use crate::output::Output;
+use crate::radix::Radix;
+use crate::record::Record;
+use crate::snap::{Snap, SnapError, SnapMode};
+use crate::summary::Summary;
+use ut_cli::datetime::{epoch_day, Hms, HmsError, Ymd, YmdError};
+use ut_cli::delta::{parse_items, DeltaItem};
+use ut_cli::find::FindByName;
+use ut_cli::fold::Fold;
+use ut_cli::offset::{Offset, OffsetError};
+use ut_cli::parse::parse_argv_opt;
+use ut_cli::precision::Precision;
+use ut_cli::preset::Preset;
+use ut_cli::provider::{start_of_day, DateTimeProvider, FixedNowProvider};
+use ut_cli::random::SplitMix64;
+use ut_cli::read::{read_records, Delimiter};
+use ut_cli::timedelta::{Adjustment, ApplyDateTime, ApplyError, TimeDelta, TimeDeltaBuilder};
+use ut_cli::timestamp::Timestamp;
+use ut_cli::unit::TimeUnit;
+use ut_cli::validate::parse_timestamp;
+use ut_cli::week::{NthWeekday, NthWeekdayError, WeekStart};
 
-#[derive(Debug)]
+/// Sets the time of the base DATE.
+#[derive(Debug, Copy, Clone)]
+enum TimeOfDay {
+    Hms(Hms),
+    StartOfDay,
+    EndOfDay,
+}
+
+impl TimeOfDay {
+    fn naive_time(self, precision: Precision) -> NaiveTime {
+        match self {
+            TimeOfDay::Hms(hms) => hms.into(),
+            TimeOfDay::StartOfDay => NaiveTime::MIN,
+            TimeOfDay::EndOfDay => precision.end_of_day(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 struct GenerateOptions {
     timestamp: Option<i64>,
+    detect_precision: bool,
+    zero_base: bool,
     preset: Option<Preset>,
     ymd: Option<Ymd>,
-    hms: Option<Hms>,
-    truncate: Option<TimeUnit>,
+    wrap_year: bool,
+    time: Option<TimeOfDay>,
+    fold: Fold,
+    input_offset: Option<FixedOffset>,
+    truncate: Vec<TimeUnit>,
+    snap: Option<Snap>,
+    snap_mode: SnapMode,
+    align_to: Option<Weekday>,
+    nth_weekday: Option<NthWeekday>,
     deltas: Vec<DeltaItem>,
+    source_date_epoch: Option<i64>,
+    week_start: WeekStart,
 }
 
 impl GenerateOptions {
+    /// Describes the selected base, like `date 2019-06-17 at 09:02:09`.
+    fn base_description(&self, precision: Precision) -> String {
+        let base = if let Some(timestamp) = self.timestamp {
+            format!("timestamp {}", timestamp)
+        } else if self.zero_base {
+            "the unix epoch".to_string()
+        } else if let Some(preset) = self.preset {
+            preset.to_string()
+        } else if let Some(ymd) = self.ymd {
+            TryInto::<NaiveDate>::try_into(ymd)
+                .map_or_else(|_| format!("{:?}", ymd), |date| format!("date {}", date))
+        } else if let Some(seconds) = self.source_date_epoch {
+            format!("SOURCE_DATE_EPOCH {}", seconds)
+        } else {
+            "now".to_string()
+        };
+        match self.time {
+            Some(time) => format!("{} at {}", base, time.naive_time(precision)),
+            None => base,
+        }
+    }
+
+    /// Returns the base datetime. An explicit base takes precedence over `SOURCE_DATE_EPOCH`,
+    /// which takes precedence over the current time of the provider.
+    /// Each step is traced to the output, from the source of the base to the alignment.
     pub fn base_datetime<P, Tz>(
         &self,
-        provider: P,
+        provider: &P,
+        precision: Precision,
+        output: &mut Output,
+    ) -> Result<DateTime<Tz>, Box<dyn std::error::Error>>
+    where
+        Tz: TimeZone + Debug,
+        P: DateTimeProvider<Tz>,
+    {
+        match self.source_date_epoch {
+            Some(seconds) => {
+                let provider = FixedNowProvider::from_timestamp(provider.timezone(), seconds)
+                    .ok_or_else(|| anyhow::anyhow!("Wrong SOURCE_DATE_EPOCH: {}", seconds))?;
+                output.trace(format_args!("now: SOURCE_DATE_EPOCH {}", seconds))?;
+                self.base_datetime_with(&provider, precision, output)
+            }
+            None => self.base_datetime_with(provider, precision, output),
+        }
+    }
+
+    fn base_datetime_with<P, Tz>(
+        &self,
+        provider: &P,
         precision: Precision,
+        output: &mut Output,
     ) -> Result<DateTime<Tz>, Box<dyn std::error::Error>>
     where
         Tz: TimeZone + Debug,
         P: DateTimeProvider<Tz>,
     {
-        //
         let base = if let Some(timestamp) = self.timestamp {
-            precision.parse_timestamp(provider.timezone(), timestamp)
+            let precision = self.timestamp_precision(timestamp, precision);
+            output.trace(format_args!(
+                "base: timestamp {} in {}",
+                timestamp, precision
+            ))?;
+            Timestamp::new(timestamp, precision).to_datetime(provider.timezone())?
         } else {
-            let now = provider.now();
-            let maybe_date = self.base_date(&provider)?;
+            let now = if self.zero_base {
+                provider.timezone().timestamp_opt(0, 0).unwrap()
+            } else {
+                provider.now()
+            };
+            output.trace(format_args!("now: {:?}", now))?;
+            let maybe_date = self.base_date(provider)?;
+            if let Some(ref date) = maybe_date {
+                output.trace(format_args!("date: {:?}", date))?;
+            }
             let has_date = maybe_date.is_some();
-            let date = maybe_date.unwrap_or_else(|| now.date());
-            let time = self.hms.map(|hms| hms.into()).unwrap_or_else(|| {
-                if has_date {
-                    NaiveTime::from_hms(0, 0, 0)
-                } else {
-                    now.time()
+            match self.input_offset {
+                Some(input_offset) => {
+                    // compose the wall-clock in the input offset, then convert it to the output.
+                    let now = now.with_timezone(&input_offset);
+                    let date = maybe_date.unwrap_or_else(|| now.date_naive());
+                    let time = self.base_time(has_date, now.time(), precision);
+                    input_offset
+                        .from_local_datetime(&date.and_time(time))
+                        .single()
+                        .ok_or_else(|| anyhow::anyhow!("Wrong datetime."))?
+                        .with_timezone(&provider.timezone())
+                }
+                None => {
+                    let date = maybe_date.unwrap_or_else(|| now.date_naive());
+                    let time = self.base_time(has_date, now.time(), precision);
+                    if time == NaiveTime::MIN {
+                        // the day may start after midnight, or at the first of repeated midnights.
+                        start_of_day("start of day", &provider.timezone(), date)?
+                    } else {
+                        // a time in a DST transition does not exist, or occurs twice.
+                        let local = date.and_time(time);
+                        self.fold
+                            .pick(provider.timezone().from_local_datetime(&local))
+                            .ok_or_else(|| anyhow::anyhow!("Wrong datetime."))?
+                    }
                 }
-            });
+            }
+        };
+
+        output.trace(format_args!("base: {:?}", base))?;
 
-            date.and_time(time).unwrap()
+        let truncated = self.truncate.iter().try_fold(base, |dt, unit| {
+            let dt = unit.truncate(dt)?;
+            output.trace(format_args!("truncate to {}: {:?}", unit, dt))?;
+            Ok::<_, Box<dyn std::error::Error>>(dt)
+        })?;
+
+        let snapped = match self.snap {
+            Some(snap) => {
+                let dt = snap
+                    .apply(truncated, self.snap_mode)
+                    .ok_or_else(|| anyhow::anyhow!("Snap error."))?;
+                output.trace(format_args!("snap: {:?}", dt))?;
+                dt
+            }
+            None => truncated,
         };
 
-        Ok(self
-            .truncate
-            .iter()
-            .fold(base, |dt, unit| unit.truncate(dt)))
+        match self.align_to {
+            Some(weekday) => {
+                let dt = align_to(snapped, weekday).context("Align error.")?;
+                output.trace(format_args!("align to {}: {:?}", weekday, dt))?;
+                Ok(dt)
+            }
+            None => Ok(snapped),
+        }
     }
 
-    fn base_date<P, Tz>(&self, provider: &P) -> Result<Option<Date<Tz>>, Box<dyn std::error::Error>>
+    /// Returns the precision of the base timestamp, which is detected by its digits with `--detect-precision`.
+    fn timestamp_precision(&self, timestamp: i64, precision: Precision) -> Precision {
+        if self.detect_precision {
+            Precision::detect(timestamp)
+        } else {
+            precision
+        }
+    }
+
+    /// Returns the base datetime of a line like `2019-06-17 11:22:33` or `2019-06-17`.
+    fn line_datetime<P, Tz>(
+        &self,
+        provider: &P,
+        precision: Precision,
+        line: &str,
+        output: &mut Output,
+    ) -> Result<DateTime<Tz>, Box<dyn std::error::Error>>
     where
         Tz: TimeZone + Debug,
         P: DateTimeProvider<Tz>,
     {
-        let date = self
-            .preset
-            .map(|p| Ok(Some(p.as_date(provider))))
-            .unwrap_or_else(|| {
-                self.ymd.map_or(Ok(None), |ymd| {
-                    ymd.into_date(&provider.timezone()).map(Some)
+        let mut fields = line.split_whitespace();
+        let ymd = fields
+            .next()
+            .map(|s| ymd_from(s, self.wrap_year))
+            .transpose()?;
+        let hms = fields
+            .next()
+            .map(Hms::from_str)
+            .transpose()
+            .context("Wrong time.")?;
+        if fields.next().is_some() {
+            return Err(anyhow::anyhow!(
+                "Wrong line: '{}'. line must be a date and an optional time.",
+                line
+            )
+            .into());
+        }
+
+        GenerateOptions {
+            ymd,
+            time: hms.map(TimeOfDay::Hms).or(self.time),
+            ..self.clone()
+        }
+        .base_datetime(provider, precision, output)
+    }
+
+    fn base_time(&self, has_date: bool, now: NaiveTime, precision: Precision) -> NaiveTime {
+        self.time
+            .map(|t| t.naive_time(precision))
+            .unwrap_or_else(|| if has_date { NaiveTime::MIN } else { now })
+    }
+
+    /// Returns the local date of the base, which exists in the timezone.
+    fn base_date<P, Tz>(
+        &self,
+        provider: &P,
+    ) -> Result<Option<NaiveDate>, Box<dyn std::error::Error>>
+    where
+        Tz: TimeZone + Debug,
+        P: DateTimeProvider<Tz>,
+    {
+        let date = if let Some(preset) = self.preset {
+            Some(preset.as_date(provider, self.week_start)?.naive_local())
+        } else {
+            self.ymd
+                .map_or(Ok(None), |ymd| {
+                    ymd.into_date(&provider.timezone())
+                        .map(|date| Some(date.naive_local()))
                 })
-            })
-            .context("Wrong date.")?;
+                .context("Wrong date.")?
+        };
 
-        Ok(date)
+        match self.nth_weekday {
+            // the month is of the date, or of today.
+            Some(nth_weekday) => {
+                let date = date.unwrap_or_else(|| provider.today().naive_local());
+                let nth = nth_weekday.date_in(date.year(), date.month())?;
+                start_of_day("nth weekday", &provider.timezone(), nth).map_err(|_| {
+                    anyhow::anyhow!("Nonexistent date: {} does not exist in the timezone.", nth)
+                })?;
+                Ok(Some(nth))
+            }
+            None => Ok(date),
+        }
     }
 }
 
@@ -84,39 +297,175 @@ impl TryFrom<&ArgMatches<'_>> for GenerateOptions {
     type Error = Box<dyn std::error::Error>;
 
     fn try_from(m: &ArgMatches<'_>) -> Result<Self, Self::Error> {
-        fn delta_item_from(s: &str) -> Result<DeltaItem, Box<dyn std::error::Error>> {
-            Ok(DeltaItem::from_str(s).context("Delta error.")?)
+        fn delta_items_from(s: &str) -> Result<Vec<DeltaItem>, Box<dyn std::error::Error>> {
+            Ok(parse_items(s).context("Delta error.")?)
         }
 
+        let strict_numeric = m.is_present("STRICT_NUMERIC");
         let timestamp = m
             .value_of("BASE_TIMESTAMP")
-            .map(|s| i64::from_str(s).map(Some).context("Wrong timestamp."))
-            .unwrap_or_else(|| Ok(None))?;
+            .map(|s| parse_timestamp(s, strict_numeric))
+            .transpose()?;
         let preset = Preset::find_by_name_opt(m.value_of("BASE")).context("Preset error.")?;
-        let ymd = parse_argv_opt::<Ymd, YmdError>(m.value_of("YMD")).context("Wrong date.")?;
+        let wrap_year = m.is_present("WRAP_YEAR");
+        let ymd = m
+            .value_of("YMD")
+            .map(|s| ymd_from(s, wrap_year))
+            .transpose()?;
         let hms = parse_argv_opt::<Hms, HmsError>(m.value_of("HMS")).context("Wrong time.")?;
-        let truncate =
-            TimeUnit::find_by_name_opt(m.value_of("TRUNCATE")).context("Time unit error.")?;
+        let time = if m.is_present("START_OF_DAY") {
+            Some(TimeOfDay::StartOfDay)
+        } else if m.is_present("END_OF_DAY") {
+            Some(TimeOfDay::EndOfDay)
+        } else {
+            hms.map(TimeOfDay::Hms)
+        };
+        let fold = Fold::find_by_name_opt(m.value_of("FOLD"))
+            .context("Fold error.")?
+            .unwrap_or_default();
+        let input_offset = parse_argv_opt::<Offset, OffsetError>(m.value_of("INPUT_OFFSET"))
+            .context("Wrong input offset.")?
+            .map(Offset::into);
+        let truncate = m
+            .values_of("TRUNCATE")
+            .map(|values| values.map(TimeUnit::find_by_name).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))
+            .context("Time unit error.")?;
+        let snap = parse_argv_opt::<Snap, SnapError>(m.value_of("SNAP")).context("Snap error.")?;
+        let snap_mode = SnapMode::find_by_name_opt(m.value_of("SNAP_MODE"))
+            .context("Snap mode error.")?
+            .unwrap_or(SnapMode::Floor);
+        let align_to = m
+            .value_of("ALIGN_TO")
+            .map(Weekday::from_str)
+            .transpose()
+            .map_err(|_| anyhow::anyhow!("Wrong weekday."))?;
+        let nth_weekday = parse_argv_opt::<NthWeekday, NthWeekdayError>(m.value_of("NTH_WEEKDAY"))
+            .context("Wrong nth weekday.")?;
         let deltas = m
             .values_of("DELTA")
-            .map(|values| values.map(delta_item_from).collect())
-            .unwrap_or_else(|| Ok(Vec::new()))?;
+            .map(|values| values.map(delta_items_from).collect())
+            .unwrap_or_else(|| Ok(Vec::new()))?
+            .concat();
 
         Ok(GenerateOptions {
             timestamp,
+            detect_precision: m.is_present("DETECT_PRECISION"),
+            zero_base: m.is_present("ZERO_BASE"),
             preset,
             ymd,
-            hms,
+            wrap_year,
+            time,
+            fold,
+            input_offset,
             truncate,
+            snap,
+            snap_mode,
+            align_to,
+            nth_weekday,
             deltas,
+            source_date_epoch: None,
+            week_start: WeekStart::Monday,
+        })
+    }
+}
+
+/// Parses the date, with a hint for two-digit years.
+fn ymd_from(s: &str, wrap_year: bool) -> Result<Ymd, Box<dyn std::error::Error>> {
+    Ymd::from_str_with(s, wrap_year).map_err(|e| match e {
+        YmdError::TwoDigitYear(_) => {
+            anyhow::anyhow!("{} use --wrap-year to read it with a pivot.", e).into()
+        }
+        e => anyhow::Error::new(e).context("Wrong date.").into(),
+    })
+}
+
+/// Moves the datetime forward to the weekday, keeping the wall-clock time.
+fn align_to<Tz: TimeZone>(dt: DateTime<Tz>, weekday: Weekday) -> Result<DateTime<Tz>, ApplyError> {
+    let days =
+        i64::from(weekday.num_days_from_monday()) - i64::from(dt.weekday().num_days_from_monday());
+    TimeDeltaBuilder::default()
+        .days(days.rem_euclid(7))
+        .build()
+        .apply_datetime(dt)
+}
+
+#[derive(Debug)]
+enum GenerateInput<Tz: TimeZone> {
+    Base(DateTime<Tz>),
+    /// Bases read from lines of stdin, with their line numbers.
+    Lines(Vec<(usize, Result<DateTime<Tz>, String>)>),
+}
+
+/// Warns units which are not coarser than the previous one, as truncating to them has no effect.
+fn truncate_warnings(units: &[TimeUnit]) -> Vec<String> {
+    units
+        .windows(2)
+        .filter(|w| w[1] >= w[0])
+        .map(|w| {
+            format!(
+                "Warning: truncating to {} after {} has no effect. list units from finer to coarser.",
+                w[1], w[0]
+            )
+        })
+        .collect()
+}
+
+/// A random offset of `--jitter`, within plus or minus `max` ticks of the precision.
+#[derive(Debug)]
+struct Jitter {
+    max: i64,
+    tick: i64,
+    rng: Cell<SplitMix64>,
+}
+
+impl Jitter {
+    /// Creates a jitter of the delta, which must be a fixed length.
+    fn new(s: &str, precision: Precision, seed: u64) -> Result<Jitter, Box<dyn std::error::Error>> {
+        let wrong = || {
+            anyhow::anyhow!(
+                "Wrong jitter: '{}'. jitter must be a fixed length like 30s or 2h, without years and months.",
+                s
+            )
+        };
+        let nanos = parse_items(s)
+            .context("Delta error.")?
+            .into_iter()
+            .fold(TimeDeltaBuilder::default(), |b, d| {
+                d.apply_timedelta_builder(b)
+            })
+            .try_build()?
+            .as_duration()
+            .and_then(|d| d.num_nanoseconds())
+            .ok_or_else(wrong)?;
+        let tick = precision.tick_duration().num_nanoseconds().unwrap_or(1);
+        Ok(Jitter {
+            max: (nanos / tick).checked_abs().ok_or_else(wrong)?,
+            tick,
+            rng: Cell::new(SplitMix64::new(seed)),
         })
     }
+
+    fn apply<Tz: TimeZone>(&self, dt: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let mut rng = self.rng.get();
+        let ticks = rng.between(-self.max, self.max);
+        self.rng.set(rng);
+        // ticks within max never overflow, as max is of the nanoseconds of the jitter.
+        dt.checked_add_signed(chrono::Duration::nanoseconds(ticks * self.tick))
+    }
 }
 
 pub struct GenerateRequest<Tz: TimeZone> {
-    base: DateTime<Tz>,
+    input: GenerateInput<Tz>,
     deltas: Vec<DeltaItem>,
+    jitter: Option<Jitter>,
     precision: Precision,
+    radix: Radix,
+    saturate: bool,
+    clamp_day: bool,
+    trim: bool,
+    epoch_day: bool,
+    skip_invalid: bool,
 }
 
 impl<Tz> GenerateRequest<Tz>
@@ -127,6 +476,9 @@ where
         m: &ArgMatches,
         provider: P,
         precision: Precision,
+        source_date_epoch: Option<i64>,
+        week_start: WeekStart,
+        output: &mut Output,
     ) -> Result<GenerateRequest<Tz>, Box<dyn std::error::Error>>
     where
         P: DateTimeProvider<Tz>,
@@ -134,45 +486,953 @@ where
         let maybe_precision =
             Precision::find_by_name_opt(m.value_of("PRECISION")).context("Precision error.")?;
         if maybe_precision.is_some() {
-            eprintln!("-p PRECISION option is deprecated.");
+            output.notice(format_args!("-p PRECISION option is deprecated."))?;
         }
         let precision = maybe_precision.unwrap_or(precision);
 
-        let generate_options = GenerateOptions::try_from(m)?;
-        let base = generate_options.base_datetime(provider, precision)?;
-        let deltas = generate_options.deltas;
+        let radix = Radix::find_by_name_opt(m.value_of("RADIX"))
+            .context("Radix error.")?
+            .unwrap_or(Radix::Decimal);
+
+        let generate_options = GenerateOptions {
+            source_date_epoch,
+            week_start,
+            ..GenerateOptions::try_from(m)?
+        };
+        for warning in truncate_warnings(&generate_options.truncate) {
+            output.notice(format_args!("{}", warning))?;
+        }
+        if let Some(timestamp) = generate_options.timestamp {
+            if generate_options.detect_precision {
+                let detected = generate_options.timestamp_precision(timestamp, precision);
+                output.notice(format_args!("Detected precision: {}", detected))?;
+            }
+        }
+        output.info(format_args!(
+            "selected base: {}",
+            generate_options.base_description(precision)
+        ))?;
+        if !generate_options.truncate.is_empty() {
+            output.info(format_args!(
+                "truncation units: {}",
+                joined(&generate_options.truncate)
+            ))?;
+        }
+        if !generate_options.deltas.is_empty() {
+            output.info(format_args!("deltas: {}", joined(&generate_options.deltas)))?;
+        }
+        let jitter = match m.value_of("JITTER") {
+            Some(s) => {
+                let seed = match m.value_of("SEED") {
+                    Some(seed) => seed.parse::<u64>()?,
+                    None => SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_nanos() as u64),
+                };
+                output.info(format_args!("seed: {}", seed))?;
+                Some(Jitter::new(s, precision, seed)?)
+            }
+            None => None,
+        };
+        let input = if m.is_present("STDIN") {
+            GenerateInput::Lines(read_bases(
+                io::stdin().lock(),
+                &generate_options,
+                &provider,
+                precision,
+                output,
+            ))
+        } else {
+            GenerateInput::Base(generate_options.base_datetime(&provider, precision, output)?)
+        };
         Ok(GenerateRequest {
-            base,
-            deltas,
+            input,
+            deltas: generate_options.deltas,
+            jitter,
             precision,
+            radix,
+            saturate: m.is_present("SATURATE"),
+            clamp_day: m.is_present("CLAMP_DAY"),
+            trim: !m.is_present("NO_TRIM"),
+            epoch_day: m.is_present("EPOCH_DAY"),
+            skip_invalid: m.is_present("SKIP_INVALID"),
         })
     }
 }
 
-pub fn run<Tz>(request: GenerateRequest<Tz>) -> Result<(), Box<dyn std::error::Error>>
+/// A generated timestamp in `--output json` or `csv`.
+/// An epoch day of `--epoch-day` is a timestamp in the precision `day`.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct GeneratedRecord {
+    timestamp: i64,
+    precision: String,
+}
+
+impl GeneratedRecord {
+    pub fn new(timestamp: i64, precision: &str) -> GeneratedRecord {
+        GeneratedRecord {
+            timestamp,
+            precision: precision.to_string(),
+        }
+    }
+}
+
+impl Record for GeneratedRecord {
+    const HEADER: &'static [&'static str] = &["timestamp", "precision"];
+}
+
+fn joined<T: ToString>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Timestamps, or epoch days with `--epoch-day`, generated by a request.
+#[derive(Debug, PartialEq)]
+pub enum Generated {
+    Value(i64),
+    /// Values of lines of stdin with their line numbers, or the errors of the lines.
+    Lines(Vec<(usize, Result<i64, String>)>),
+}
+
+pub fn run<Tz>(
+    request: GenerateRequest<Tz>,
+    output: &mut Output,
+) -> Result<(), Box<dyn std::error::Error>>
 where
     Tz: TimeZone + Debug,
 {
-    generate(request)
+    let generated = timestamps(&request, output)?;
+    print(&request, generated, output)
 }
 
-fn generate<Tz: TimeZone>(request: GenerateRequest<Tz>) -> Result<(), Box<dyn std::error::Error>> {
-    use anyhow::anyhow;
+/// Generates the timestamps of the request, without printing them. Adjustments of `--saturate` are noticed to the output.
+pub fn timestamps<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    output: &mut Output,
+) -> Result<Generated, Box<dyn std::error::Error>> {
+    Ok(match request.input {
+        GenerateInput::Base(ref base) => Generated::Value(value(request, base.clone(), output)?),
+        GenerateInput::Lines(ref lines) => Generated::Lines(
+            lines
+                .iter()
+                .map(|(n, base)| {
+                    let r = base
+                        .clone()
+                        .and_then(|base| value(request, base, output).map_err(|e| e.to_string()));
+                    (*n, r)
+                })
+                .collect(),
+        ),
+    })
+}
 
-    let delta = request
-        .deltas
+/// Prints the timestamps in the radix. Errors of lines stop printing, or are skipped with `--skip-invalid`.
+fn print<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    generated: Generated,
+    output: &mut Output,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let radix = request.radix;
+    let precision = if request.epoch_day {
+        "day".to_string()
+    } else {
+        request.precision.to_string()
+    };
+    let write = |value: i64, output: &mut Output| {
+        output.record(
+            &GeneratedRecord::new(value, &precision),
+            format_args!("{}\n", radix.format(value)),
+        )
+    };
+    match generated {
+        Generated::Value(value) => write(value, output)?,
+        Generated::Lines(lines) => {
+            let mut summary = Summary::default();
+            for (n, r) in lines {
+                match r {
+                    Ok(value) => {
+                        write(value, output)?;
+                        summary.converted();
+                    }
+                    Err(e) if request.skip_invalid => {
+                        output.warn(format_args!("line {}: {}", n, e))?;
+                        summary.error();
+                    }
+                    Err(e) => return Err(anyhow::anyhow!("line {}: {}", n, e).into()),
+                }
+            }
+            output.flush()?;
+            if !summary.is_empty() {
+                output.notice(format_args!("{}", summary))?;
+            }
+        }
+    }
+    output.flush()?;
+    Ok(())
+}
+
+/// Reads bases from lines. Blank lines are skipped, and reading stops at an IO error.
+fn read_bases<R, P, Tz>(
+    src: R,
+    options: &GenerateOptions,
+    provider: &P,
+    precision: Precision,
+    output: &mut Output,
+) -> Vec<(usize, Result<DateTime<Tz>, String>)>
+where
+    R: BufRead,
+    Tz: TimeZone + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let mut bases = Vec::new();
+    for (i, line) in read_records(src, Delimiter::Whitespace).enumerate() {
+        let base = match line {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => options
+                .line_datetime(provider, precision, line.trim(), output)
+                .map_err(|e| e.to_string()),
+            Err(e) => {
+                bases.push((i + 1, Err(e.to_string())));
+                break;
+            }
+        };
+        bases.push((i + 1, base));
+    }
+    bases
+}
+
+/// Returns the timestamp, or the epoch day of the local date with `--epoch-day`.
+fn value<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    base: DateTime<Tz>,
+    output: &mut Output,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    if request.epoch_day {
+        let day = epoch_day(datetime(request, base, output)?.naive_local().date());
+        output.trace(format_args!("epoch day: {}", day))?;
+        Ok(day)
+    } else {
+        let timestamp = timestamp(request, base, output)?;
+        output.trace(format_args!("timestamp: {}", timestamp.value()))?;
+        Ok(timestamp.value())
+    }
+}
+
+fn timestamp<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    base: DateTime<Tz>,
+    output: &mut Output,
+) -> Result<Timestamp, Box<dyn std::error::Error>> {
+    Ok(Timestamp::from_datetime(
+        datetime(request, base, output)?,
+        request.precision,
+    )?)
+}
+
+/// Applies the delta to the base, as `--saturate` or `--clamp-day` chooses.
+fn apply<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    delta: &TimeDelta,
+    base: DateTime<Tz>,
+) -> Result<(DateTime<Tz>, Vec<Adjustment>), ApplyError> {
+    if request.saturate {
+        delta.apply_datetime_saturating(base)
+    } else if request.clamp_day {
+        delta
+            .apply_datetime_clamping_day(base)
+            .map(|(dt, adjustment)| (dt, adjustment.into_iter().collect()))
+    } else {
+        delta.apply_datetime(base).map(|dt| (dt, Vec::new()))
+    }
+}
+
+/// Applies the deltas to the base.
+/// The deltas are summed up before applied, so each delta is traced with the datetime of the sum so far.
+fn datetime<Tz: TimeZone>(
+    request: &GenerateRequest<Tz>,
+    base: DateTime<Tz>,
+    output: &mut Output,
+) -> Result<DateTime<Tz>, Box<dyn std::error::Error>> {
+    let base = if request.trim {
+        request.precision.trim(base)
+    } else {
+        base
+    };
+
+    let mut builder = TimeDeltaBuilder::default();
+    for d in request.deltas.iter() {
+        builder = d.apply_timedelta_builder(builder);
+        let applied = match builder.try_build() {
+            Ok(delta) => apply(request, &delta, base.clone()).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match applied {
+            Ok((dt, _)) => output.trace(format_args!("delta: {} -> {:?}", d, dt))?,
+            Err(e) => output.trace(format_args!("delta: {} -> {}", d, e))?,
+        }
+    }
+    let delta = builder.try_build()?;
+
+    let base_text = format!("{:?}", base);
+    let (dt, adjustments) = apply(request, &delta, base).map_err(with_hint)?;
+    for adjustment in adjustments {
+        output.notice(format_args!("Adjusted: {}", adjustment))?;
+    }
+    if !request.deltas.is_empty() {
+        output.trace(format_args!("applied: {} -> {:?}", base_text, dt))?;
+    }
+    match request.jitter {
+        Some(ref jitter) => {
+            let jittered = jitter
+                .apply(dt.clone())
+                .ok_or_else(|| anyhow::anyhow!("Jitter error: out of range of datetimes."))?;
+            output.trace(format_args!("jitter: {:?} -> {:?}", dt, jittered))?;
+            Ok(jittered)
+        }
+        None => Ok(dt),
+    }
+}
+
+/// Appends how to resolve the error, if `--saturate` can resolve it.
+fn with_hint(e: ApplyError) -> Box<dyn std::error::Error> {
+    match e {
+        ApplyError::NonexistentDate { .. } => anyhow::anyhow!(
+            "{} use --clamp-day or --saturate to clamp it to the last day of the month.",
+            e
+        )
+        .into(),
+        ApplyError::InvalidComposition(_) => {
+            anyhow::anyhow!("{} use --saturate to resolve it to a valid datetime.", e).into()
+        }
+        ApplyError::OutOfRange(..) => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Duration, TimeZone, Utc, Weekday};
+
+    use std::io;
+
+    use super::{
+        read_bases, run, timestamp, timestamps, truncate_warnings, GenerateInput, GenerateOptions,
+        GenerateRequest, Generated, GeneratedRecord, Jitter,
+    };
+    use crate::cmd::generate::command;
+    use crate::output::Output;
+    use crate::radix::Radix;
+    use crate::verbosity::Verbosity;
+    use ut_cli::find::FindOptions;
+    use ut_cli::precision::Precision;
+    use ut_cli::provider::{FixedNowProvider, FromTimeZone, UtcProvider};
+    use ut_cli::unit::TimeUnit;
+    use ut_cli::week::WeekStart;
+
+    fn try_generate(args: &[&str], precision: Precision) -> Result<i64, String> {
+        try_generate_with(args, precision, None)
+    }
+
+    fn try_generate_with(
+        args: &[&str],
+        precision: Precision,
+        source_date_epoch: Option<i64>,
+    ) -> Result<i64, String> {
+        let m = command("generate", FindOptions::default()).get_matches_from(args);
+        let provider = UtcProvider::from_timezone(Utc);
+        let (mut out, mut err) = (io::sink(), io::sink());
+        let mut output = Output::new(&mut out, &mut err);
+        let request = GenerateRequest::new(
+            &m,
+            provider,
+            precision,
+            source_date_epoch,
+            WeekStart::Monday,
+            &mut output,
+        )
+        .map_err(|e| e.to_string())?;
+        match timestamps(&request, &mut output).map_err(|e| e.to_string())? {
+            Generated::Value(value) => Ok(value),
+            generated => panic!("unexpected output: {:?}", generated),
+        }
+    }
+
+    fn generate(args: &[&str], precision: Precision) -> i64 {
+        try_generate(args, precision).unwrap()
+    }
+
+    #[test]
+    fn record_header() {
+        use crate::record::{assert_header, Record};
+
+        assert_eq!(GeneratedRecord::HEADER, &["timestamp", "precision"]);
+        assert_header(&GeneratedRecord::new(1_560_762_129, "second"));
+    }
+
+    #[test]
+    fn zero_base() {
+        assert_eq!(generate(&["generate", "--zero-base"], Precision::Second), 0);
+        assert_eq!(
+            generate(&["generate", "--zero-base", "-d", "1d"], Precision::Second),
+            86400
+        );
+        assert_eq!(
+            generate(
+                &["generate", "--zero-base", "-d", "1y", "-d", "1ms"],
+                Precision::MilliSecond
+            ),
+            365 * 86400 * 1000 + 1
+        );
+        assert_eq!(
+            generate(
+                &["generate", "--zero-base", "--hms", "01:00:00"],
+                Precision::Second
+            ),
+            3600
+        );
+    }
+
+    #[test]
+    fn epoch_day() {
+        let days = |args: &[&str]| {
+            let args = [&["generate", "--epoch-day"], args].concat();
+            generate(&args, Precision::Second)
+        };
+
+        assert_eq!(days(&["0"]), 0);
+        assert_eq!(days(&["86399"]), 0);
+        assert_eq!(days(&["86400"]), 1);
+        // days before the epoch are floored.
+        assert_eq!(days(&["-1"]), -1);
+        assert_eq!(days(&["-86400"]), -1);
+        assert_eq!(days(&["-86401"]), -2);
+        assert_eq!(days(&["--ymd", "1969-01-01"]), -365);
+        assert_eq!(days(&["--ymd", "2019-06-17", "-d", "1d"]), 18_065);
+        assert_eq!(
+            generate(
+                &["generate", "--epoch-day", "1560762129000"],
+                Precision::MilliSecond
+            ),
+            18_064
+        );
+    }
+
+    #[test]
+    fn source_date_epoch() {
+        let generate = |args: &[&str]| {
+            try_generate_with(args, Precision::Second, Some(1_560_762_129)).unwrap()
+        };
+
+        assert_eq!(generate(&["generate"]), 1_560_762_129);
+        assert_eq!(generate(&["generate", "-d", "1h"]), 1_560_765_729);
+        assert_eq!(generate(&["generate", "-b", "today"]), 1_560_729_600);
+        assert_eq!(generate(&["generate", "-b", "tomorrow"]), 1_560_816_000);
+        assert_eq!(generate(&["generate", "--hms", "00:00:01"]), 1_560_729_601);
+
+        // an explicit base takes precedence.
+        assert_eq!(generate(&["generate", "123"]), 123);
+        assert_eq!(generate(&["generate", "--zero-base"]), 0);
+        assert_eq!(generate(&["generate", "--ymd", "20190101"]), 1_546_300_800);
+    }
+
+    #[test]
+    fn trim() {
+        // the base is read in milliseconds, and printed in seconds.
+        let args = |flag| {
+            vec![
+                "generate",
+                "1560762129700",
+                "--detect-precision",
+                "-d",
+                "500ms",
+                flag,
+            ]
+        };
+        assert_eq!(generate(&args("--trim"), Precision::Second), 1_560_762_129);
+        assert_eq!(
+            generate(&args("--no-trim"), Precision::Second),
+            1_560_762_130
+        );
+
+        // the fraction of the current time is also dropped.
+        let m = command("generate", FindOptions::default())
+            .get_matches_from(vec!["generate", "-d", "500ms"]);
+        let now = Utc.timestamp_opt(1_560_762_129, 700_000_000).unwrap();
+        let (mut out, mut err) = (io::sink(), io::sink());
+        let mut output = Output::new(&mut out, &mut err);
+        let request = GenerateRequest::new(
+            &m,
+            FixedNowProvider::new(now),
+            Precision::MilliSecond,
+            None,
+            WeekStart::Monday,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            timestamp(&request, now, &mut output).unwrap().value(),
+            1_560_762_130_200
+        );
+        let request = GenerateRequest {
+            precision: Precision::Second,
+            ..request
+        };
+        assert_eq!(
+            timestamp(&request, now, &mut output).unwrap().value(),
+            1_560_762_129
+        );
+        let request = GenerateRequest {
+            trim: false,
+            ..request
+        };
+        assert_eq!(
+            timestamp(&request, now, &mut output).unwrap().value(),
+            1_560_762_130
+        );
+    }
+
+    #[test]
+    fn day_boundaries() {
+        let args = |flag| vec!["generate", "--ymd", "20190617", flag];
+
+        assert_eq!(
+            generate(&args("--start-of-day"), Precision::Second),
+            1_560_729_600
+        );
+        assert_eq!(
+            generate(&args("--end-of-day"), Precision::Second),
+            1_560_815_999
+        );
+        assert_eq!(
+            generate(&args("--start-of-day"), Precision::MilliSecond),
+            1_560_729_600_000
+        );
+        assert_eq!(
+            generate(&args("--end-of-day"), Precision::MilliSecond),
+            1_560_815_999_999
+        );
+        assert_eq!(
+            generate(&args("--end-of-day"), Precision::MicroSecond),
+            1_560_815_999_999_999
+        );
+        assert_eq!(
+            generate(
+                &["generate", "--zero-base", "--end-of-day", "-d", "1ms"],
+                Precision::MilliSecond
+            ),
+            86_400_000
+        );
+    }
+
+    #[test]
+    fn input_offset() {
+        let utc = generate(
+            &["generate", "--ymd", "20190617", "--hms", "180209"],
+            Precision::Second,
+        );
+        assert_eq!(utc, 1_560_794_529);
+
+        let args = |offset| {
+            vec![
+                "generate",
+                "--ymd",
+                "20190617",
+                "--hms",
+                "180209",
+                "--input-offset",
+                offset,
+            ]
+        };
+        assert_eq!(generate(&args("+9"), Precision::Second), utc - 9 * 3600);
+        assert_eq!(generate(&args("-07:00"), Precision::Second), utc + 7 * 3600);
+        assert_eq!(generate(&args("0"), Precision::Second), utc);
+
+        // the date of the wall-clock is also interpreted in the input offset.
+        assert_eq!(
+            generate(
+                &[
+                    "generate",
+                    "--zero-base",
+                    "--hms",
+                    "08:00:00",
+                    "--input-timezone",
+                    "+9"
+                ],
+                Precision::Second
+            ),
+            -3600
+        );
+    }
+
+    #[test]
+    fn multiple_truncate_units() {
+        let args = |units: &[&'static str]| {
+            let mut args = vec!["generate", "--ymd", "20190617", "--hms", "112233"];
+            for unit in units {
+                args.push("-t");
+                args.push(unit);
+            }
+            args
+        };
+        let generate = |units: &[&'static str]| generate(&args(units), Precision::Second);
+
+        assert_eq!(generate(&["hour"]), 1_560_769_200);
+        assert_eq!(generate(&["hour", "day"]), 1_560_729_600);
+        assert_eq!(generate(&["day", "hour"]), 1_560_729_600);
+        assert_eq!(generate(&["minute", "hour", "month"]), 1_559_347_200);
+
+        assert!(truncate_warnings(&[TimeUnit::Hour, TimeUnit::Day, TimeUnit::Month]).is_empty());
+        assert_eq!(
+            truncate_warnings(&[TimeUnit::Day, TimeUnit::Hour]),
+            vec!["Warning: truncating to hour after day has no effect. list units from finer to coarser."]
+        );
+        assert_eq!(
+            truncate_warnings(&[TimeUnit::Day, TimeUnit::Day, TimeUnit::Year]).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn nth_weekday() {
+        let generate = |args: &[&str]| {
+            let mut all = vec!["generate"];
+            all.extend_from_slice(args);
+            try_generate(&all, Precision::Second).map(|timestamp| Utc.timestamp(timestamp, 0))
+        };
+
+        // the 3rd monday and the last friday of June 2019.
+        assert_eq!(
+            generate(&["--ymd", "20190601", "--nth-weekday", "3monday"]),
+            Ok(Utc.ymd(2019, 6, 17).and_hms(0, 0, 0))
+        );
+        assert_eq!(
+            generate(&["--ymd", "20190630", "--nth-weekday", "-1fri"]),
+            Ok(Utc.ymd(2019, 6, 28).and_hms(0, 0, 0))
+        );
+        assert_eq!(
+            generate(&[
+                "--ymd",
+                "20190601",
+                "--nth-weekday",
+                "-1friday",
+                "--hms",
+                "112233",
+                "-d",
+                "1d"
+            ]),
+            Ok(Utc.ymd(2019, 6, 29).and_hms(11, 22, 33))
+        );
+        assert_eq!(
+            generate(&["--ymd", "20190201", "--nth-weekday", "5fri"]),
+            Err("Nonexistent date: there is no 5th friday in 2019-02.".to_string())
+        );
+    }
+
+    #[test]
+    fn align_to_weekday() {
+        let generate = |args: &[&str]| {
+            let mut all = vec!["generate", "--hms", "112233"];
+            all.extend_from_slice(args);
+            let timestamp = generate(&all, Precision::Second);
+            Utc.timestamp(timestamp, 0)
+        };
+
+        // 2019-06-19 is a wednesday.
+        let dt = generate(&["--ymd", "20190619", "--align-to", "monday"]);
+        assert_eq!(dt, Utc.ymd(2019, 6, 24).and_hms(11, 22, 33));
+        assert_eq!(dt.weekday(), Weekday::Mon);
+
+        // weekly mondays, advanced by 7 days.
+        for (i, delta) in ["0d", "7d", "14d", "21d"].iter().enumerate() {
+            let dt = generate(&["--ymd", "20190619", "--align-to", "mon", "-d", delta]);
+            assert_eq!(dt.weekday(), Weekday::Mon);
+            assert_eq!(
+                dt,
+                Utc.ymd(2019, 6, 24).and_hms(11, 22, 33) + Duration::weeks(i as i64)
+            );
+        }
+
+        // a base on the weekday is kept.
+        assert_eq!(
+            generate(&["--ymd", "20190624", "--align-to", "Mon"]),
+            Utc.ymd(2019, 6, 24).and_hms(11, 22, 33)
+        );
+        assert_eq!(
+            generate(&["--ymd", "20190629", "--align-to", "sun"]),
+            Utc.ymd(2019, 6, 30).and_hms(11, 22, 33)
+        );
+    }
+
+    #[test]
+    fn read_lines() {
+        use std::convert::TryFrom;
+
+        let m = command("generate", FindOptions::default())
+            .get_matches_from(vec!["generate", "--stdin", "-t", "hour"]);
+        let options = GenerateOptions::try_from(&m).unwrap();
+        let provider = UtcProvider::from_timezone(Utc);
+        let input = "2019-06-17 11:22:33\r\n\n2019/06/18\n20190619 112233\n2019-02-30\nx 1 2\n";
+
+        let (mut out, mut err) = (io::sink(), io::sink());
+        let mut output = Output::new(&mut out, &mut err);
+        let bases = read_bases(
+            input.as_bytes(),
+            &options,
+            &provider,
+            Precision::Second,
+            &mut output,
+        )
         .into_iter()
-        .fold(TimeDeltaBuilder::default(), |b, d| {
-            d.apply_timedelta_builder(b)
-        })
-        .build();
+        .map(|(n, r)| (n, r.map(|dt| dt.timestamp())))
+        .collect::<Vec<_>>();
+        assert_eq!(bases[0], (1, Ok(1_560_769_200)));
+        assert_eq!(bases[1], (3, Ok(1_560_816_000)));
+        assert_eq!(bases[2], (4, Ok(1_560_942_000)));
+        assert_eq!(bases[3].0, 5);
+        assert!(bases[3].1.is_err());
+        assert_eq!(bases[4].0, 6);
+        assert!(bases[4].1.is_err());
+        assert_eq!(bases.len(), 5);
+    }
+
+    #[test]
+    fn wrap_year() {
+        use std::convert::TryFrom;
+
+        let generate = |ymd: &str| {
+            generate(
+                &["generate", "--wrap-year", "--ymd", ymd],
+                Precision::Second,
+            )
+        };
+        assert_eq!(generate("19-06-17"), 1_560_729_600);
+        assert_eq!(generate("68-01-01"), 3_092_601_600);
+        assert_eq!(generate("69-01-01"), -31_536_000);
+        assert_eq!(generate("2019-06-17"), 1_560_729_600);
+
+        let m = command("generate", FindOptions::default())
+            .get_matches_from(vec!["generate", "--ymd", "19-06-17"]);
+        assert_eq!(
+            GenerateOptions::try_from(&m).unwrap_err().to_string(),
+            "Wrong year: '19-06-17'. two-digit years are ambiguous without a pivot. use --wrap-year to read it with a pivot."
+        );
+        assert!(command("generate", FindOptions::default())
+            .get_matches_from_safe(vec!["generate", "--wrap-year", "--ymd", "19-02-29"])
+            .is_err());
+
+        // lines of stdin are read with the pivot too.
+        let m = command("generate", FindOptions::default()).get_matches_from(vec![
+            "generate",
+            "--stdin",
+            "--wrap-year",
+        ]);
+        let options = GenerateOptions::try_from(&m).unwrap();
+        let provider = UtcProvider::from_timezone(Utc);
+        let (mut out, mut err) = (io::sink(), io::sink());
+        let bases = read_bases(
+            "69/7/20 20:17:40\n".as_bytes(),
+            &options,
+            &provider,
+            Precision::Second,
+            &mut Output::new(&mut out, &mut err),
+        );
+        assert_eq!(
+            bases[0].1.as_ref().map(|dt| dt.timestamp()),
+            Ok(-14_182_940)
+        );
+    }
 
-    match delta.apply_datetime(request.base) {
-        Some(dt) => {
-            println!("{}", request.precision.to_timestamp(dt));
+    #[test]
+    fn jitter() {
+        let jitter = Jitter::new("-1s", Precision::MilliSecond, 1).unwrap();
+        assert_eq!((jitter.max, jitter.tick), (1000, 1_000_000));
+        let jitter = Jitter::new("P1DT1S", Precision::Second, 1).unwrap();
+        assert_eq!((jitter.max, jitter.tick), (86_401, 1_000_000_000));
+        // finer than the precision.
+        let jitter = Jitter::new("999ms", Precision::Second, 1).unwrap();
+        let dt = Utc.timestamp_opt(1_560_762_129, 0).unwrap();
+        assert_eq!(jitter.apply(dt), Some(dt));
+
+        assert!(Jitter::new("1y", Precision::Second, 1).is_err());
+        // too large for nanoseconds.
+        assert!(Jitter::new("110000d", Precision::Second, 1).is_err());
+
+        let args = ["generate", "--ymd", "2019-06-17", "--jitter", "1h"];
+        let seeded = |seed| {
+            let args = [&args[..], &["--seed", seed]].concat();
+            generate(&args, Precision::Second)
+        };
+        assert_eq!(seeded("1"), seeded("1"));
+        assert!((1_560_729_600 - 3600..=1_560_729_600 + 3600).contains(&seeded("1")));
+    }
+
+    #[test]
+    fn generate_lines() {
+        use std::convert::TryFrom;
+
+        let m = command("generate", FindOptions::default())
+            .get_matches_from(vec!["generate", "--stdin", "-d", "1d"]);
+        let options = GenerateOptions::try_from(&m).unwrap();
+        let provider = UtcProvider::from_timezone(Utc);
+        let input = "2019-06-17\n2019-02-30\n\n2019-06-18\n";
+        let request = GenerateRequest {
+            input: GenerateInput::Lines(read_bases(
+                input.as_bytes(),
+                &options,
+                &provider,
+                Precision::Second,
+                &mut Output::new(&mut io::sink(), &mut io::sink()),
+            )),
+            deltas: options.deltas,
+            jitter: None,
+            precision: Precision::MilliSecond,
+            radix: Radix::Decimal,
+            saturate: false,
+            clamp_day: false,
+            trim: true,
+            epoch_day: false,
+            skip_invalid: false,
+        };
+
+        let (mut out, mut err) = (io::sink(), io::sink());
+        match timestamps(&request, &mut Output::new(&mut out, &mut err)).unwrap() {
+            Generated::Lines(lines) => {
+                assert_eq!(lines.len(), 3);
+                assert_eq!(lines[0], (1, Ok(1_560_816_000_000)));
+                assert_eq!(lines[1].0, 2);
+                assert!(lines[1].1.is_err());
+                assert_eq!(lines[2], (4, Ok(1_560_902_400_000)));
+            }
+            generated => panic!("unexpected output: {:?}", generated),
         }
-        None => Err(anyhow!("Time unit error."))?,
     }
 
-    Ok(())
+    #[test]
+    fn strict_numeric() {
+        assert_eq!(generate(&["generate", "00123"], Precision::Second), 123);
+        assert_eq!(
+            try_generate(
+                &["generate", "--strict-numeric", "-d", "1s", "123"],
+                Precision::Second
+            ),
+            Ok(124)
+        );
+        assert_eq!(
+            try_generate(&["generate", "--strict-numeric", "00123"], Precision::Second),
+            Err(
+                "Wrong timestamp: '00123'. timestamp must not have leading zeros with --strict-numeric."
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn saturate() {
+        let args = ["generate", "--ymd", "20190131", "-d", "1mon"];
+        assert_eq!(
+            try_generate(&args, Precision::Second),
+            Err("Nonexistent date: 2019-02-31 does not exist. use --clamp-day or --saturate to clamp it to the last day of the month.".to_string())
+        );
+
+        let args = ["generate", "--ymd", "20190131", "-d", "1mon", "--saturate"];
+        assert_eq!(generate(&args, Precision::Second), 1_551_312_000);
+    }
+
+    #[test]
+    fn clamp_day() {
+        let clamped = |ymd, delta| {
+            let args = ["generate", "--ymd", ymd, "-d", delta, "--clamp-day"];
+            try_generate(&args, Precision::Second)
+        };
+        // 2019-02-28, 2020-02-29, 2021-02-28 and 2024-02-29.
+        assert_eq!(clamped("20190131", "1mon"), Ok(1_551_312_000));
+        assert_eq!(clamped("20200131", "1mon"), Ok(1_582_934_400));
+        assert_eq!(clamped("20200229", "1y"), Ok(1_614_470_400));
+        assert_eq!(clamped("20200229", "4y"), Ok(1_709_164_800));
+        // valid days are kept.
+        assert_eq!(clamped("20190115", "1mon"), Ok(1_550_188_800));
+
+        let args = ["generate", "--ymd", "20200229", "-d", "1y"];
+        assert_eq!(
+            try_generate(&args, Precision::Second),
+            Err("Nonexistent date: 2021-02-29 does not exist. use --clamp-day or --saturate to clamp it to the last day of the month.".to_string())
+        );
+        let args = ["generate", "-d", "1mon", "--clamp-day", "--saturate"];
+        assert!(command("generate", FindOptions::default())
+            .get_matches_from_safe(args.iter())
+            .is_err());
+    }
+
+    #[test]
+    fn run_output() {
+        let run_with = |args: &[&str], quiet: bool| {
+            let m = command("generate", FindOptions::default()).get_matches_from(args);
+            let (mut out, mut err) = (Vec::new(), Vec::new());
+            let mut output = Output::new(&mut out, &mut err).verbosity(Verbosity::new(0, quiet));
+            let provider = UtcProvider::from_timezone(Utc);
+            let request = GenerateRequest::new(
+                &m,
+                provider,
+                Precision::Second,
+                None,
+                WeekStart::Monday,
+                &mut output,
+            )
+            .unwrap();
+            run(request, &mut output).unwrap();
+            (
+                String::from_utf8(out).unwrap(),
+                String::from_utf8(err).unwrap(),
+            )
+        };
+
+        let args = ["generate", "--ymd", "20190131", "-d", "1mon", "--saturate"];
+        assert_eq!(
+            run_with(&args, false),
+            (
+                "1551312000\n".to_string(),
+                "Adjusted: day 31 does not exist in the month, clamped to day 28.\n".to_string()
+            )
+        );
+        assert_eq!(
+            run_with(&args, true),
+            ("1551312000\n".to_string(), "".to_string())
+        );
+        assert_eq!(
+            run_with(
+                &[
+                    "generate",
+                    "--radix",
+                    "hex",
+                    "--detect-precision",
+                    "1560762129000"
+                ],
+                false
+            ),
+            (
+                "0x5D075711\n".to_string(),
+                "Detected precision: millisecond\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn accumulated_deltas() {
+        let mut args = vec!["generate", "--zero-base"];
+        args.extend(["-d", "1000000000s"].iter().cycle().take(6));
+        assert_eq!(try_generate(&args, Precision::Second), Ok(3_000_000_000));
+
+        let args = ["generate", "--zero-base", "-d", "2147483647ms", "-d", "1ms"];
+        assert_eq!(
+            try_generate(&args, Precision::MilliSecond),
+            Ok(2_147_483_648)
+        );
+
+        let args = ["generate", "--zero-base", "-d", "2147483647d", "-d", "1d"];
+        assert!(try_generate(&args, Precision::Second)
+            .unwrap_err()
+            .starts_with("Cannot apply delta '2147483648d'"));
+    }
 }
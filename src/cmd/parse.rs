@@ -1,5 +1,5 @@
 mod app;
 mod run;
 
-pub use app::command;
+pub use app::{command, VALUE_OPTIONS};
 pub use run::{run, ParseRequest};
@@ -1,28 +1,303 @@
-use crate::find::FindByName;
-use crate::precision::Precision;
-use crate::validate::IntoValidationError;
+use crate::extract::{DigitRange, DigitRangeError};
+use crate::format::{validate_format_argv, FormatStyle, FormatStyleError};
+use crate::locale::{Locale, LocaleError};
+use crate::template::{Placeholder, Template, TemplateError};
+use crate::zone::{ZoneError, Zones};
 use clap::{App, AppSettings, Arg, SubCommand};
+use lazy_static::lazy_static;
+use ut_cli::find::{FindOptions, PossibleNames};
+use ut_cli::precision::{Precision, PrecisionError};
+use ut_cli::validate::{validate_argv, validate_argv_by_name, validate_timestamp};
 
-pub fn command(name: &str) -> App<'static, 'static> {
+lazy_static! {
+    static ref PRECISION_HELP: String =
+        Precision::help_with_possible_names("[Deprecated] Set a precision of the timestamp.");
+    static ref STYLE_HELP: String = FormatStyle::help_with_possible_names(
+        "Set the datetime format by a name, instead of strftime specifiers."
+    );
+    static ref TEMPLATE_HELP: String = format!(
+        "Print the TEMPLATE with placeholders filled, like `{{iso}}\\t{{weekday}}`, instead of the datetime. \
+         `{{{{` and `}}}}` are braces. [placeholders: {}]",
+        Placeholder::possible_names().join(", ")
+    );
+}
+
+/// Options of the subcommand which take a value, including aliases, so `-0` following them is the value.
+/// Keep in sync with the arguments below.
+pub const VALUE_OPTIONS: &[&str] = &[
+    "--timestamp",
+    "--key",
+    "--inject",
+    "--digits",
+    "-f",
+    "--format",
+    "--style",
+    "--format-preset",
+    "--template",
+    "--locale",
+    "--zone-label",
+    "--offset-output",
+    "--compare-zones",
+    "-p",
+    "--precision",
+];
+
+pub fn command(name: &str, options: FindOptions) -> App<'static, 'static> {
     SubCommand::with_name(name)
         .about("Parse a unix timestamp and print it in human readable format.")
         .settings(&[AppSettings::AllowNegativeNumbers, AppSettings::ColoredHelp])
         .arg(
             Arg::with_name("TIMESTAMP")
                 .help("Set a timestamp to parse.")
-                .validator(|s| s.parse::<i64>().map(|_| ()).map_err(|e| format!("{:?}", e)))
-                .allow_hyphen_values(true),
+                .validator(validate_timestamp)
+                .allow_hyphen_values(true)
+                .conflicts_with_all(&["TIMESTAMP_OPTION", "EXTRACT", "JSON_INPUT"]),
+        )
+        .arg(
+            Arg::with_name("TIMESTAMP_OPTION")
+                .value_name("TS")
+                .help("Set a timestamp to parse. Alternative to TIMESTAMP.")
+                .long("timestamp")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_timestamp)
+                .conflicts_with_all(&["EXTRACT", "JSON_INPUT"]),
+        )
+        .arg(
+            Arg::with_name("STRICT_NUMERIC")
+                .help("Reject timestamps with leading zeros, like `00123`.")
+                .long("strict-numeric"),
+        )
+        .arg(
+            Arg::with_name("EXTRACT")
+                .help("Read lines from stdin, and replace timestamps in them with datetimes.")
+                .long("extract")
+                .alias("inline"),
+        )
+        .arg(
+            Arg::with_name("JSON_INPUT")
+                .help("Read JSON Lines from stdin, and parse the timestamp in the KEY of each object.")
+                .long("json-input")
+                .requires("KEY")
+                .conflicts_with_all(&["EXTRACT", "PRETTY"]),
+        )
+        .arg(
+            Arg::with_name("KEY")
+                .help("Set the KEY of timestamps in JSON Lines. Only top-level keys are supported, `a.b` is a key as it is.")
+                .long("key")
+                .takes_value(true)
+                .requires("JSON_INPUT"),
+        )
+        .arg(
+            Arg::with_name("INJECT")
+                .value_name("FIELD")
+                .help("Print each object with the datetime in the FIELD, instead of the datetime only.")
+                .long("inject")
+                .takes_value(true)
+                .requires("JSON_INPUT"),
+        )
+        .arg(
+            Arg::with_name("JSON_ARRAY")
+                .help("Print the outputs of --extract or --json-input as a JSON array. The outputs are buffered until the end of the input.")
+                .long("json-array")
+                .conflicts_with_all(&["TIMESTAMP", "TIMESTAMP_OPTION", "PRETTY"]),
+        )
+        .arg(
+            Arg::with_name("EPOCH_DAY")
+                .help("Read the timestamp as the number of days from 1970-01-01, and print the date. [default format: %Y-%m-%d]")
+                .long("epoch-day"),
+        )
+        .arg(
+            Arg::with_name("ASSUME_LOCAL")
+                .help("Fix up a timestamp of a local wall-clock which was stored as if it were UTC: keep the wall-clock of the timestamp in UTC, and read it in the timezone. The instant is shifted by the offset.")
+                .long("assume-local")
+                .conflicts_with("EPOCH_DAY"),
+        )
+        .arg(
+            Arg::with_name("NULL")
+                .help("Split input by NUL instead of whitespace and newlines, and terminate outputs with NUL.")
+                .short("0")
+                .long("null"),
+        )
+        .arg(
+            Arg::with_name("DIGITS")
+                .value_name("MIN-MAX")
+                .help("Set the number of digits of timestamps to extract. [default: by precision, like 9-10 for second]")
+                .long("digits")
+                .takes_value(true)
+                .requires("EXTRACT")
+                .validator(validate_argv::<DigitRange, DigitRangeError>),
+        )
+        .arg(
+            Arg::with_name("FORMAT")
+                .help("Set the datetime format in chrono's strftime specifiers.")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .validator(validate_format_argv),
+        )
+        .arg(
+            Arg::with_name("STYLE")
+                .help(STYLE_HELP.as_str())
+                .long("style")
+                .alias("format-preset")
+                .takes_value(true)
+                .validator(validate_argv_by_name::<FormatStyle, FormatStyleError>(options))
+                .conflicts_with("FORMAT"),
+        )
+        .arg(
+            Arg::with_name("SECONDS_OF_DAY")
+                .help("Print the seconds past midnight in the timezone, like `3600` for 01:00:00, instead of the datetime.")
+                .long("seconds-of-day")
+                .conflicts_with_all(&["FORMAT", "STYLE", "PRETTY", "COMPARE_ZONES", "EPOCH_DAY"]),
+        )
+        .arg(
+            Arg::with_name("MILLISECONDS_OF_DAY")
+                .help("Print the milliseconds past midnight in the timezone, like `3600500` for 01:00:00.500, instead of the datetime.")
+                .long("milliseconds-of-day")
+                .alias("millis-of-day")
+                .conflicts_with_all(&[
+                    "SECONDS_OF_DAY",
+                    "FORMAT",
+                    "STYLE",
+                    "PRETTY",
+                    "COMPARE_ZONES",
+                    "EPOCH_DAY",
+                ]),
+        )
+        .arg(
+            Arg::with_name("TEMPLATE")
+                .help(TEMPLATE_HELP.as_str())
+                .long("template")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_argv::<Template, TemplateError>)
+                .conflicts_with_all(&[
+                    "PRETTY",
+                    "COMPARE_ZONES",
+                    "SECONDS_OF_DAY",
+                    "MILLISECONDS_OF_DAY",
+                ]),
+        )
+        .arg(
+            Arg::with_name("LIST_FORMAT_SPECIFIERS")
+                .help("Print the list of format specifiers, then exit.")
+                .long("list-format-specifiers")
+                .alias("help-format"),
+        )
+        .arg(
+            Arg::with_name("LOCALE")
+                .help("Print weekday and month names (%a, %A, %b and %B) in the LOCALE, like `ja` or `de_DE`. [default: en]")
+                .long("locale")
+                .takes_value(true)
+                .validator(validate_argv::<Locale, LocaleError>),
+        )
+        .arg(
+            Arg::with_name("PRETTY")
+                .help("Print the datetime in local and utc, RFC3339, the weekday and the time from now.")
+                .long("pretty")
+                .conflicts_with_all(&["EXTRACT"]),
+        )
+        .arg(
+            Arg::with_name("ZONE_LABEL")
+                .value_name("LABEL")
+                .help("Print the given label in place of the timezone (%Z), like JST.")
+                .long("zone-label")
+                .alias("offset-output")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("COMPARE_ZONES")
+                .value_name("ZONES")
+                .help("Print the datetime in each of the comma-separated ZONES, like `UTC,+09:00,local`.")
+                .long("compare-zones")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_argv::<Zones, ZoneError>)
+                .conflicts_with_all(&["EXTRACT", "JSON_INPUT", "PRETTY"]),
         )
         .arg(
             Arg::with_name("PRECISION")
-                .help("[Deprecated] Set a precision of the timestamp.")
+                .help(PRECISION_HELP.as_str())
                 .short("p")
                 .long("precision")
                 .takes_value(true)
-                .validator(|s| {
-                    Precision::find_by_name(&s)
-                        .map(|_| ())
-                        .map_err(|e| e.into_validation_error())
-                }),
+                .validator(validate_argv_by_name::<Precision, PrecisionError>(options)),
         )
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::AppSettings;
+
+    use super::command;
+    use ut_cli::find::FindOptions;
+
+    #[test]
+    fn timestamp_option() {
+        let m = command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "--timestamp", "-1234"])
+            .unwrap();
+        assert_eq!(m.value_of("TIMESTAMP_OPTION"), Some("-1234"));
+        assert_eq!(m.value_of("TIMESTAMP"), None);
+
+        let m = command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "--timestamp=-1234"])
+            .unwrap();
+        assert_eq!(m.value_of("TIMESTAMP_OPTION"), Some("-1234"));
+
+        let m = command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "-p", "ms", "--timestamp", "1234"])
+            .unwrap();
+        assert_eq!(m.value_of("TIMESTAMP_OPTION"), Some("1234"));
+    }
+
+    #[test]
+    fn timestamp_option_conflicts_with_positional() {
+        let r = command("parse", FindOptions::default()).get_matches_from_safe(vec![
+            "parse",
+            "--timestamp",
+            "-1",
+            "2",
+        ]);
+        assert!(r.is_err());
+
+        let r = command("parse", FindOptions::default()).get_matches_from_safe(vec![
+            "parse",
+            "--timestamp",
+            "abc",
+        ]);
+        assert!(r.is_err());
+    }
+
+    fn error_message(args: Vec<&str>) -> String {
+        let e = command("parse", FindOptions::default())
+            .setting(AppSettings::ColorNever)
+            .get_matches_from_safe(args)
+            .unwrap_err();
+        e.message.split("\n\n").next().unwrap().to_string()
+    }
+
+    #[test]
+    fn validation_messages() {
+        assert_eq!(
+            error_message(vec!["parse", "abc"]),
+            "error: Invalid value for '<TIMESTAMP>': Wrong timestamp: timestamp must be an integer.
+    input: 'abc'
+    accepted: seconds or milliseconds since the epoch, like `1561302000` or `0x5D0775D1`"
+        );
+        assert_eq!(
+            error_message(vec!["parse", "-f", "%Y %Q", "0"]),
+            "error: Invalid value for '--format <FORMAT>': Wrong datetime format: unknown specifier '%Q' at index 3.
+    input: '%Y %Q'
+    accepted: text with chrono's strftime specifiers, like `%Y-%m-%d %H:%M:%S`"
+        );
+        assert_eq!(
+            error_message(vec!["parse", "-p", "sec0nd", "0"]),
+            "error: Invalid value for '--precision <PRECISION>': Wrong precision: no matching name. did you mean 'second'?
+    input: 'sec0nd'
+    accepted: a possible name, or its unique prefix
+    possible names: [second, millisecond, microsecond, nanosecond]"
+        );
+    }
+}
@@ -1,21 +1,141 @@
+use std::cell::Cell;
 use std::fmt::{Debug, Display};
-use std::io;
+use std::io::{self, BufRead, IsTerminal};
+use std::str::FromStr;
 
 use anyhow::Context;
-use chrono::{Offset, TimeZone};
+use chrono::{DateTime, Datelike, Offset, SecondsFormat, TimeZone, Timelike, Utc};
 use clap::ArgMatches;
+use serde::Serialize;
 
-use crate::find::FindByName;
-use crate::precision::Precision;
-use crate::provider::DateTimeProvider;
-use crate::read::{read_next, ReadError};
+use crate::extract::{DigitRange, Extractor};
+use crate::format::{replace_zone_name, validate_format, FormatStyle};
+use crate::json::{quote, JsonError, JsonObject};
+use crate::locale::Locale;
+use crate::output::Output;
+use crate::record::{OutputFormat, Record};
+use crate::summary::Summary;
+use crate::template::{Placeholder, Template};
+use crate::zone::Zones;
+use ut_cli::datetime::{from_epoch_day, resolve_local_datetime};
+use ut_cli::find::FindByName;
+use ut_cli::precision::{Precision, PrecisionError};
+use ut_cli::provider::{start_of_day, DateTimeProvider};
+use ut_cli::read::{read_records, read_tokens_with, Delimiter, ReadError};
+use ut_cli::timedelta::TimeDelta;
+use ut_cli::timestamp::Timestamp;
+use ut_cli::validate::{parse_timestamp, TimestampError};
+
+/// Years beyond this are likely from timestamps in a finer precision.
+const SUSPICIOUS_YEAR: i32 = 5000;
+
+/// A time of the day to print as a number, instead of the datetime.
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum OfDay {
+    Seconds,
+    Milliseconds,
+}
+
+impl OfDay {
+    fn value<T: Timelike>(self, time: &T) -> u64 {
+        let seconds = u64::from(time.num_seconds_from_midnight());
+        match self {
+            OfDay::Seconds => seconds,
+            // a leap second has 1_000_000_000 or more nanoseconds.
+            OfDay::Milliseconds => seconds * 1000 + u64::from(time.nanosecond() / 1_000_000),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct ParseRequest<P> {
     provider: P,
     precision: Precision,
     datetime_format: String,
-    timestamp: i64,
+    template: Option<Template>,
+    pretty: bool,
+    json_array: bool,
+    compare_zones: Option<Zones>,
+    locale: Locale,
+    epoch_day: bool,
+    assume_local: bool,
+    of_day: Option<OfDay>,
+    delimiter: Delimiter,
+    input: ParseInput,
+}
+
+/// A timestamp as it is read, before the precision is applied. Fractional ones like `1560762129.5` are accepted from stdin.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+enum RawTimestamp {
+    Integer(i64),
+    Fractional(f64),
+}
+
+impl Display for RawTimestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RawTimestamp::Integer(t) => write!(f, "{}", t),
+            RawTimestamp::Fractional(t) => write!(f, "{}", t),
+        }
+    }
+}
+
+impl RawTimestamp {
+    /// Parses the text as an integer first, then as a fractional number.
+    fn parse(s: &str, strict: bool) -> Result<RawTimestamp, TimestampError> {
+        parse_timestamp(s, strict)
+            .map(RawTimestamp::Integer)
+            .or_else(|e| RawTimestamp::parse_fractional(s).ok_or(e))
+    }
+
+    fn parse_fractional(s: &str) -> Option<RawTimestamp> {
+        if !s.contains('.') {
+            return None;
+        }
+        s.parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(RawTimestamp::Fractional)
+    }
+
+    fn datetime<Tz: TimeZone>(
+        self,
+        precision: Precision,
+        tz: Tz,
+    ) -> Result<DateTime<Tz>, PrecisionError> {
+        match self {
+            RawTimestamp::Integer(t) => Timestamp::new(t, precision).to_datetime(tz),
+            RawTimestamp::Fractional(t) => precision.parse_fractional_timestamp(tz, t),
+        }
+    }
+}
+
+/// A parsed timestamp in `--output json` or `csv`, with the datetime as it is printed in plain.
+#[derive(Debug, PartialEq, Serialize)]
+struct ParsedRecord {
+    timestamp: RawTimestamp,
+    datetime: String,
+}
+
+impl Record for ParsedRecord {
+    const HEADER: &'static [&'static str] = &["timestamp", "datetime"];
+}
+
+/// Flags which print several lines or transform lines, so their outputs are not records.
+const PLAIN_ONLY_FLAGS: &[(&str, &str)] = &[
+    ("PRETTY", "--pretty"),
+    ("COMPARE_ZONES", "--compare-zones"),
+    ("EXTRACT", "--extract"),
+    ("JSON_INPUT", "--json-input"),
+    ("JSON_ARRAY", "--json-array"),
+];
+
+#[derive(Debug)]
+enum ParseInput {
+    Timestamp(RawTimestamp),
+    Extract(DigitRange),
+    JsonLines { key: String, inject: Option<String> },
 }
 
 impl<P> ParseRequest<P> {
@@ -24,46 +144,1261 @@ impl<P> ParseRequest<P> {
         provider: P,
         precision: Precision,
         datetime_format: Option<&str>,
+        compare_zones: Option<&str>,
+        output: &mut Output,
     ) -> Result<ParseRequest<P>, Box<dyn std::error::Error>> {
-        let timestamp = get_timestamp(m.value_of("TIMESTAMP"))?;
         let maybe_precision =
             Precision::find_by_name_opt(m.value_of("PRECISION")).context("Precision error.")?;
         if maybe_precision.is_some() {
-            eprintln!("-p PRECISION option is deprecated.");
+            output.notice(format_args!("-p PRECISION option is deprecated."))?;
         }
         let precision = maybe_precision.unwrap_or(precision);
-        let datetime_format = datetime_format
-            .unwrap_or_else(|| precision.preferred_format())
-            .to_string();
+        let epoch_day = m.is_present("EPOCH_DAY");
+        // `--style` is validated while parsing arguments.
+        let style = FormatStyle::find_by_name_opt(m.value_of("STYLE"))?;
+        let datetime_format = m
+            .value_of("FORMAT")
+            .or_else(|| style.map(FormatStyle::format))
+            .or(datetime_format)
+            .unwrap_or_else(|| {
+                if epoch_day {
+                    "%Y-%m-%d"
+                } else {
+                    precision.preferred_format()
+                }
+            });
+        validate_format(datetime_format).context("Wrong datetime format.")?;
+        let datetime_format = match m.value_of("ZONE_LABEL") {
+            Some(label) => replace_zone_name(datetime_format, label),
+            None => datetime_format.to_string(),
+        };
 
+        // `--template` is validated while parsing arguments.
+        let template = m
+            .value_of("TEMPLATE")
+            .map(Template::from_str)
+            .transpose()
+            .context("Wrong template.")?;
+
+        let of_day = if m.is_present("SECONDS_OF_DAY") {
+            Some(OfDay::Seconds)
+        } else if m.is_present("MILLISECONDS_OF_DAY") {
+            Some(OfDay::Milliseconds)
+        } else {
+            None
+        };
+        let output_format = output.output_format();
+        if output_format != OutputFormat::Plain {
+            if let Some((_, flag)) = PLAIN_ONLY_FLAGS.iter().find(|(name, _)| m.is_present(name)) {
+                return Err(anyhow::anyhow!(
+                    "{} cannot be used with --output {}.",
+                    flag,
+                    output_format
+                )
+                .into());
+            }
+        }
+        // the zones of the config are not used for a time of the day, or a record.
+        let compare_zones = m
+            .value_of("COMPARE_ZONES")
+            .or(compare_zones.filter(|_| of_day.is_none() && output_format == OutputFormat::Plain))
+            .map(Zones::from_str)
+            .transpose()
+            .context("Wrong zones.")?;
+        let locale = m
+            .value_of("LOCALE")
+            .map(Locale::from_str)
+            .transpose()
+            .context("Wrong locale.")?
+            .unwrap_or_default();
+
+        let delimiter = if m.is_present("NULL") {
+            Delimiter::Nul
+        } else {
+            Delimiter::Whitespace
+        };
+        let input = if m.is_present("EXTRACT") {
+            let range = m
+                .value_of("DIGITS")
+                .map(DigitRange::from_str)
+                .transpose()
+                .context("Wrong digit range.")?
+                .unwrap_or_else(|| DigitRange::for_precision(precision));
+            ParseInput::Extract(range)
+        } else if m.is_present("JSON_INPUT") {
+            ParseInput::JsonLines {
+                key: m.value_of("KEY").expect("never happen").to_string(),
+                inject: m.value_of("INJECT").map(String::from),
+            }
+        } else if m.is_present("JSON_ARRAY") {
+            return Err(anyhow::anyhow!("--json-array requires --extract or --json-input.").into());
+        } else {
+            ParseInput::Timestamp(get_timestamp(
+                m.value_of("TIMESTAMP")
+                    .or_else(|| m.value_of("TIMESTAMP_OPTION")),
+                m.is_present("STRICT_NUMERIC"),
+                delimiter,
+            )?)
+        };
+
+        output.info(format_args!("format: {}", datetime_format))?;
+        output.info(format_args!("input: {:?}", input))?;
         Ok(ParseRequest {
             provider,
             precision,
             datetime_format,
-            timestamp,
+            template,
+            pretty: m.is_present("PRETTY"),
+            json_array: m.is_present("JSON_ARRAY"),
+            compare_zones,
+            locale,
+            epoch_day,
+            assume_local: m.is_present("ASSUME_LOCAL"),
+            of_day,
+            delimiter,
+            input,
         })
     }
 }
 
-pub fn run<O, Tz, P>(request: ParseRequest<P>) -> Result<(), Box<dyn std::error::Error>>
+/// Returns the datetime of the timestamp, or the start of the date with `--epoch-day`.
+fn datetime<O, Tz, P>(
+    request: &ParseRequest<P>,
+    timestamp: RawTimestamp,
+) -> Result<DateTime<Tz>, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let tz = request.provider.timezone();
+    if request.assume_local {
+        // the wall-clock in UTC is the one in the timezone, so the instant is a different one.
+        let utc = timestamp.datetime(request.precision, Utc)?;
+        return Ok(resolve_local_datetime(&tz, utc.naive_utc()));
+    }
+    if !request.epoch_day {
+        return Ok(timestamp.datetime(request.precision, tz)?);
+    }
+    match timestamp {
+        RawTimestamp::Integer(days) => Ok(start_of_day("Epoch day", &tz, from_epoch_day(days)?)?),
+        RawTimestamp::Fractional(days) => {
+            Err(anyhow::anyhow!("Wrong epoch day: '{}'. epoch days must be integers.", days).into())
+        }
+    }
+}
+
+pub fn run<O, Tz, P>(
+    request: ParseRequest<P>,
+    output: &mut Output,
+) -> Result<(), Box<dyn std::error::Error>>
 where
     O: Offset + Display + Sized,
     Tz: TimeZone<Offset = O> + Debug,
     P: DateTimeProvider<Tz>,
 {
-    let dt = request
-        .precision
-        .parse_timestamp(request.provider.timezone(), request.timestamp);
-    println!("{}", dt.format(&request.datetime_format).to_string());
+    let terminator = request.delimiter.terminator();
+    // elements are buffered to print the whole array at the end.
+    let mut elements = if request.json_array {
+        Some(Vec::new())
+    } else {
+        None
+    };
+    let mut summary = None;
+    match request.input {
+        ParseInput::Timestamp(timestamp) => {
+            if let Some(hint) = precision_hint(&request, timestamp) {
+                output.notice(format_args!("{}", hint))?;
+            }
+            let datetime = render(&request, timestamp)?;
+            output.record(
+                &ParsedRecord {
+                    timestamp,
+                    datetime: datetime.clone(),
+                },
+                format_args!("{}{}", datetime, terminator),
+            )?
+        }
+        ParseInput::Extract(range) => {
+            let extractor = Extractor::new(range);
+            let summary = summary.insert(Summary::default());
+            for line in read_records(io::stdin().lock(), request.delimiter) {
+                let (extracted, line_summary) = extract(&request, &extractor, &line?);
+                summary.merge(line_summary);
+                match elements {
+                    Some(ref mut elements) => elements.push(quote(&extracted)),
+                    None => write!(output.out(), "{}{}", extracted, terminator)?,
+                }
+            }
+        }
+        ParseInput::JsonLines {
+            ref key,
+            ref inject,
+        } => {
+            let summary = summary.insert(Summary::default());
+            for (i, line) in read_records(io::stdin().lock(), request.delimiter).enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let converted = json_line(&request, key, inject.as_deref(), &line)
+                    .map_err(|e| anyhow::anyhow!("line {}: {}", i + 1, e))?;
+                summary.converted();
+                match elements {
+                    Some(ref mut elements) if inject.is_some() => elements.push(converted),
+                    Some(ref mut elements) => elements.push(quote(&converted)),
+                    None => write!(output.out(), "{}{}", converted, terminator)?,
+                }
+            }
+        }
+    }
+    if let Some(elements) = elements {
+        write!(output.out(), "[{}]{}", elements.join(","), terminator)?;
+    }
+    output.out().flush()?;
+    if let Some(summary) = summary.filter(|summary| !summary.is_empty()) {
+        output.notice(format_args!("{}", summary))?;
+    }
+    output.flush()?;
     Ok(())
 }
 
-fn get_timestamp(maybe_timestamp: Option<&str>) -> Result<i64, Box<dyn std::error::Error>> {
-    Ok(maybe_timestamp
-        .map(|s| s.parse::<i64>().context("Wrong timestamp."))
-        .unwrap_or_else(|| {
+/// Renders the timestamp in the requested form, without printing it.
+fn render<O, Tz, P>(
+    request: &ParseRequest<P>,
+    timestamp: RawTimestamp,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    if request.pretty {
+        pretty(request, timestamp)
+    } else if let Some(ref zones) = request.compare_zones {
+        compare_zones(request, zones, timestamp)
+    } else {
+        format(request, timestamp)
+    }
+}
+
+fn format<O, Tz, P>(
+    request: &ParseRequest<P>,
+    timestamp: RawTimestamp,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let dt = datetime(request, timestamp)?;
+    if let Some(ref template) = request.template {
+        return fill(request, template, timestamp, &dt);
+    }
+    Ok(match request.of_day {
+        Some(of_day) => of_day.value(&dt).to_string(),
+        None => localized(request, &dt),
+    })
+}
+
+/// Fills the placeholders of the template with the values of the datetime.
+fn fill<O, Tz, P>(
+    request: &ParseRequest<P>,
+    template: &Template,
+    timestamp: RawTimestamp,
+    dt: &DateTime<Tz>,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let precision = request.precision;
+    // now is taken only if the template has `{relative}`.
+    let now = if template.contains(Placeholder::Relative) {
+        Some(
+            Timestamp::from_datetime(request.provider.now(), precision)?
+                .to_datetime(request.provider.timezone())?,
+        )
+    } else {
+        None
+    };
+    template.render(|placeholder| {
+        use Placeholder::*;
+        Ok(match placeholder {
+            Timestamp => ut_cli::timestamp::Timestamp::from_datetime(dt.clone(), precision)?
+                .value()
+                .to_string(),
+            Iso | Rfc3339 => dt.to_rfc3339_opts(SecondsFormat::AutoSi, false),
+            Formatted => localized(request, dt),
+            Weekday => request.locale.weekday(dt.weekday()).to_string(),
+            DayOfYear => dt.ordinal().to_string(),
+            Week => dt.iso_week().week().to_string(),
+            Offset => dt.offset().fix().to_string(),
+            Relative => relative(dt, now.as_ref().expect("never happen")),
+            Input => timestamp.to_string(),
+        })
+    })
+}
+
+/// Formats the datetime, with weekday and month names in the locale.
+fn localized<Tz, P>(request: &ParseRequest<P>, dt: &DateTime<Tz>) -> String
+where
+    Tz: TimeZone,
+    Tz::Offset: Display,
+{
+    dt.format(&request.locale.localize_format(&request.datetime_format, dt))
+        .to_string()
+}
+
+/// Renders the datetime in several forms, one per line.
+fn pretty<O, Tz, P>(
+    request: &ParseRequest<P>,
+    timestamp: RawTimestamp,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let precision = request.precision;
+    let dt = datetime(request, timestamp)?;
+    let utc = dt.with_timezone(&Utc);
+    // truncate now to the precision, not to show the fraction of the time to run.
+    let now = Timestamp::from_datetime(request.provider.now(), precision)?
+        .to_datetime(request.provider.timezone())?;
+
+    let lines = [
+        ("Local", localized(request, &dt)),
+        ("UTC", utc.format(precision.preferred_format()).to_string()),
+        ("RFC3339", dt.to_rfc3339_opts(SecondsFormat::AutoSi, false)),
+        ("Weekday", request.locale.weekday(dt.weekday()).to_string()),
+        ("Relative", relative(&dt, &now)),
+    ];
+    Ok(lines
+        .iter()
+        .map(|(label, value)| format!("{:<10}{}", format!("{}:", label), value))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Renders the datetime in each zone, one per line in the order of the zones.
+fn compare_zones<O, Tz, P>(
+    request: &ParseRequest<P>,
+    zones: &Zones,
+    timestamp: RawTimestamp,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let dt = datetime(request, timestamp)?;
+    let width = zones
+        .iter()
+        .map(|(name, _)| name.len() + 1)
+        .max()
+        .unwrap_or(0);
+    Ok(zones
+        .iter()
+        .map(|(name, zone)| {
+            format!(
+                "{:<width$} {}",
+                format!("{}:", name),
+                zone.format(&dt, &request.datetime_format),
+                width = width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Describes the datetime from now, like `3 days 04:05:06 ago`.
+fn relative<Tz: TimeZone>(dt: &DateTime<Tz>, now: &DateTime<Tz>) -> String {
+    if dt < now {
+        format!("{} ago", TimeDelta::between(dt, now))
+    } else if dt > now {
+        format!("in {}", TimeDelta::between(now, dt))
+    } else {
+        "now".to_string()
+    }
+}
+
+/// Parses the timestamp in the top-level key of the JSON object.
+/// Returns the datetime, or the object with the datetime in the `inject` field.
+fn json_line<O, Tz, P>(
+    request: &ParseRequest<P>,
+    key: &str,
+    inject: Option<&str>,
+    line: &str,
+) -> Result<String, Box<dyn std::error::Error>>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let object = JsonObject::parse(line)?;
+    let timestamp = match object.get_i64(key) {
+        Ok(t) => RawTimestamp::Integer(t),
+        Err(JsonError::NotInteger(k, v)) => {
+            RawTimestamp::parse_fractional(&v).ok_or(JsonError::NotInteger(k, v))?
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let formatted = format(request, timestamp)?;
+    Ok(match inject {
+        Some(field) => object.with_field(field, &quote(&formatted)),
+        None => formatted,
+    })
+}
+
+/// Returns a hint if the timestamp in seconds looks like one in milliseconds.
+fn precision_hint<O, Tz, P>(request: &ParseRequest<P>, timestamp: RawTimestamp) -> Option<String>
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    if request.precision != Precision::Second || request.epoch_day {
+        return None;
+    }
+
+    let dt = datetime(request, timestamp).ok()?;
+    if dt.year() > SUSPICIOUS_YEAR {
+        Some(format!(
+            "Hint: year {} is far in the future, the timestamp might be in milliseconds. use `-p ms` to parse it in milliseconds.",
+            dt.year()
+        ))
+    } else {
+        None
+    }
+}
+
+/// Replaces timestamps in the line. Timestamps out of range are kept as they are, and counted as errors.
+fn extract<O, Tz, P>(
+    request: &ParseRequest<P>,
+    extractor: &Extractor,
+    line: &str,
+) -> (String, Summary)
+where
+    O: Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    let summary = Cell::new(Summary::default());
+    let extracted = extractor.replace(line, |timestamp| {
+        let mut counts = summary.get();
+        let formatted = format(request, RawTimestamp::Integer(timestamp)).ok();
+        match formatted {
+            Some(_) => counts.converted(),
+            None => counts.error(),
+        }
+        summary.set(counts);
+        formatted
+    });
+    (extracted, summary.get())
+}
+
+fn get_timestamp(
+    maybe_timestamp: Option<&str>,
+    strict: bool,
+    delimiter: Delimiter,
+) -> Result<RawTimestamp, Box<dyn std::error::Error>> {
+    let text = match maybe_timestamp {
+        Some(s) => s.to_string(),
+        None => {
             let stdin = io::stdin();
-            let r: Result<i64, ReadError> = read_next(stdin);
-            r.context("Wrong timestamp.")
-        })?)
+            check_stdin(stdin.is_terminal())?;
+            read_timestamp(stdin.lock(), delimiter)?
+        }
+    };
+    Ok(RawTimestamp::parse(&text, strict)?)
+}
+
+/// Fails instead of waiting for input, if stdin is a terminal.
+fn check_stdin(is_terminal: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if is_terminal {
+        Err(anyhow::anyhow!(
+            "no TIMESTAMP given and stdin is a terminal — pass a value or pipe one in"
+        )
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+fn read_timestamp<R: BufRead>(
+    src: R,
+    delimiter: Delimiter,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let r = read_tokens_with(src, delimiter)
+        .next()
+        .unwrap_or(Err(ReadError::Empty));
+    match r {
+        Err(ReadError::Empty) => Err(anyhow::anyhow!(
+            "no timestamp provided: pass TIMESTAMP as an argument or pipe a value on stdin"
+        )
+        .into()),
+        r => Ok(r.context("Wrong timestamp.")?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, TimeZone};
+
+    use std::io;
+
+    use clap::ArgMatches;
+
+    use super::{
+        check_stdin, compare_zones, extract, format, json_line, precision_hint, pretty,
+        read_timestamp, relative, render, run, OfDay, ParseInput, ParseRequest, ParsedRecord,
+        RawTimestamp,
+    };
+    use crate::cmd::parse::command;
+    use crate::extract::Extractor;
+    use crate::output::Output;
+    use crate::verbosity::Verbosity;
+    use ut_cli::find::FindOptions;
+    use ut_cli::precision::Precision;
+    use ut_cli::provider::{DateTimeProvider, FixedOffsetProvider, FromTimeZone};
+    use ut_cli::read::Delimiter;
+
+    /// Fixes now at 2019-06-20 12:00:00.123 (+09:00).
+    struct FixedNowProvider;
+
+    impl DateTimeProvider<FixedOffset> for FixedNowProvider {
+        fn timezone(&self) -> FixedOffset {
+            FixedOffset::east(9 * 3600)
+        }
+
+        fn now(&self) -> DateTime<FixedOffset> {
+            self.timezone().timestamp_millis(1_560_999_600_123)
+        }
+    }
+
+    /// Builds the request, discarding the warnings.
+    fn new_request<P>(
+        m: &ArgMatches,
+        provider: P,
+        precision: Precision,
+        datetime_format: Option<&str>,
+        compare_zones: Option<&str>,
+    ) -> Result<ParseRequest<P>, Box<dyn std::error::Error>> {
+        let (mut out, mut err) = (io::sink(), io::sink());
+        let mut output = Output::new(&mut out, &mut err);
+        ParseRequest::new(
+            m,
+            provider,
+            precision,
+            datetime_format,
+            compare_zones,
+            &mut output,
+        )
+    }
+
+    fn request(args: Vec<&str>) -> ParseRequest<FixedOffsetProvider> {
+        let m = command("parse", FindOptions::default()).get_matches_from(args);
+        let provider = FixedOffsetProvider::from_timezone(FixedOffset::east(9 * 3600));
+        new_request(&m, provider, Precision::Second, None, None).unwrap()
+    }
+
+    fn parse_with(args: Vec<&str>) -> String {
+        let request = request(args);
+        match request.input {
+            ParseInput::Timestamp(timestamp) => render(&request, timestamp).unwrap(),
+            _ => panic!("unexpected input: {:?}", request.input),
+        }
+    }
+
+    fn extract_with(args: Vec<&str>, line: &str) -> String {
+        let request = request(args);
+        match request.input {
+            ParseInput::Extract(range) => extract(&request, &Extractor::new(range), line).0,
+            _ => panic!("unexpected input: {:?}", request.input),
+        }
+    }
+
+    #[test]
+    fn record_header() {
+        use crate::record::{assert_header, Record};
+
+        assert_eq!(ParsedRecord::HEADER, &["timestamp", "datetime"]);
+        assert_header(&ParsedRecord {
+            timestamp: RawTimestamp::Fractional(1_560_762_129.5),
+            datetime: "2019-06-17 09:02:09".to_string(),
+        });
+    }
+
+    #[test]
+    fn json_lines() {
+        let convert = |args: Vec<&str>, line: &str| {
+            let mut args = args;
+            args.insert(0, "parse");
+            let request = request(args);
+            match request.input {
+                ParseInput::JsonLines {
+                    ref key,
+                    ref inject,
+                } => json_line(&request, key, inject.as_deref(), line).map_err(|e| e.to_string()),
+                _ => panic!("unexpected input: {:?}", request.input),
+            }
+        };
+        let line = r#"{"ts": 1560762129, "a": {"ts": 0}, "msg": "hello"}"#;
+
+        assert_eq!(
+            convert(vec!["--json-input", "--key", "ts"], line),
+            Ok("2019-06-17 18:02:09 (+09:00)".to_string())
+        );
+        assert_eq!(
+            convert(
+                vec!["--json-input", "--key", "ts", "--inject", "formatted_ts"],
+                line
+            ),
+            Ok(r#"{"ts": 1560762129, "a": {"ts": 0}, "msg": "hello","formatted_ts":"2019-06-17 18:02:09 (+09:00)"}"#.to_string())
+        );
+        assert_eq!(
+            convert(
+                vec!["--json-input", "--key", "ts", "--inject", "ts", "-f", "%F"],
+                line
+            ),
+            Ok(r#"{"ts": "2019-06-17", "a": {"ts": 0}, "msg": "hello"}"#.to_string())
+        );
+
+        // nested keys are not supported.
+        assert_eq!(
+            convert(vec!["--json-input", "--key", "a.ts"], line),
+            Err("Key 'a.ts' is not found.".to_string())
+        );
+        assert_eq!(
+            convert(vec!["--json-input", "--key", "msg"], line),
+            Err("Value of 'msg' is not an integer: \"hello\"".to_string())
+        );
+        assert_eq!(
+            convert(vec!["--json-input", "--key", "ts"], "ts=1560762129"),
+            Err("Wrong JSON: expected '{' at column 1.".to_string())
+        );
+    }
+
+    #[test]
+    fn terminal_stdin() {
+        assert_eq!(
+            check_stdin(true).map_err(|e| e.to_string()),
+            Err(
+                "no TIMESTAMP given and stdin is a terminal — pass a value or pipe one in"
+                    .to_string()
+            )
+        );
+        assert!(check_stdin(false).is_ok());
+    }
+
+    #[test]
+    fn empty_stdin() {
+        let message =
+            "no timestamp provided: pass TIMESTAMP as an argument or pipe a value on stdin";
+        let read = |input: &'static str| {
+            read_timestamp(input.as_bytes(), Delimiter::Whitespace).map_err(|e| e.to_string())
+        };
+
+        assert_eq!(read(""), Err(message.to_string()));
+        assert_eq!(read(" \n\t "), Err(message.to_string()));
+        assert_eq!(read(" 1560762129\n"), Ok("1560762129".to_string()));
+        assert_eq!(read("\u{feff}1560762129\r\n"), Ok("1560762129".to_string()));
+        assert_eq!(read("\u{feff}\r\n"), Err(message.to_string()));
+        assert_eq!(
+            read_timestamp(std::io::empty(), Delimiter::Whitespace).map_err(|e| e.to_string()),
+            Err(message.to_string())
+        );
+    }
+
+    #[test]
+    fn pretty_report() {
+        let report = |args: Vec<&str>, precision| {
+            let m = command("parse", FindOptions::default()).get_matches_from(args);
+            let request = new_request(&m, FixedNowProvider, precision, None, None).unwrap();
+            match request.input {
+                ParseInput::Timestamp(timestamp) => pretty(&request, timestamp).unwrap(),
+                _ => panic!("unexpected input: {:?}", request.input),
+            }
+        };
+
+        assert_eq!(
+            report(vec!["parse", "--pretty", "1560762129"], Precision::Second),
+            [
+                "Local:    2019-06-17 18:02:09 (+09:00)",
+                "UTC:      2019-06-17 09:02:09 (UTC)",
+                "RFC3339:  2019-06-17T18:02:09+09:00",
+                "Weekday:  Monday",
+                "Relative: 2 days 17:57:51 ago",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            report(
+                vec!["parse", "--pretty", "-f", "%H:%M", "1561000000500"],
+                Precision::MilliSecond
+            ),
+            [
+                "Local:    12:06",
+                "UTC:      2019-06-20 03:06:40.500 (UTC)",
+                "RFC3339:  2019-06-20T12:06:40.500+09:00",
+                "Weekday:  Thursday",
+                "Relative: in 00:06:40.377000",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            report(
+                vec![
+                    "parse",
+                    "--pretty",
+                    "--locale",
+                    "ja",
+                    "-f",
+                    "%B%e日 (%a)",
+                    "1560762129"
+                ],
+                Precision::Second
+            ),
+            [
+                "Local:    6月17日 (月)",
+                "UTC:      2019-06-17 09:02:09 (UTC)",
+                "RFC3339:  2019-06-17T18:02:09+09:00",
+                "Weekday:  月曜日",
+                "Relative: 2 days 17:57:51 ago",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn locale() {
+        assert_eq!(
+            parse_with(vec![
+                "parse",
+                "--locale",
+                "fr",
+                "-f",
+                "%A %-d %B %Y",
+                "1560762129"
+            ]),
+            "lundi 17 juin 2019"
+        );
+        assert_eq!(
+            parse_with(vec![
+                "parse",
+                "--locale",
+                "de_DE.UTF-8",
+                "-f",
+                "%a, %d. %b",
+                "1560762129"
+            ]),
+            "Mo, 17. Jun"
+        );
+        // numbers and ISO formats are kept as they are.
+        assert_eq!(
+            parse_with(vec!["parse", "--locale", "ja", "-f", "%F %a", "1560762129"]),
+            "2019-06-17 月"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "-f", "%A %B", "1560762129"]),
+            "Monday June"
+        );
+    }
+
+    #[test]
+    fn template() {
+        let fill = |args: Vec<&str>| {
+            let m = command("parse", FindOptions::default()).get_matches_from(args);
+            let request = new_request(&m, FixedNowProvider, Precision::Second, None, None).unwrap();
+            match request.input {
+                ParseInput::Timestamp(timestamp) => render(&request, timestamp).unwrap(),
+                _ => panic!("unexpected input: {:?}", request.input),
+            }
+        };
+
+        assert_eq!(
+            fill(vec![
+                "parse",
+                "--template",
+                r"{timestamp}\t{iso}\t{weekday}\t{relative}",
+                "1560762129"
+            ]),
+            "1560762129\t2019-06-17T18:02:09+09:00\tMonday\t2 days 17:57:51 ago"
+        );
+        assert_eq!(
+            fill(vec![
+                "parse",
+                "--template",
+                "{input}|{rfc3339}|{formatted}|{doy}|{week}|{offset}",
+                "-f",
+                "%b %-d",
+                "--locale",
+                "fr",
+                "1560762129"
+            ]),
+            "1560762129|2019-06-17T18:02:09+09:00|juin 17|168|25|+09:00"
+        );
+        // the ISO week of 2021-01-01 is the last week of 2020.
+        assert_eq!(
+            fill(vec!["parse", "--template", "{doy} {week}", "1609426800"]),
+            "1 53"
+        );
+        assert_eq!(
+            fill(vec![
+                "parse",
+                "--locale",
+                "ja",
+                "--template",
+                "{{{weekday}}}",
+                "1560762129"
+            ]),
+            "{月曜日}"
+        );
+        // an epoch day is read as it is, and the timestamp is of the start of the day.
+        assert_eq!(
+            fill(vec![
+                "parse",
+                "--epoch-day",
+                "--template",
+                "{input} {timestamp} {iso}",
+                "18064"
+            ]),
+            "18064 1560697200 2019-06-17T00:00:00+09:00"
+        );
+
+        let e = command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "--template", "{time}", "0"])
+            .unwrap_err();
+        assert!(e
+            .message
+            .contains("Wrong template: unknown placeholder 'time'."));
+        for flag in &["--pretty", "--seconds-of-day"] {
+            assert!(command("parse", FindOptions::default())
+                .get_matches_from_safe(vec!["parse", "--template", "{iso}", flag, "0"])
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn relative_to_now() {
+        let tz = FixedOffset::east(9 * 3600);
+        let now = tz.ymd(2019, 6, 20).and_hms(12, 0, 0);
+        assert_eq!(relative(&now, &now), "now");
+        assert_eq!(
+            relative(&tz.ymd(2018, 4, 19).and_hms(11, 0, 0), &now),
+            "1 year 2 months 1 day 01:00:00 ago"
+        );
+        assert_eq!(
+            relative(&tz.ymd(2019, 7, 20).and_hms(12, 0, 30), &now),
+            "in 1 month 00:00:30"
+        );
+    }
+
+    #[test]
+    fn strict_numeric() {
+        assert_eq!(
+            parse_with(vec!["parse", "00123"]),
+            "1970-01-01 09:02:03 (+09:00)"
+        );
+
+        let m = command("parse", FindOptions::default()).get_matches_from(vec![
+            "parse",
+            "--strict-numeric",
+            "00123",
+        ]);
+        let provider = FixedOffsetProvider::from_timezone(FixedOffset::east(9 * 3600));
+        let e = new_request(&m, provider, Precision::Second, None, None)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Wrong timestamp: '00123'. timestamp must not have leading zeros with --strict-numeric."
+        );
+    }
+
+    #[test]
+    fn fractional_timestamps() {
+        assert_eq!(
+            RawTimestamp::parse("1560762129", false),
+            Ok(RawTimestamp::Integer(1_560_762_129))
+        );
+        assert_eq!(
+            RawTimestamp::parse("1560762129.5", false),
+            Ok(RawTimestamp::Fractional(1_560_762_129.5))
+        );
+        assert_eq!(
+            RawTimestamp::parse("-.5", false),
+            Ok(RawTimestamp::Fractional(-0.5))
+        );
+        assert!(RawTimestamp::parse("1e9", false).is_err());
+        assert!(RawTimestamp::parse("inf", false).is_err());
+        assert!(RawTimestamp::parse("1.5.0", false).is_err());
+
+        let text = read_timestamp(
+            "1560762129.5\n1560762130\n".as_bytes(),
+            Delimiter::Whitespace,
+        )
+        .unwrap();
+        let timestamp = RawTimestamp::parse(&text, false).unwrap();
+        let formatted = request(vec!["parse", "-f", "%T%.3f", "0"]);
+        assert_eq!(
+            format(&formatted, timestamp).map_err(|e| e.to_string()),
+            Ok("18:02:09.500".to_string())
+        );
+
+        // lines of JSON can mix integers and fractional numbers.
+        let request = request(vec!["parse", "--json-input", "--key", "ts"]);
+        let convert = |line| json_line(&request, "ts", None, line).map_err(|e| e.to_string());
+        assert_eq!(
+            convert(r#"{"ts": 1560762129.5}"#),
+            Ok("2019-06-17 18:02:09 (+09:00)".to_string())
+        );
+        assert_eq!(
+            convert(r#"{"ts": 1560762130}"#),
+            Ok("2019-06-17 18:02:10 (+09:00)".to_string())
+        );
+        assert_eq!(
+            convert(r#"{"ts": "1.5"}"#),
+            Err("Value of 'ts' is not an integer: \"1.5\"".to_string())
+        );
+    }
+
+    #[test]
+    fn millisecond_hint() {
+        let hint = |args: Vec<&str>| {
+            let request = request(args);
+            match request.input {
+                ParseInput::Timestamp(timestamp) => precision_hint(&request, timestamp),
+                _ => panic!("unexpected input: {:?}", request.input),
+            }
+        };
+
+        assert_eq!(
+            hint(vec!["parse", "1560762129000"]),
+            Some("Hint: year 51428 is far in the future, the timestamp might be in milliseconds. use `-p ms` to parse it in milliseconds.".to_string())
+        );
+        assert_eq!(hint(vec!["parse", "1560762129"]), None);
+        assert_eq!(hint(vec!["parse", "-p", "ms", "1560762129000"]), None);
+
+        // the output is kept as it is.
+        assert_eq!(
+            parse_with(vec!["parse", "1560762129000"]),
+            "+51428-08-01 20:50:00 (+09:00)"
+        );
+    }
+
+    #[test]
+    fn zone_label() {
+        assert_eq!(
+            parse_with(vec!["parse", "0"]),
+            "1970-01-01 09:00:00 (+09:00)"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--zone-label", "JST", "0"]),
+            "1970-01-01 09:00:00 (JST)"
+        );
+        assert_eq!(
+            parse_with(vec![
+                "parse",
+                "-f",
+                "%H:%M %Z",
+                "--offset-output",
+                "JST",
+                "0"
+            ]),
+            "09:00 JST"
+        );
+    }
+
+    #[test]
+    fn extract_timestamps() {
+        let line = "start=1560762129 end=1560765729 id=42";
+        assert_eq!(
+            extract_with(vec!["parse", "--extract"], line),
+            "start=2019-06-17 18:02:09 (+09:00) end=2019-06-17 19:02:09 (+09:00) id=42"
+        );
+        assert_eq!(
+            extract_with(
+                vec!["parse", "--inline", "-f", "%H:%M", "--digits", "2-10"],
+                line
+            ),
+            "start=18:02 end=19:02 id=09:00"
+        );
+        assert_eq!(
+            extract_with(vec!["parse", "--extract", "--template", "{iso}"], line),
+            "start=2019-06-17T18:02:09+09:00 end=2019-06-17T19:02:09+09:00 id=42"
+        );
+    }
+
+    #[test]
+    fn compare_zones_in_order() {
+        let compare = |args: Vec<&str>| {
+            let request = request(args);
+            match (&request.input, &request.compare_zones) {
+                (ParseInput::Timestamp(timestamp), Some(zones)) => {
+                    compare_zones(&request, zones, *timestamp).unwrap()
+                }
+                _ => panic!("unexpected input: {:?}", request.input),
+            }
+        };
+
+        assert_eq!(
+            compare(vec![
+                "parse",
+                "1560762129",
+                "--compare-zones",
+                "+09:00,UTC,-05:00"
+            ]),
+            "+09:00: 2019-06-17 18:02:09 (+09:00)\n\
+             UTC:    2019-06-17 09:02:09 (UTC)\n\
+             -05:00: 2019-06-17 04:02:09 (-05:00)"
+        );
+        assert_eq!(
+            compare(vec![
+                "parse",
+                "-f",
+                "%H:%M",
+                "--compare-zones",
+                "-1,+1",
+                "0"
+            ]),
+            "-1: 23:00\n+1: 01:00"
+        );
+
+        // the zones from the config are used, unless the option is given.
+        let m = command("parse", FindOptions::default()).get_matches_from(vec!["parse", "0"]);
+        let provider = FixedOffsetProvider::from_timezone(FixedOffset::east(0));
+        let request =
+            new_request(&m, provider, Precision::Second, Some("%H"), Some("UTC,+09")).unwrap();
+        let zones = request.compare_zones.as_ref().unwrap();
+        assert_eq!(
+            compare_zones(&request, zones, RawTimestamp::Integer(0)).map_err(|e| e.to_string()),
+            Ok("UTC: 00\n+09: 09".to_string())
+        );
+    }
+
+    #[test]
+    fn epoch_day() {
+        assert_eq!(parse_with(vec!["parse", "--epoch-day", "0"]), "1970-01-01");
+        assert_eq!(parse_with(vec!["parse", "--epoch-day", "-1"]), "1969-12-31");
+        assert_eq!(
+            parse_with(vec!["parse", "--epoch-day", "18064"]),
+            "2019-06-17"
+        );
+        // the date starts at midnight in the timezone.
+        assert_eq!(
+            parse_with(vec!["parse", "--epoch-day", "-f", "%FT%T%:z", "-25567"]),
+            "1900-01-01T00:00:00+09:00"
+        );
+
+        let request = request(vec!["parse", "--epoch-day", "0"]);
+        let convert = |timestamp| format(&request, timestamp).map_err(|e| e.to_string());
+        assert_eq!(
+            convert(RawTimestamp::Integer(i64::MAX)),
+            Err("Epoch day out of range: 9223372036854775807. days must be between -96465658 and 95026601.".to_string())
+        );
+        assert_eq!(
+            convert(RawTimestamp::Fractional(1.5)),
+            Err("Wrong epoch day: '1.5'. epoch days must be integers.".to_string())
+        );
+        assert_eq!(
+            precision_hint(&request, RawTimestamp::Integer(1_560_762_129_000)),
+            None
+        );
+    }
+
+    #[test]
+    fn custom_formats() {
+        // `%s` echoes the input at second precision, before and after the epoch.
+        for timestamp in ["1560762129", "0", "-1", "253402300799"].iter() {
+            assert_eq!(
+                parse_with(vec!["parse", "-f", "%s", timestamp]),
+                timestamp.to_string()
+            );
+        }
+        // `%s` is in seconds in any precision, and the rest is in `%3f`.
+        assert_eq!(
+            parse_with(vec!["parse", "-p", "ms", "-f", "%s %3f", "1560762129012"]),
+            "1560762129 012"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "-p", "ns", "-f", "%s%.9f", "-1"]),
+            "-1.999999999"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "-f", "%_m|%-d|%e|%_H|%-M", "1560143529"]),
+            " 6|10|10|14|12"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--locale", "ja", "-f", "%s %a", "1560762129"]),
+            "1560762129 月"
+        );
+        assert_eq!(
+            extract_with(vec!["parse", "--extract", "-f", "%s"], "t=1560762129"),
+            "t=1560762129"
+        );
+    }
+
+    #[test]
+    fn styles() {
+        let styles = [
+            ("iso", "2019-06-17T18:02:09+09:00"),
+            ("log", "2019-06-17 18:02:09"),
+            ("compact", "20190617T180209+0900"),
+            ("us", "06/17/2019"),
+            ("eu", "17/06/2019"),
+        ];
+        for (style, expected) in styles.iter().copied() {
+            assert_eq!(
+                parse_with(vec!["parse", "--style", style, "1560762129"]),
+                expected
+            );
+        }
+        assert_eq!(
+            parse_with(vec!["parse", "-p", "ms", "--style", "iso", "1560762129012"]),
+            "2019-06-17T18:02:09.012+09:00"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--format-preset", "log", "0"]),
+            "1970-01-01 09:00:00"
+        );
+    }
+
+    #[test]
+    fn of_day() {
+        // the provider of the tests is in +09:00.
+        assert_eq!(parse_with(vec!["parse", "--seconds-of-day", "-32400"]), "0");
+        assert_eq!(parse_with(vec!["parse", "--seconds-of-day", "54000"]), "0");
+        assert_eq!(
+            parse_with(vec!["parse", "--seconds-of-day", "53999"]),
+            "86399"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--seconds-of-day", "1560762129"]),
+            "64929"
+        );
+        assert_eq!(
+            parse_with(vec![
+                "parse",
+                "-p",
+                "ms",
+                "--milliseconds-of-day",
+                "53999999"
+            ]),
+            "86399999"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "-p", "ms", "--millis-of-day", "-32400000"]),
+            "0"
+        );
+        // milliseconds are dropped in seconds.
+        assert_eq!(
+            parse_with(vec!["parse", "-p", "ms", "--seconds-of-day", "53999999"]),
+            "86399"
+        );
+        assert_eq!(
+            extract_with(
+                vec!["parse", "--extract", "--seconds-of-day"],
+                "t=1560762129"
+            ),
+            "t=64929"
+        );
+        assert!(command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "--seconds-of-day", "-f", "%s", "0"])
+            .is_err());
+
+        let leap_second = chrono::NaiveTime::from_hms_milli_opt(23, 59, 59, 1_500).unwrap();
+        assert_eq!(OfDay::Seconds.value(&leap_second), 86399);
+        assert_eq!(OfDay::Milliseconds.value(&leap_second), 86_400_500);
+    }
+
+    #[test]
+    fn assume_local() {
+        let fmt = "%s %FT%T%:z";
+        assert_eq!(
+            parse_with(vec!["parse", "-f", fmt, "1560762129"]),
+            "1560762129 2019-06-17T18:02:09+09:00"
+        );
+        // the wall-clock is kept, and the instant is shifted back by the offset.
+        assert_eq!(
+            parse_with(vec!["parse", "--assume-local", "-f", fmt, "1560762129"]),
+            "1560729729 2019-06-17T09:02:09+09:00"
+        );
+        assert!(command("parse", FindOptions::default())
+            .get_matches_from_safe(vec!["parse", "--assume-local", "--epoch-day", "0"])
+            .is_err());
+    }
+
+    #[test]
+    fn run_output() {
+        let run_with = |args: Vec<&str>, quiet: bool| {
+            let m = command("parse", FindOptions::default()).get_matches_from(args);
+            let (mut out, mut err) = (Vec::new(), Vec::new());
+            let mut output = Output::new(&mut out, &mut err).verbosity(Verbosity::new(0, quiet));
+            let provider = FixedOffsetProvider::from_timezone(FixedOffset::east(9 * 3600));
+            let request =
+                ParseRequest::new(&m, provider, Precision::Second, None, None, &mut output)
+                    .unwrap();
+            run(request, &mut output).unwrap();
+            (
+                String::from_utf8(out).unwrap(),
+                String::from_utf8(err).unwrap(),
+            )
+        };
+
+        assert_eq!(
+            run_with(vec!["parse", "1560762129"], false),
+            ("2019-06-17 18:02:09 (+09:00)\n".to_string(), "".to_string())
+        );
+        let (out, err) = run_with(vec!["parse", "1560762129000"], false);
+        assert_eq!(out, "+51428-08-01 20:50:00 (+09:00)\n");
+        assert!(err.starts_with("Hint: year 51428 is far in the future"));
+        assert_eq!(
+            run_with(vec!["parse", "1560762129000"], true),
+            (
+                "+51428-08-01 20:50:00 (+09:00)\n".to_string(),
+                "".to_string()
+            )
+        );
+        assert_eq!(
+            run_with(vec!["parse", "-p", "s", "0"], false),
+            (
+                "1970-01-01 09:00:00 (+09:00)\n".to_string(),
+                "-p PRECISION option is deprecated.\n".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn render_by_form() {
+        assert_eq!(
+            parse_with(vec!["parse", "1560762129"]),
+            "2019-06-17 18:02:09 (+09:00)"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--compare-zones", "UTC,+09:00", "1560762129"]),
+            "UTC:    2019-06-17 09:02:09 (UTC)\n+09:00: 2019-06-17 18:02:09 (+09:00)"
+        );
+        assert_eq!(
+            parse_with(vec!["parse", "--pretty", "1560762129"])
+                .lines()
+                .next(),
+            Some("Local:    2019-06-17 18:02:09 (+09:00)")
+        );
+    }
+
+    #[test]
+    fn compare_zones_invalid() {
+        let e = command("parse", FindOptions::default())
+            .get_matches_from_safe(vec![
+                "parse",
+                "0",
+                "--compare-zones",
+                "UTC,America/New_York,+09:00",
+            ])
+            .unwrap_err();
+        assert!(e.message.contains("input: 'America/New_York'"), "{}", e);
+    }
 }
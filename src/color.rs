@@ -0,0 +1,170 @@
+use clap::AppSettings;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use ut_cli::find::{FindByName, FindError, FindOptions, PossibleNames, PossibleValues};
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ColorChoiceError {
+    #[error("Wrong color choice. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for ColorChoiceError {
+    fn from(e: FindError) -> Self {
+        ColorChoiceError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for ColorChoiceError {
+    fn into_validation_error(self) -> String {
+        use ColorChoiceError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("color", &e, ColorChoice::possible_names())
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// When to color the help, errors and other outputs for terminals.
+#[derive(Debug, Default, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum ColorChoice {
+    /// Color if the output is a terminal.
+    #[default]
+    #[strum(serialize = "auto")]
+    Auto,
+
+    #[strum(serialize = "always")]
+    Always,
+
+    #[strum(serialize = "never")]
+    Never,
+}
+
+impl PossibleValues for ColorChoice {
+    type Iterator = ColorChoiceIter;
+
+    fn possible_values() -> Self::Iterator {
+        ColorChoice::iter()
+    }
+}
+
+impl PossibleNames for ColorChoice {}
+
+impl FindByName for ColorChoice {
+    type Error = ColorChoiceError;
+}
+
+impl ColorChoice {
+    /// Returns the choice by `--color`, or by `NO_COLOR` and `CLICOLOR_FORCE` without it.
+    /// `NO_COLOR` takes precedence over `CLICOLOR_FORCE`, and empty values are ignored.
+    pub fn resolve(
+        flag: Option<ColorChoice>,
+        no_color: Option<&str>,
+        clicolor_force: Option<&str>,
+    ) -> ColorChoice {
+        let is_set = |v: Option<&str>| v.is_some_and(|v| !v.is_empty());
+        match flag {
+            Some(choice) => choice,
+            None if is_set(no_color) => ColorChoice::Never,
+            None if is_set(clicolor_force) && clicolor_force != Some("0") => ColorChoice::Always,
+            None => ColorChoice::Auto,
+        }
+    }
+
+    /// Returns the choice of the process, by the arguments and the environment variables.
+    /// The help is colored while parsing arguments, so `--color` is looked up in advance.
+    /// A wrong name is ignored here, and reported by the validator of the argument.
+    pub fn from_env(options: FindOptions) -> ColorChoice {
+        // arguments which are not UTF-8 can't be `--color`, and are reported by clap.
+        let args: Vec<String> = std::env::args_os()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let flag = args
+            .iter()
+            .enumerate()
+            .find_map(|(i, arg)| match arg.strip_prefix("--color") {
+                Some("") => args.get(i + 1).map(String::as_str),
+                Some(value) => value.strip_prefix('='),
+                None => None,
+            })
+            .and_then(|name| ColorChoice::find_by_name_opt_with(Some(name), options).ok())
+            .flatten();
+        let var = |key| std::env::var(key).ok();
+        ColorChoice::resolve(
+            flag,
+            var("NO_COLOR").as_deref(),
+            var("CLICOLOR_FORCE").as_deref(),
+        )
+    }
+
+    /// The setting of clap, which detects a terminal by itself in auto.
+    pub fn app_setting(self) -> AppSettings {
+        match self {
+            ColorChoice::Auto => AppSettings::ColorAuto,
+            ColorChoice::Always => AppSettings::ColorAlways,
+            ColorChoice::Never => AppSettings::ColorNever,
+        }
+    }
+
+    /// Returns whether to color an output, which is a terminal or not.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => is_terminal,
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+/// Paints the text in bold red, as clap does for its errors.
+pub fn red(text: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[1;31m{}\x1b[0m", text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::{red, ColorChoice};
+    use ut_cli::find::FindByName;
+
+    #[test]
+    fn find() {
+        assert_eq!(ColorChoice::find_by_name("never"), Ok(ColorChoice::Never));
+        assert_eq!(ColorChoice::find_by_name("al"), Ok(ColorChoice::Always));
+        assert!(ColorChoice::find_by_name("yes").is_err());
+    }
+
+    #[test]
+    fn resolve() {
+        use ColorChoice::*;
+
+        assert_eq!(ColorChoice::resolve(None, None, None), Auto);
+        assert_eq!(ColorChoice::resolve(None, Some("1"), None), Never);
+        assert_eq!(ColorChoice::resolve(None, Some(""), None), Auto);
+        assert_eq!(ColorChoice::resolve(None, None, Some("1")), Always);
+        assert_eq!(ColorChoice::resolve(None, None, Some("0")), Auto);
+        assert_eq!(ColorChoice::resolve(None, Some("1"), Some("1")), Never);
+        // the flag takes precedence over the environment variables.
+        assert_eq!(ColorChoice::resolve(Some(Always), Some("1"), None), Always);
+        assert_eq!(ColorChoice::resolve(Some(Never), None, Some("1")), Never);
+    }
+
+    #[test]
+    fn enabled() {
+        assert!(ColorChoice::Auto.enabled(true));
+        assert!(!ColorChoice::Auto.enabled(false));
+        assert!(ColorChoice::Always.enabled(false));
+        assert!(!ColorChoice::Never.enabled(true));
+
+        assert_eq!(red("error:", true), "\x1b[1;31merror:\x1b[0m");
+        assert_eq!(red("error:", false), "error:");
+    }
+}
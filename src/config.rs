@@ -1,21 +1,128 @@
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-#[derive(Debug)]
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::format::{validate_format, FormatError};
+use crate::record::OutputFormatError;
+use crate::zone::{ZoneError, Zones};
+use ut_cli::offset::OffsetError;
+use ut_cli::precision::PrecisionError;
+use ut_cli::week::WeekStartError;
+
+const LOCAL_CONFIG_NAME: &str = ".utrc";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Cannot read config file: '{0}'. error:{1}")]
+    Io(String, io::Error),
+
+    #[error("Wrong config file: '{0}'. error:{1}")]
+    WrongFormat(String, toml::de::Error),
+
+    #[error("Unknown profile: '{0}'. available profiles: [{}]", .1.join(", "))]
+    UnknownProfile(String, Vec<String>),
+
+    #[error("Wrong datetime format. error:{0}")]
+    WrongDateTimeFormat(FormatError),
+
+    #[error("Wrong compare zones. error:{0}")]
+    WrongCompareZones(ZoneError),
+
+    #[error("Wrong SOURCE_DATE_EPOCH: '{0}'. it must be a unix timestamp in seconds.")]
+    WrongSourceDateEpoch(String),
+
+    #[error("Wrong offset in the config or UT_OFFSET. error:{0}")]
+    WrongOffset(OffsetError),
+
+    #[error("Wrong precision in the config or UT_PRECISION. error:{0}")]
+    WrongPrecision(PrecisionError),
+
+    #[error("Wrong week start in the config or UT_WEEK_START. error:{0}")]
+    WrongWeekStart(WeekStartError),
+
+    #[error("Wrong output format in the config or UT_OUTPUT. error:{0}")]
+    WrongOutputFormat(OutputFormatError),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 pub struct Config {
     offset: Option<String>,
     precision: Option<String>,
     datetime_format: Option<String>,
+    week_start: Option<String>,
+    compare_zones: Option<String>,
+    output: Option<String>,
+
+    /// Only taken from the environment, since it is set by build systems.
+    #[serde(skip)]
+    source_date_epoch: Option<String>,
 }
 
 impl Config {
+    #[cfg(test)]
+    pub fn new(
+        offset: Option<&str>,
+        precision: Option<&str>,
+        datetime_format: Option<&str>,
+    ) -> Config {
+        Config {
+            offset: offset.map(String::from),
+            precision: precision.map(String::from),
+            datetime_format: datetime_format.map(String::from),
+            week_start: None,
+            compare_zones: None,
+            output: None,
+            source_date_epoch: None,
+        }
+    }
+
     pub fn from_env() -> Config {
         Config {
             offset: env::var("UT_OFFSET").ok(),
             precision: env::var("UT_PRECISION").ok(),
             datetime_format: env::var("UT_DATETIME_FORMAT").ok(),
+            week_start: env::var("UT_WEEK_START").ok(),
+            compare_zones: env::var("UT_COMPARE_ZONES").ok(),
+            output: env::var("UT_OUTPUT").ok(),
+            source_date_epoch: env::var("SOURCE_DATE_EPOCH").ok().filter(|s| !s.is_empty()),
         }
     }
 
+    /// Loads the nearest `.utrc` and the config file,
+    /// then overlays environment variables and the given profile.
+    pub fn load(profile: Option<&str>) -> Result<Config, ConfigError> {
+        let local = match local_config_path() {
+            Some(path) => ConfigFile::from_path(path)?,
+            None => ConfigFile::default(),
+        };
+        let global = match config_path() {
+            Some(path) if path.exists() => ConfigFile::from_path(path)?,
+            _ => ConfigFile::default(),
+        };
+        let file = local.overlay(global);
+
+        let config = file.resolve(Config::from_env(), profile)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.datetime_format()
+            .map_or(Ok(()), validate_format)
+            .map_err(ConfigError::WrongDateTimeFormat)?;
+
+        self.compare_zones()
+            .map_or(Ok(()), |s| Zones::from_str(s).map(|_| ()))
+            .map_err(ConfigError::WrongCompareZones)
+            .map(|_| ())
+    }
+
     pub fn offset(&self) -> Option<&str> {
         self.offset.as_deref()
     }
@@ -27,6 +134,43 @@ impl Config {
     pub fn datetime_format(&self) -> Option<&str> {
         self.datetime_format.as_deref()
     }
+
+    pub fn week_start(&self) -> Option<&str> {
+        self.week_start.as_deref()
+    }
+
+    pub fn compare_zones(&self) -> Option<&str> {
+        self.compare_zones.as_deref()
+    }
+
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// Returns `SOURCE_DATE_EPOCH`, which is used as the current time if it is set.
+    /// It is validated here, not in `validate`, since commands without the current time ignore it.
+    pub fn source_date_epoch(&self) -> Result<Option<i64>, ConfigError> {
+        self.source_date_epoch
+            .as_deref()
+            .map(|s| {
+                s.parse()
+                    .map_err(|_| ConfigError::WrongSourceDateEpoch(s.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Returns a new config whose values are taken from `other` if they exist.
+    pub fn overlay(self, other: Config) -> Config {
+        Config {
+            offset: other.offset.or(self.offset),
+            precision: other.precision.or(self.precision),
+            datetime_format: other.datetime_format.or(self.datetime_format),
+            week_start: other.week_start.or(self.week_start),
+            compare_zones: other.compare_zones.or(self.compare_zones),
+            output: other.output.or(self.output),
+            source_date_epoch: other.source_date_epoch.or(self.source_date_epoch),
+        }
+    }
 }
 
 impl Default for Config {
@@ -35,6 +179,335 @@ impl Default for Config {
             offset: None,
             precision: None,
             datetime_format: None,
+            week_start: None,
+            compare_zones: None,
+            output: None,
+            source_date_epoch: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(flatten)]
+    base: Config,
+
+    #[serde(default)]
+    profile: BTreeMap<String, Config>,
+}
+
+impl ConfigFile {
+    fn from_path(path: PathBuf) -> Result<ConfigFile, ConfigError> {
+        let name = path.display().to_string();
+        let text = fs::read_to_string(&path).map_err(|e| ConfigError::Io(name.clone(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::WrongFormat(name, e))
+    }
+
+    /// Returns a new config file whose settings and profiles are taken from `other` if they exist.
+    fn overlay(self, other: ConfigFile) -> ConfigFile {
+        let mut profile = self.profile;
+        for (name, config) in other.profile {
+            let merged = match profile.remove(&name) {
+                Some(base) => base.overlay(config),
+                None => config,
+            };
+            profile.insert(name, merged);
+        }
+
+        ConfigFile {
+            base: self.base.overlay(other.base),
+            profile,
+        }
+    }
+
+    fn resolve(self, env: Config, profile: Option<&str>) -> Result<Config, ConfigError> {
+        let ConfigFile {
+            base,
+            profile: mut profiles,
+        } = self;
+        let config = Config::default().overlay(base).overlay(env);
+
+        match profile {
+            Some(name) => profiles
+                .remove(name)
+                .map(|p| config.overlay(p))
+                .ok_or_else(|| {
+                    ConfigError::UnknownProfile(name.to_string(), profiles.into_keys().collect())
+                }),
+            None => Ok(config),
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    env::var_os("UT_CONFIG").map(PathBuf::from).or_else(|| {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join("ut").join("config.toml"))
+    })
+}
+
+/// Searches `.utrc` from the current directory up to the home directory or the root.
+fn local_config_path() -> Option<PathBuf> {
+    let dir = env::current_dir().ok()?;
+    let home = env::var_os("HOME").map(PathBuf::from);
+    find_local_config(&dir, home.as_deref())
+}
+
+fn find_local_config(dir: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    for dir in dir.ancestors() {
+        let path = dir.join(LOCAL_CONFIG_NAME);
+        if path.is_file() {
+            return Some(path);
         }
+        if Some(dir) == home {
+            break;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG_TEXT: &str = r#"
+offset = "+09:00"
+precision = "second"
+
+[profile.prod]
+offset = "0"
+precision = "millisecond"
+datetime_format = "%+"
+
+[profile.local]
+offset = "+09:00"
+"#;
+
+    fn config_file() -> ConfigFile {
+        toml::from_str(CONFIG_TEXT).unwrap()
+    }
+
+    #[test]
+    fn resolve_defaults() {
+        let r = ConfigFile::default().resolve(Config::default(), None);
+        assert_eq!(r.unwrap(), Config::default());
+    }
+
+    #[test]
+    fn resolve_base_over_defaults() {
+        let r = config_file().resolve(Config::default(), None);
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("+09:00"), Some("second"), None)
+        );
+    }
+
+    #[test]
+    fn resolve_profile_over_base() {
+        let r = config_file().resolve(Config::default(), Some("prod"));
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("0"), Some("millisecond"), Some("%+"))
+        );
+
+        let r = config_file().resolve(Config::default(), Some("local"));
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("+09:00"), Some("second"), None)
+        );
+    }
+
+    #[test]
+    fn resolve_profile_over_env() {
+        let env = Config::new(Some("-07:00"), None, Some("%s"));
+
+        let r = config_file().resolve(env.clone(), None);
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("-07:00"), Some("second"), Some("%s"))
+        );
+
+        let r = config_file().resolve(env, Some("prod"));
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("0"), Some("millisecond"), Some("%+"))
+        );
+    }
+
+    #[test]
+    fn validate_datetime_format() {
+        assert!(Config::new(None, None, Some("%Y-%m-%d")).validate().is_ok());
+        assert!(Config::new(None, None, None).validate().is_ok());
+
+        let r = Config::new(None, None, Some("%Y-%m-%d %Q")).validate();
+        assert!(matches!(r, Err(ConfigError::WrongDateTimeFormat(_))));
+    }
+
+    #[test]
+    fn week_start() {
+        let file: ConfigFile = toml::from_str("week_start = \"sunday\"").unwrap();
+        let r = file.resolve(Config::default(), None).unwrap();
+        assert_eq!(r.week_start(), Some("sunday"));
+
+        let file: ConfigFile = toml::from_str("week_start = \"sunday\"").unwrap();
+        let env = Config {
+            week_start: Some("monday".to_string()),
+            ..Config::default()
+        };
+        let r = file.resolve(env, None).unwrap();
+        assert_eq!(r.week_start(), Some("monday"));
+    }
+
+    #[test]
+    fn compare_zones() {
+        let file: ConfigFile = toml::from_str("compare_zones = \"UTC,+09:00\"").unwrap();
+        let r = file.resolve(Config::default(), None).unwrap();
+        assert_eq!(r.compare_zones(), Some("UTC,+09:00"));
+        assert!(r.validate().is_ok());
+
+        let config = Config {
+            compare_zones: Some("UTC,Asia/Tokyo".to_string()),
+            ..Config::default()
+        };
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::WrongCompareZones(_))
+        ));
+    }
+
+    #[test]
+    fn source_date_epoch() {
+        let env = Config {
+            source_date_epoch: Some("1560762129".to_string()),
+            ..Config::default()
+        };
+        let r = config_file().resolve(env, Some("prod")).unwrap();
+        assert_eq!(r.source_date_epoch().unwrap(), Some(1_560_762_129));
+        assert!(r.validate().is_ok());
+
+        assert_eq!(
+            config_file()
+                .resolve(Config::default(), None)
+                .unwrap()
+                .source_date_epoch()
+                .unwrap(),
+            None
+        );
+
+        // a wrong value is an error only where it is used.
+        let config = Config {
+            source_date_epoch: Some("2019-06-17".to_string()),
+            ..Config::default()
+        };
+        assert!(config.validate().is_ok());
+        assert!(matches!(
+            config.source_date_epoch(),
+            Err(ConfigError::WrongSourceDateEpoch(_))
+        ));
+
+        // it can't be set in config files.
+        let file: ConfigFile = toml::from_str("source_date_epoch = \"0\"").unwrap();
+        assert_eq!(file.base, Config::default());
+    }
+
+    #[test]
+    fn resolve_unknown_profile() {
+        let r = config_file().resolve(Config::default(), Some("staging"));
+        match r {
+            Err(ConfigError::UnknownProfile(name, profiles)) => {
+                assert_eq!(name, "staging");
+                assert_eq!(profiles, vec!["local".to_string(), "prod".to_string()]);
+            }
+            _ => panic!("unexpected result: {:?}", r),
+        }
+    }
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let path = env::temp_dir().join(format!("ut-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(path.join("a/b/c")).unwrap();
+            TempDir(path)
+        }
+
+        fn write_utrc(&self, dir: &str, text: &str) -> PathBuf {
+            let path = self.0.join(dir).join(LOCAL_CONFIG_NAME);
+            fs::write(&path, text).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn find_nearest_local_config() {
+        let tmp = TempDir::new("nearest");
+        let start = tmp.0.join("a/b/c");
+        assert_eq!(find_local_config(&start, Some(&tmp.0)), None);
+
+        let root = tmp.write_utrc("", "offset = \"0\"");
+        assert_eq!(find_local_config(&start, Some(&tmp.0)), Some(root));
+
+        let a = tmp.write_utrc("a", "offset = \"+01:00\"");
+        assert_eq!(find_local_config(&start, Some(&tmp.0)), Some(a));
+
+        let c = tmp.write_utrc("a/b/c", "offset = \"+03:00\"");
+        assert_eq!(find_local_config(&start, Some(&tmp.0)), Some(c.clone()));
+
+        let file = ConfigFile::from_path(c).unwrap();
+        assert_eq!(file.base, Config::new(Some("+03:00"), None, None));
+    }
+
+    #[test]
+    fn find_local_config_stops_at_home() {
+        let tmp = TempDir::new("home");
+        let start = tmp.0.join("a/b/c");
+        tmp.write_utrc("", "offset = \"0\"");
+
+        let home = tmp.0.join("a");
+        assert_eq!(find_local_config(&start, Some(&home)), None);
+
+        let a = tmp.write_utrc("a", "offset = \"+01:00\"");
+        assert_eq!(find_local_config(&start, Some(&home)), Some(a));
+    }
+
+    #[test]
+    fn local_config_under_global() {
+        let local: ConfigFile = toml::from_str(
+            r#"
+offset = "+01:00"
+datetime_format = "%s"
+
+[profile.local]
+precision = "millisecond"
+datetime_format = "%c"
+"#,
+        )
+        .unwrap();
+
+        let r = local
+            .overlay(config_file())
+            .resolve(Config::default(), Some("local"));
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("+09:00"), Some("millisecond"), Some("%c"))
+        );
+
+        let local: ConfigFile = toml::from_str("datetime_format = \"%s\"").unwrap();
+        let r = local
+            .overlay(config_file())
+            .resolve(Config::default(), None);
+        assert_eq!(
+            r.unwrap(),
+            Config::new(Some("+09:00"), Some("second"), Some("%s"))
+        );
     }
 }
@@ -1,13 +1,16 @@
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::fmt::Debug;
 use std::str::FromStr;
 
-use regex::Regex;
 use thiserror::Error;
 
 use crate::parse::extract_number;
-use crate::validate::{validate_number, IntoValidationError};
-use chrono::{Date, LocalResult, NaiveDate, NaiveTime, TimeZone};
+use crate::pattern;
+use crate::validate::{validate_number, IntoValidationError, ValidationMessage};
+use chrono::{
+    Date, DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike,
+};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum YmdError {
@@ -25,11 +28,31 @@ pub enum YmdError {
 
     #[error("Wrong date: '{0}'.")]
     WrongDate(String),
+
+    #[error("Wrong year: '{0}'. two-digit years are ambiguous without a pivot.")]
+    TwoDigitYear(String),
 }
 
 impl IntoValidationError for YmdError {
     fn into_validation_error(self) -> String {
-        format!("{:?}", self)
+        use YmdError::*;
+        let (problem, input) = match self {
+            WrongFormat(s) => ("Wrong date: text is not in a date format.".to_string(), s),
+            WrongYear(s, min, max) => (
+                format!("Wrong date: year must be between {} and {}.", min, max),
+                s,
+            ),
+            WrongMonth(s) => ("Wrong date: month must be between 1 and 12.".to_string(), s),
+            WrongDay(s) => ("Wrong date: day must be between 1 and 31.".to_string(), s),
+            WrongDate(s) => ("Wrong date: the date does not exist.".to_string(), s),
+            TwoDigitYear(s) => (
+                "Wrong date: two-digit years are ambiguous without a pivot.".to_string(),
+                s,
+            ),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted("`yyyyMMdd`, `yyyy-MM-dd` or `yyyy/MM/dd`")
+            .to_string()
     }
 }
 
@@ -60,16 +83,28 @@ impl Ymd {
     }
 }
 
-impl FromStr for Ymd {
-    type Err = YmdError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"^(?:(\d{4})(\d{2})(\d{2})|(\d{4})[-/](\d{1,2})[-/](\d{1,2}))$")
-            .expect("wrong regex pattern");
+/// Two-digit years below the pivot are in the 2000s, and the others are in the 1900s, like POSIX.
+const YEAR_PIVOT: i32 = 69;
 
-        re.captures(s)
+impl Ymd {
+    /// Parses the text like `FromStr`. With `wrap_year`, two-digit years like `19-06-17` are
+    /// also accepted, and read with the pivot: 00-68 are 2000-2068, and 69-99 are 1969-1999.
+    pub fn from_str_with(s: &str, wrap_year: bool) -> Result<Ymd, YmdError> {
+        pattern::ymd(s)
             .map(|capture| {
-                let y = extract_number(capture.get(1).or_else(|| capture.get(4)));
+                let year = capture.get(1).or_else(|| capture.get(4));
+                let y = match (year.map(str::len), wrap_year) {
+                    (Some(2), false) => return Err(YmdError::TwoDigitYear(s.to_string())),
+                    (Some(2), true) => {
+                        let y: i32 = extract_number(year);
+                        if y < YEAR_PIVOT {
+                            2000 + y
+                        } else {
+                            1900 + y
+                        }
+                    }
+                    _ => extract_number(year),
+                };
                 let m = extract_number(capture.get(2).or_else(|| capture.get(5)));
                 let d = extract_number(capture.get(3).or_else(|| capture.get(6)));
 
@@ -84,6 +119,32 @@ impl FromStr for Ymd {
     }
 }
 
+impl FromStr for Ymd {
+    type Err = YmdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ymd::from_str_with(s, false)
+    }
+}
+
+/// Validates ymd text including whether the date exists in the calendar.
+pub fn validate_ymd(s: String) -> Result<(), String> {
+    validate_ymd_with(false)(s)
+}
+
+/// Validates ymd text like `validate_ymd`, with two-digit years if `wrap_year`.
+pub fn validate_ymd_with(wrap_year: bool) -> impl Fn(String) -> Result<(), String> {
+    move |s| {
+        Ymd::from_str_with(&s, wrap_year)
+            .and_then(|ymd| {
+                TryInto::<NaiveDate>::try_into(ymd)
+                    .map(|_| ())
+                    .map_err(|_| YmdError::WrongDate(s.clone()))
+            })
+            .map_err(|e| e.into_validation_error())
+    }
+}
+
 impl TryInto<NaiveDate> for Ymd {
     type Error = YmdError;
 
@@ -110,7 +171,16 @@ pub enum HmsError {
 
 impl IntoValidationError for HmsError {
     fn into_validation_error(self) -> String {
-        format!("{:?}", self)
+        use HmsError::*;
+        let (problem, input) = match self {
+            WrongFormat(s) => ("Wrong time: text is not in a time format.", s),
+            WrongHour(s) => ("Wrong time: hour must be between 0 and 23.", s),
+            WrongMinute(s) => ("Wrong time: minute must be between 0 and 59.", s),
+            WrongSecond(s) => ("Wrong time: second must be between 0 and 59.", s),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted("`HHmmss` or `HH:mm:ss`")
+            .to_string()
     }
 }
 
@@ -125,10 +195,7 @@ impl FromStr for Hms {
     type Err = HmsError;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"^(?:(\d{2})(\d{2})(\d{2})|(\d{1,2})[:](\d{1,2})[:](\d{1,2}))$")
-            .expect("wrong regex pattern");
-
-        re.captures(text)
+        pattern::hms(text)
             .map(|capture| {
                 let h = extract_number(capture.get(1).or_else(|| capture.get(4)));
                 let m = extract_number(capture.get(2).or_else(|| capture.get(5)));
@@ -149,16 +216,221 @@ impl FromStr for Hms {
 
 impl Into<NaiveTime> for Hms {
     fn into(self) -> NaiveTime {
-        NaiveTime::from_hms(self.h, self.m, self.s)
+        NaiveTime::from_hms_opt(self.h, self.m, self.s)
+            .expect("hour, minute and second are validated when parsed")
+    }
+}
+
+/// Converts a local datetime into the timezone without panicking around DST transitions.
+/// The earlier instant is chosen for an ambiguous time, and the first valid time after
+/// the gap is chosen for a time which does not exist.
+pub fn resolve_local_datetime<Tz: TimeZone>(tz: &Tz, local: NaiveDateTime) -> DateTime<Tz> {
+    const MAX_GAP_MINUTES: i64 = 24 * 60;
+
+    (0..=MAX_GAP_MINUTES)
+        .find_map(|minutes| {
+            let shifted = if minutes == 0 {
+                local
+            } else {
+                // the first valid time after the gap is a whole minute in real timezones.
+                local
+                    .checked_add_signed(Duration::minutes(minutes))?
+                    .with_second(0)?
+                    .with_nanosecond(0)?
+            };
+            match tz.from_local_datetime(&shifted) {
+                LocalResult::Single(dt) => Some(dt),
+                LocalResult::Ambiguous(earliest, _) => Some(earliest),
+                LocalResult::None => None,
+            }
+        })
+        .unwrap_or_else(|| tz.from_utc_datetime(&local))
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum EpochDayError {
+    #[error("Epoch day out of range: {0}. days must be between {1} and {2}.")]
+    OutOfRange(i64, i64, i64),
+}
+
+fn epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(1970, 1, 1).expect("never happen")
+}
+
+/// Returns the number of days from 1970-01-01 to the date, like PostgreSQL stores dates.
+/// Dates before the epoch are negative, like -1 for 1969-12-31.
+pub fn epoch_day(date: NaiveDate) -> i64 {
+    date.signed_duration_since(epoch()).num_days()
+}
+
+/// Returns the date of the number of days from 1970-01-01.
+pub fn from_epoch_day(days: i64) -> Result<NaiveDate, EpochDayError> {
+    let out_of_range =
+        || EpochDayError::OutOfRange(days, epoch_day(NaiveDate::MIN), epoch_day(NaiveDate::MAX));
+    i32::try_from(days)
+        .ok()
+        .and_then(|days| days.checked_add(epoch().num_days_from_ce()))
+        .and_then(NaiveDate::from_num_days_from_ce_opt)
+        .ok_or_else(out_of_range)
+}
+
+#[cfg(test)]
+pub mod test_zones {
+    use std::fmt::Debug;
+    use std::marker::PhantomData;
+
+    use chrono::{Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone};
+
+    /// A transition of a timezone: the utc datetime, and the offsets before and after it in hours.
+    pub trait Rule: Debug + Copy + PartialEq {
+        const AT: (i32, u32, u32, u32);
+        const OFFSETS: [i32; 2];
+    }
+
+    /// A timezone whose offset changes once by the rule, like a DST transition.
+    /// The rule is a type, since `TimeZone::from_offset` restores the timezone from an offset.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct Transition<R: Rule>(PhantomData<R>);
+
+    impl<R: Rule> Transition<R> {
+        pub fn new() -> Self {
+            Transition(PhantomData)
+        }
+
+        fn transition() -> NaiveDateTime {
+            let (y, m, d, h) = R::AT;
+            NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(h, 0, 0)
+                .unwrap()
+        }
+
+        pub fn offsets() -> [FixedOffset; 2] {
+            let [before, after] = R::OFFSETS;
+            [
+                FixedOffset::east_opt(before * 3600).unwrap(),
+                FixedOffset::east_opt(after * 3600).unwrap(),
+            ]
+        }
+    }
+
+    impl<R: Rule> Default for Transition<R> {
+        fn default() -> Self {
+            Transition::new()
+        }
+    }
+
+    impl<R: Rule> TimeZone for Transition<R> {
+        type Offset = FixedOffset;
+
+        fn from_offset(_offset: &FixedOffset) -> Self {
+            Transition::new()
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> LocalResult<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            let candidates: Vec<FixedOffset> = Self::offsets()
+                .iter()
+                .copied()
+                .filter(|offset| {
+                    let utc = *local - Duration::seconds(i64::from(offset.fix().local_minus_utc()));
+                    self.offset_from_utc_datetime(&utc) == *offset
+                })
+                .collect();
+
+            match candidates.as_slice() {
+                [] => LocalResult::None,
+                [offset] => LocalResult::Single(*offset),
+                [a, b, ..] => LocalResult::Ambiguous(*a, *b),
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_hms_opt(0, 0, 0).unwrap())
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            let [before, after] = Self::offsets();
+            if *utc < Self::transition() {
+                before
+            } else {
+                after
+            }
+        }
+    }
+
+    /// Skips local midnight on 2018-11-04, like America/Sao_Paulo.
+    /// The clock jumps from 00:00 (-03:00) to 01:00 (-02:00).
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct SkippedMidnightRule;
+
+    impl Rule for SkippedMidnightRule {
+        const AT: (i32, u32, u32, u32) = (2018, 11, 4, 3);
+        const OFFSETS: [i32; 2] = [-3, -2];
+    }
+
+    pub type SkippedMidnight = Transition<SkippedMidnightRule>;
+
+    /// Repeats the hour from local midnight on 2019-04-07, when DST ends at 01:00.
+    /// The clock goes back from 01:00 (-02:00) to 00:00 (-03:00).
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct RepeatedMidnightRule;
+
+    impl Rule for RepeatedMidnightRule {
+        const AT: (i32, u32, u32, u32) = (2019, 4, 7, 3);
+        const OFFSETS: [i32; 2] = [-2, -3];
+    }
+
+    pub type RepeatedMidnight = Transition<RepeatedMidnightRule>;
+
+    /// Skips the whole day of 2011-12-30, like Pacific/Apia.
+    /// The clock jumps from 2011-12-30 00:00 (-10:00) to 2011-12-31 00:00 (+14:00).
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct SkippedDayRule;
+
+    impl Rule for SkippedDayRule {
+        const AT: (i32, u32, u32, u32) = (2011, 12, 30, 10);
+        const OFFSETS: [i32; 2] = [-10, 14];
+    }
+
+    pub type SkippedDay = Transition<SkippedDayRule>;
+
+    /// Springs forward from 02:00 (-08:00) to 03:00 (-07:00) on 2019-03-10, like America/Los_Angeles.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct SpringForwardRule;
+
+    impl Rule for SpringForwardRule {
+        const AT: (i32, u32, u32, u32) = (2019, 3, 10, 10);
+        const OFFSETS: [i32; 2] = [-8, -7];
     }
+
+    pub type SpringForward = Transition<SpringForwardRule>;
+
+    /// Falls back from 02:00 (-07:00) to 01:00 (-08:00) on 2019-11-03, like America/Los_Angeles.
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    pub struct FallBackRule;
+
+    impl Rule for FallBackRule {
+        const AT: (i32, u32, u32, u32) = (2019, 11, 3, 9);
+        const OFFSETS: [i32; 2] = [-7, -8];
+    }
+
+    pub type FallBack = Transition<FallBackRule>;
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::datetime::{Hms, Ymd};
-    use chrono::Local;
+    use crate::datetime::{
+        epoch_day, from_epoch_day, validate_ymd, validate_ymd_with, EpochDayError, Hms, Ymd,
+        YmdError,
+    };
+    use crate::validate::IntoValidationError;
+    use chrono::{Local, NaiveDate};
 
     fn ymd(y: i32, m: u32, d: u32) -> Ymd {
         Ymd { y, m, d }
@@ -184,6 +456,62 @@ mod tests {
         assert!(r.unwrap().into_date(&Local).is_err());
     }
 
+    #[test]
+    fn ymd_wrap_year() {
+        assert_eq!(
+            Ymd::from_str("19-06-17"),
+            Err(YmdError::TwoDigitYear("19-06-17".to_string()))
+        );
+        assert_eq!(Ymd::from_str_with("19-06-17", true), Ok(ymd(2019, 6, 17)));
+        assert_eq!(Ymd::from_str_with("00/1/2", true), Ok(ymd(2000, 1, 2)));
+        assert_eq!(Ymd::from_str_with("68-12-31", true), Ok(ymd(2068, 12, 31)));
+        assert_eq!(Ymd::from_str_with("69-01-01", true), Ok(ymd(1969, 1, 1)));
+        assert_eq!(Ymd::from_str_with("99-12-31", true), Ok(ymd(1999, 12, 31)));
+        // four-digit years are not wrapped, and compact dates need them.
+        assert_eq!(Ymd::from_str_with("2019-06-17", true), Ok(ymd(2019, 6, 17)));
+        assert_eq!(
+            Ymd::from_str_with("190617", true),
+            Err(YmdError::WrongFormat("190617".to_string()))
+        );
+        assert_eq!(
+            Ymd::from_str_with("190-06-17", true),
+            Err(YmdError::WrongFormat("190-06-17".to_string()))
+        );
+
+        let validate = validate_ymd_with(true);
+        assert!(validate("20-02-29".to_string()).is_ok());
+        assert!(validate("19-02-29".to_string()).is_err());
+        assert!(validate_ymd("19-06-17".to_string()).is_err());
+    }
+
+    #[test]
+    fn ymd_validate() {
+        let validate = |s: &str| validate_ymd(s.to_string());
+
+        assert!(validate("20190621").is_ok());
+        assert!(validate("2019-06-21").is_ok());
+        assert!(validate("2019/06/21").is_ok());
+        assert!(validate("2019/6/21").is_ok());
+        assert!(validate("2020/2/29").is_ok());
+        assert!(validate("2019-01-31").is_ok());
+
+        assert_eq!(
+            validate("2019/2/29"),
+            Err(YmdError::WrongDate("2019/2/29".to_string()).into_validation_error())
+        );
+        assert_eq!(
+            validate("2019-02-30"),
+            Err(YmdError::WrongDate("2019-02-30".to_string()).into_validation_error())
+        );
+        assert_eq!(
+            validate("2019-04-31"),
+            Err(YmdError::WrongDate("2019-04-31".to_string()).into_validation_error())
+        );
+        assert!(validate("2019-13-01").is_err());
+        assert!(validate("2019-01-32").is_err());
+        assert!(validate("190621").is_err());
+    }
+
     #[test]
     fn hms_from_str() {
         assert_eq!(Hms::from_str("112233"), Ok(hms(11, 22, 33)));
@@ -202,4 +530,31 @@ mod tests {
         assert!(Hms::from_str("11:22:").is_err());
         assert!(Hms::from_str("::").is_err());
     }
+
+    #[test]
+    fn epoch_days() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let cases = [
+            (date(1970, 1, 1), 0),
+            (date(1970, 1, 2), 1),
+            (date(1969, 12, 31), -1),
+            (date(1900, 1, 1), -25_567),
+            (date(2019, 6, 17), 18_064),
+        ];
+        for (date, days) in cases.iter().copied() {
+            assert_eq!(epoch_day(date), days);
+            assert_eq!(from_epoch_day(days), Ok(date));
+        }
+
+        let max = epoch_day(NaiveDate::MAX);
+        assert_eq!(from_epoch_day(max), Ok(NaiveDate::MAX));
+        assert!(matches!(
+            from_epoch_day(max + 1),
+            Err(EpochDayError::OutOfRange(..))
+        ));
+        assert!(matches!(
+            from_epoch_day(i64::MIN),
+            Err(EpochDayError::OutOfRange(..))
+        ));
+    }
 }
@@ -1,23 +1,24 @@
+use std::fmt;
 use std::str::FromStr;
 
-use regex::Regex;
 use thiserror::Error;
 
-use crate::find::FindByName;
+use crate::find::{FindByName, FindOptions, PossibleNames};
+use crate::pattern;
 use crate::timedelta::TimeDeltaBuilder;
 use crate::unit::{TimeUnit, TimeUnitError};
-use crate::validate::IntoValidationError;
+use crate::validate::{IntoValidationError, ValidationMessage};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum DeltaItemError {
     #[error("Wrong format. error:{0}")]
     WrongFormat(String),
 
-    #[error("Wrong value. error:{0}")]
+    #[error("Wrong value: '{0}'. value must be a 32-bit integer.")]
     WrongValue(String),
 
-    #[error("Wrong unit. error:{0}")]
-    WrongUnit(TimeUnitError),
+    #[error("Wrong unit: '{0}'. error:{1}")]
+    WrongUnit(String, TimeUnitError),
 }
 
 #[cfg(test)]
@@ -41,23 +42,39 @@ impl DeltaItemError {
     pub fn is_wrong_unit(&self) -> bool {
         use DeltaItemError::*;
         match self {
-            WrongUnit(_) => true,
+            WrongUnit(_, _) => true,
             _ => false,
         }
     }
 }
 
+/// The accepted forms of a delta, by prefix matching or by `--strict-names`.
+const ACCEPTED_DELTA: &str = "a number followed by a unit, like `1d`, `-3h` or `+15min`, \
+    or an ISO 8601 duration like `P1DT12H`";
+const ACCEPTED_EXACT_DELTA: &str =
+    "a number followed by a possible name, like `1day` or `-3hour`, \
+    or an ISO 8601 duration like `P1DT12H`";
+
 impl IntoValidationError for DeltaItemError {
     fn into_validation_error(self) -> String {
         use DeltaItemError::*;
-        match &self {
-            WrongFormat(_) => format!(
-                "{} DELTA must consist of number and unit. See examples on help.",
-                self
-            ),
-            WrongValue(_) => format!("{} DELTA value must be a number.", self),
-            WrongUnit(e) => format!("{} {}", self, e),
+        let accepted = ACCEPTED_DELTA;
+        match self {
+            WrongFormat(s) => {
+                ValidationMessage::new("Wrong delta: text is not in a delta format.", s)
+                    .accepted(accepted)
+            }
+            WrongValue(s) => {
+                ValidationMessage::new("Wrong delta: value must be a 32-bit integer.", s)
+                    .accepted(accepted)
+            }
+            WrongUnit(s, TimeUnitError::WrongName(e)) => {
+                ValidationMessage::from_find_error("delta unit", &e, TimeUnit::possible_names())
+                    .input(s)
+                    .accepted(accepted)
+            }
         }
+        .to_string()
     }
 }
 
@@ -68,54 +85,223 @@ pub struct DeltaItem {
 }
 
 impl DeltaItem {
-    #[cfg(test)]
     pub fn new(unit: TimeUnit, value: i32) -> DeltaItem {
         DeltaItem { unit, value }
     }
 
+    pub fn unit(self) -> TimeUnit {
+        self.unit
+    }
+
+    pub fn value(self) -> i32 {
+        self.value
+    }
+
     pub fn apply_timedelta_builder(self, builder: TimeDeltaBuilder) -> TimeDeltaBuilder {
-        match self.unit {
-            TimeUnit::Year => builder.add_years(self.value),
-            TimeUnit::Month => builder.add_months(self.value),
-            TimeUnit::Day => builder.add_days(self.value),
-            TimeUnit::Hour => builder.add_hours(self.value),
-            TimeUnit::Minute => builder.add_minutes(self.value),
-            TimeUnit::Second => builder.add_seconds(self.value),
-            TimeUnit::MilliSecond => builder.add_milliseconds(self.value),
+        add_units(builder, self.unit, i64::from(self.value))
+            .expect("centuries of an i32 value must fit in i64")
+    }
+}
+
+/// Parses an item like `3000000000s` with a value in `i64`, and adds it to the builder.
+/// The fields of a `TimeDelta` are wider than the value of a `DeltaItem`,
+/// so the items of `TimeDelta::to_short_string` are parsed by this.
+pub fn apply_item(s: &str, builder: TimeDeltaBuilder) -> Result<TimeDeltaBuilder, DeltaItemError> {
+    let caps = pattern::delta_item(s).ok_or_else(|| DeltaItemError::WrongFormat(s.to_string()))?;
+    let value = caps
+        .get(1)
+        .unwrap()
+        .parse::<i64>()
+        .map_err(|_| DeltaItemError::WrongValue(s.to_string()))?;
+    let unit = TimeUnit::find_by_name(caps.get(2).unwrap())
+        .map_err(|e| DeltaItemError::WrongUnit(s.to_string(), e))?;
+    add_units(builder, unit, value).ok_or_else(|| DeltaItemError::WrongValue(s.to_string()))
+}
+
+/// Adds the value in the unit, or returns `None` if centuries or decades overflow years.
+fn add_units(builder: TimeDeltaBuilder, unit: TimeUnit, value: i64) -> Option<TimeDeltaBuilder> {
+    Some(match unit {
+        TimeUnit::Century => builder.add_years(value.checked_mul(100)?),
+        TimeUnit::Decade => builder.add_years(value.checked_mul(10)?),
+        TimeUnit::Year => builder.add_years(value),
+        TimeUnit::Month => builder.add_months(value),
+        TimeUnit::Day => builder.add_days(value),
+        TimeUnit::Hour => builder.add_hours(value),
+        TimeUnit::Minute => builder.add_minutes(value),
+        TimeUnit::Second => builder.add_seconds(value),
+        TimeUnit::MilliSecond => builder.add_milliseconds(value),
+    })
+}
+
+/// Renders the item in compact form like `-3d`, which can be parsed back.
+impl fmt::Display for DeltaItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            TimeUnit::Century => "c",
+            TimeUnit::Decade => "dec",
+            TimeUnit::Year => "y",
+            TimeUnit::Month => "mon",
+            TimeUnit::Day => "d",
+            TimeUnit::Hour => "h",
+            TimeUnit::Minute => "min",
+            TimeUnit::Second => "s",
+            TimeUnit::MilliSecond => "ms",
+        };
+        write!(f, "{}{}", self.value, unit)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DeltaItem {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DeltaItem {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        DeltaItem::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Splits compound delta text like `1y2mon3d` into the texts of each item.
+pub fn split_items(s: &str) -> Result<Vec<&str>, DeltaItemError> {
+    pattern::delta_items(s).ok_or_else(|| DeltaItemError::WrongFormat(s.to_string()))
+}
+
+/// Returns whether the text looks like an ISO 8601 duration, like `P1Y2M10DT2H30M` or `-P1D`.
+pub fn is_iso8601(s: &str) -> bool {
+    s.trim_start_matches(['+', '-']).starts_with('P')
+}
+
+/// Parses an ISO 8601 duration like `P1Y2M10DT2H30M` into items of each component.
+/// Weeks cannot be combined with other components, and a fraction is allowed to seconds only.
+pub fn parse_iso8601(s: &str) -> Result<Vec<DeltaItem>, DeltaItemError> {
+    let caps = pattern::iso8601(s).ok_or_else(|| DeltaItemError::WrongFormat(s.to_string()))?;
+
+    let sign = if caps.get(1) == Some("-") { -1 } else { 1 };
+    let value = |i: usize| {
+        caps.get(i)
+            .map(|m| {
+                m.parse::<i32>()
+                    .ok()
+                    .and_then(|v| v.checked_mul(sign))
+                    .ok_or_else(|| DeltaItemError::WrongValue(s.to_string()))
+            })
+            .transpose()
+    };
+
+    let has_time = s.contains('T');
+    let week = value(4)?;
+    let date_parts = [
+        (TimeUnit::Year, 2),
+        (TimeUnit::Month, 3),
+        (TimeUnit::Day, 5),
+    ];
+    let time_parts = [
+        (TimeUnit::Hour, 6),
+        (TimeUnit::Minute, 7),
+        (TimeUnit::Second, 8),
+    ];
+    let mut items = Vec::new();
+    for (unit, i) in date_parts.iter().chain(time_parts.iter()) {
+        if let Some(value) = value(*i)? {
+            items.push(DeltaItem::new(*unit, value));
         }
     }
+    if let Some(fraction) = caps.get(9) {
+        // pad the fraction to milliseconds, like `.5` to 500.
+        let millis = format!("{:0<3}", fraction).parse::<i32>().unwrap_or(0);
+        items.push(DeltaItem::new(TimeUnit::MilliSecond, millis * sign));
+    }
+
+    // `T` must be followed by a time component, like `PT30M`.
+    if has_time && !items.iter().any(|d| d.unit() >= TimeUnit::Hour) {
+        return Err(DeltaItemError::WrongFormat(s.to_string()));
+    }
+    match week {
+        Some(_) if !items.is_empty() => Err(DeltaItemError::WrongFormat(s.to_string())),
+        Some(weeks) => weeks
+            .checked_mul(7)
+            .map(|days| vec![DeltaItem::new(TimeUnit::Day, days)])
+            .ok_or_else(|| DeltaItemError::WrongValue(s.to_string())),
+        None if items.is_empty() => Err(DeltaItemError::WrongFormat(s.to_string())),
+        None => Ok(items),
+    }
 }
 
-impl FromStr for DeltaItem {
-    type Err = DeltaItemError;
+/// Parses the text of a `--delta`, which is an item like `3d` or an ISO 8601 duration.
+pub fn parse_items(s: &str) -> Result<Vec<DeltaItem>, DeltaItemError> {
+    parse_items_with(s, FindOptions::default())
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let re = Regex::new(r"^([-+]?\d+)([a-zA-Z]+)$").expect("wrong regex pattern.");
-        let maybe_caps = re.captures(s);
+/// Parses the text of a `--delta`, matching the unit by the options.
+pub fn parse_items_with(s: &str, options: FindOptions) -> Result<Vec<DeltaItem>, DeltaItemError> {
+    if is_iso8601(s) {
+        parse_iso8601(s)
+    } else {
+        DeltaItem::from_str_with(s, options).map(|item| vec![item])
+    }
+}
+
+/// Validates a `--delta`. With `--strict-names`, the message says units must be possible names.
+pub fn validate_delta(options: FindOptions) -> impl Fn(String) -> Result<(), String> {
+    move |s| {
+        parse_items_with(&s, options).map(|_| ()).map_err(|e| {
+            let message = e.into_validation_error();
+            if options.strict {
+                message.replace(ACCEPTED_DELTA, ACCEPTED_EXACT_DELTA)
+            } else {
+                message
+            }
+        })
+    }
+}
 
-        maybe_caps
+impl DeltaItem {
+    /// Parses an item like `3d`.
+    /// With strict options, the unit must be a possible name like `minute`, and aliases like `d` are rejected.
+    pub fn from_str_with(s: &str, options: FindOptions) -> Result<DeltaItem, DeltaItemError> {
+        pattern::delta_item(s)
             .map(|caps| {
                 let r_value = caps
                     .get(1)
                     .unwrap()
-                    .as_str()
                     .parse::<i32>()
-                    .map_err(|e| DeltaItemError::WrongValue(e.to_string()));
+                    .map_err(|_| DeltaItemError::WrongValue(s.to_string()));
 
-                TimeUnit::find_by_name(caps.get(2).unwrap().as_str())
-                    .map_err(DeltaItemError::WrongUnit)
+                let name = caps.get(2).unwrap();
+                let unit = if options.strict {
+                    TimeUnit::find_by_canonical_name(name)
+                } else {
+                    TimeUnit::find_by_name(name)
+                };
+                unit.map_err(|e| DeltaItemError::WrongUnit(s.to_string(), e))
                     .and_then(|unit| r_value.map(|value| DeltaItem { unit, value }))
             })
             .unwrap_or_else(|| Err(DeltaItemError::WrongFormat(s.to_string())))
     }
 }
 
+impl FromStr for DeltaItem {
+    type Err = DeltaItemError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DeltaItem::from_str_with(s, FindOptions::default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use crate::delta::DeltaItem;
-    use crate::unit::TimeUnit;
+    use crate::delta::{
+        parse_iso8601, parse_items, parse_items_with, split_items, DeltaItem, DeltaItemError,
+    };
+    use crate::find::{FindError, FindOptions, PossibleNames};
+    use crate::unit::{TimeUnit, TimeUnitError};
 
     #[test]
     fn delta_from_str() {
@@ -148,4 +334,235 @@ mod tests {
         assert!(r.is_err());
         assert!(r.err().unwrap().is_wrong_unit());
     }
+
+    #[test]
+    fn delta_from_str_strict() {
+        let strict = FindOptions { strict: true };
+        assert_eq!(
+            DeltaItem::from_str_with("1minute", strict),
+            Ok(DeltaItem::new(TimeUnit::Minute, 1))
+        );
+        assert_eq!(
+            DeltaItem::from_str_with("-2MILLISECOND", strict),
+            Ok(DeltaItem::new(TimeUnit::MilliSecond, -2))
+        );
+        for name in TimeUnit::possible_names() {
+            assert!(DeltaItem::from_str_with(&format!("1{}", name), strict).is_ok());
+        }
+
+        // short names and aliases, which are accepted without the options, are rejected.
+        for alias in ["c", "dec", "y", "mon", "d", "h", "min", "s", "ms"].iter() {
+            let r = DeltaItem::from_str_with(&format!("1{}", alias), strict);
+            assert!(r.err().unwrap().is_wrong_unit(), "{}", alias);
+            assert!(
+                DeltaItem::from_str(&format!("1{}", alias)).is_ok(),
+                "{}",
+                alias
+            );
+        }
+        let r = DeltaItem::from_str_with("1m", strict);
+        assert!(r.err().unwrap().is_wrong_unit());
+        assert_eq!(
+            DeltaItem::from_str_with("1y", strict),
+            Err(DeltaItemError::WrongUnit(
+                "1y".to_string(),
+                TimeUnitError::WrongName(FindError::Suggestion(
+                    "y".to_string(),
+                    vec!["year".to_string()]
+                ))
+            ))
+        );
+        assert_eq!(
+            DeltaItem::from_str("3mon"),
+            Ok(DeltaItem::new(TimeUnit::Month, 3))
+        );
+
+        // ISO 8601 durations have no unit names.
+        assert_eq!(
+            parse_items_with("PT1M", strict),
+            Ok(vec![DeltaItem::new(TimeUnit::Minute, 1)])
+        );
+    }
+
+    #[test]
+    fn large_milliseconds() {
+        use chrono::{TimeZone, Utc};
+
+        use crate::timedelta::{ApplyDateTime, TimeDeltaBuilder};
+
+        let apply = |items: &[&str]| {
+            let delta = items
+                .iter()
+                .map(|s| DeltaItem::from_str(s).unwrap())
+                .fold(TimeDeltaBuilder::default(), |b, d| {
+                    d.apply_timedelta_builder(b)
+                })
+                .try_build()
+                .unwrap();
+            delta
+                .apply_datetime(Utc.timestamp_millis(0))
+                .unwrap()
+                .timestamp_millis()
+        };
+
+        assert_eq!(apply(&["2147483647ms"]), 2_147_483_647);
+        assert_eq!(apply(&["-2147483648ms"]), -2_147_483_648);
+        assert_eq!(apply(&["2147483647ms", "2147483647ms"]), 4_294_967_294);
+        assert_eq!(apply(&["-2147483648ms", "-2147483648ms"]), -4_294_967_296);
+        assert_eq!(apply(&["2147483647ms", "-2147483648ms"]), -1);
+        assert_eq!(apply(&["100000000ms", "1s"]), 100_001_000);
+    }
+
+    #[test]
+    fn decades_and_centuries() {
+        use chrono::{TimeZone, Utc};
+
+        use crate::timedelta::{ApplyDateTime, TimeDeltaBuilder};
+
+        assert_eq!(
+            DeltaItem::from_str("2dec"),
+            Ok(DeltaItem::new(TimeUnit::Decade, 2))
+        );
+        assert_eq!(
+            DeltaItem::from_str("-1c"),
+            Ok(DeltaItem::new(TimeUnit::Century, -1))
+        );
+        assert_eq!(
+            DeltaItem::from_str("3d"),
+            Ok(DeltaItem::new(TimeUnit::Day, 3))
+        );
+
+        let apply = |s: &str| {
+            DeltaItem::from_str(s)
+                .unwrap()
+                .apply_timedelta_builder(TimeDeltaBuilder::default())
+                .build()
+                .apply_datetime(Utc.ymd(2019, 6, 17).and_hms(11, 22, 33))
+                .unwrap()
+        };
+        assert_eq!(apply("2dec"), Utc.ymd(2039, 6, 17).and_hms(11, 22, 33));
+        assert_eq!(apply("-1decade"), Utc.ymd(2009, 6, 17).and_hms(11, 22, 33));
+        assert_eq!(apply("1c"), Utc.ymd(2119, 6, 17).and_hms(11, 22, 33));
+        assert_eq!(apply("-3century"), Utc.ymd(1719, 6, 17).and_hms(11, 22, 33));
+    }
+
+    #[test]
+    fn delta_display() {
+        use strum::IntoEnumIterator;
+
+        assert_eq!(DeltaItem::new(TimeUnit::Year, 12).to_string(), "12y");
+        assert_eq!(DeltaItem::new(TimeUnit::Minute, -5).to_string(), "-5min");
+        for unit in TimeUnit::iter() {
+            for value in &[0, 1, -1, i32::MAX, i32::MIN] {
+                let item = DeltaItem::new(unit, *value);
+                assert_eq!(DeltaItem::from_str(&item.to_string()), Ok(item));
+            }
+        }
+    }
+
+    #[test]
+    fn iso8601() {
+        let items = |s| {
+            parse_iso8601(s).map(|items| {
+                items
+                    .iter()
+                    .map(|d| d.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+        };
+
+        assert_eq!(items("P1Y"), Ok("1y".to_string()));
+        assert_eq!(items("PT30M"), Ok("30min".to_string()));
+        assert_eq!(
+            items("P1Y2M10DT2H30M"),
+            Ok("1y 2mon 10d 2h 30min".to_string())
+        );
+        assert_eq!(items("P2W"), Ok("14d".to_string()));
+        assert_eq!(items("-P1DT12H"), Ok("-1d -12h".to_string()));
+        assert_eq!(items("PT1.5S"), Ok("1s 500ms".to_string()));
+        assert_eq!(items("PT0,25S"), Ok("0s 250ms".to_string()));
+
+        for s in &[
+            "P",
+            "PT",
+            "P1DT",
+            "P1W2D",
+            "P1WT1H",
+            "P1.5Y",
+            "PT1.2345S",
+            "P1H",
+            "p1d",
+        ] {
+            let r = parse_iso8601(s);
+            assert!(r.err().unwrap().is_wrong_format(), "{}", s);
+        }
+        assert!(parse_iso8601("P99999999999D")
+            .err()
+            .unwrap()
+            .is_wrong_value());
+        assert!(parse_iso8601("P306783379W").err().unwrap().is_wrong_value());
+    }
+
+    #[test]
+    fn delta_items() {
+        assert_eq!(
+            parse_items("3d"),
+            Ok(vec![DeltaItem::new(TimeUnit::Day, 3)])
+        );
+        assert_eq!(
+            parse_items("P1Y2M10D"),
+            Ok(vec![
+                DeltaItem::new(TimeUnit::Year, 1),
+                DeltaItem::new(TimeUnit::Month, 2),
+                DeltaItem::new(TimeUnit::Day, 10),
+            ])
+        );
+        assert!(parse_items("P").is_err());
+    }
+
+    #[test]
+    fn split_compound_items() {
+        assert_eq!(split_items("1y"), Ok(vec!["1y"]));
+        assert_eq!(split_items("1y2mon3d"), Ok(vec!["1y", "2mon", "3d"]));
+        assert_eq!(split_items("-1d+12h"), Ok(vec!["-1d", "+12h"]));
+        assert_eq!(split_items("1c2dec"), Ok(vec!["1c", "2dec"]));
+
+        let r = split_items("");
+        assert!(r.is_err());
+        assert!(r.err().unwrap().is_wrong_format());
+
+        let r = split_items("1y2");
+        assert!(r.is_err());
+        assert!(r.err().unwrap().is_wrong_format());
+
+        let r = split_items("1y 2d");
+        assert!(r.is_err());
+        assert!(r.err().unwrap().is_wrong_format());
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::delta::DeltaItem;
+    use crate::unit::TimeUnit;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        delta: DeltaItem,
+    }
+
+    #[test]
+    fn delta_item_serde() {
+        let item = Item {
+            delta: DeltaItem::new(TimeUnit::Hour, -12),
+        };
+        let s = toml::to_string(&item).unwrap();
+        assert_eq!(s, "delta = \"-12h\"\n");
+        assert_eq!(toml::from_str::<Item>(&s).unwrap(), item);
+
+        assert!(toml::from_str::<Item>("delta = \"12b\"").is_err());
+    }
 }
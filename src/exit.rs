@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::io;
+
+use crate::config::ConfigError;
+use ut_cli::datetime::EpochDayError;
+use ut_cli::precision::PrecisionError;
+use ut_cli::provider::ProviderError;
+use ut_cli::timedelta::{ApplyError, TimeDeltaError};
+use ut_cli::unit::TruncateError;
+
+/// A class of failures, which the process exits with a distinct code of.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Failure {
+    /// A wrong input, like a timestamp, a date or an offset.
+    Input,
+    /// A wrong environment, like a config file, an environment variable or IO.
+    Environment,
+    /// An overflow of datetimes, or a bug.
+    Internal,
+}
+
+impl Failure {
+    /// Classifies the error by the first known error in its chain.
+    /// Errors without a known one, like the ones of the inputs of lines, are of inputs.
+    pub fn of(e: &(dyn Error + 'static)) -> Failure {
+        let mut source = Some(e);
+        while let Some(e) = source {
+            if let Some(failure) = Failure::classify(e) {
+                return failure;
+            }
+            source = e.source();
+        }
+        Failure::Input
+    }
+
+    fn classify(e: &(dyn Error + 'static)) -> Option<Failure> {
+        if e.is::<ConfigError>() || e.is::<io::Error>() {
+            return Some(Failure::Environment);
+        }
+        let overflow = e.is::<EpochDayError>()
+            || matches!(e.downcast_ref(), Some(TimeDeltaError::Overflow(_)))
+            || matches!(
+                e.downcast_ref(),
+                Some(PrecisionError::OutOfRange(..)) | Some(PrecisionError::Unrepresentable(..))
+            )
+            || matches!(e.downcast_ref(), Some(ApplyError::OutOfRange(..)))
+            || matches!(e.downcast_ref(), Some(ProviderError::OutOfRange(..)))
+            || matches!(e.downcast_ref(), Some(TruncateError::OutOfRange(..)));
+        if overflow {
+            Some(Failure::Internal)
+        } else {
+            None
+        }
+    }
+
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Failure::Input => 1,
+            Failure::Environment => 2,
+            Failure::Internal => 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::io;
+
+    use anyhow::Context;
+
+    use crate::config::ConfigError;
+    use crate::exit::Failure;
+    use ut_cli::precision::{Precision, PrecisionError};
+    use ut_cli::timedelta::ApplyError;
+
+    fn failure_of<E: Into<Box<dyn Error>>>(e: E) -> Failure {
+        Failure::of(e.into().as_ref())
+    }
+
+    #[test]
+    fn of() {
+        assert_eq!(
+            failure_of(anyhow::anyhow!("Wrong datetime.")),
+            Failure::Input
+        );
+        assert_eq!(
+            failure_of(ApplyError::NonexistentDate {
+                year: 2019,
+                month: 2,
+                day: 31
+            }),
+            Failure::Input
+        );
+        assert_eq!(
+            failure_of(ConfigError::WrongSourceDateEpoch("x".to_string())),
+            Failure::Environment
+        );
+        assert_eq!(
+            failure_of(io::Error::new(io::ErrorKind::NotFound, "no file")),
+            Failure::Environment
+        );
+        assert_eq!(
+            failure_of(PrecisionError::OutOfRange(
+                i64::MAX,
+                Precision::Second,
+                0,
+                0
+            )),
+            Failure::Internal
+        );
+
+        // the error is found in the chain of contexts.
+        let r: Result<(), _> = Err(ApplyError::OutOfRange("1d".to_string(), "x".to_string()));
+        assert_eq!(
+            failure_of(r.context("Cannot generate.").unwrap_err()),
+            Failure::Internal
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use ut_cli::pattern::{self, Integers};
+use ut_cli::precision::Precision;
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+const MAX_DIGITS: usize = 19;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum DigitRangeError {
+    #[error("Wrong digit range: '{0}'. range must be in `N` or `MIN-MAX` format.")]
+    WrongFormat(String),
+
+    #[error(
+        "Wrong digit range: '{0}'. digits must be between 1 and 19, and MIN must not exceed MAX."
+    )]
+    WrongDigits(String),
+}
+
+impl IntoValidationError for DigitRangeError {
+    fn into_validation_error(self) -> String {
+        use DigitRangeError::*;
+        let (problem, input) = match self {
+            WrongFormat(s) => ("Wrong digit range: text is not in a range format.", s),
+            WrongDigits(s) => (
+                "Wrong digit range: digits must be between 1 and 19, and MIN must not exceed MAX.",
+                s,
+            ),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted("`N` or `MIN-MAX`, like `10` or `9-13`")
+            .to_string()
+    }
+}
+
+/// A range of the number of digits of timestamps to extract.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DigitRange {
+    min: usize,
+    max: usize,
+}
+
+impl DigitRange {
+    /// Returns the digits of timestamps between 1973 and 2286 in the precision.
+    pub fn for_precision(precision: Precision) -> DigitRange {
+        let min = 9 + precision.subsec_digits();
+        DigitRange { min, max: min + 1 }
+    }
+}
+
+impl FromStr for DigitRange {
+    type Err = DigitRangeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let caps =
+            pattern::digit_range(s).ok_or_else(|| DigitRangeError::WrongFormat(s.to_string()))?;
+
+        let digits = |i: usize| caps.get(i).map(|m| m.parse::<usize>());
+        let min = digits(1).unwrap();
+        let max = digits(2).unwrap_or_else(|| digits(1).unwrap());
+        match (min, max) {
+            (Ok(min), Ok(max)) if 1 <= min && min <= max && max <= MAX_DIGITS => {
+                Ok(DigitRange { min, max })
+            }
+            _ => Err(DigitRangeError::WrongDigits(s.to_string())),
+        }
+    }
+}
+
+/// Finds integers which look like timestamps in text.
+pub struct Extractor {
+    integers: Integers,
+}
+
+impl Extractor {
+    pub fn new(range: DigitRange) -> Extractor {
+        Extractor {
+            integers: Integers::new(range.min, range.max),
+        }
+    }
+
+    /// Replaces each timestamp in the line with the result of `f`.
+    /// Timestamps are kept as they are if `f` returns `None`.
+    pub fn replace<F>(&self, line: &str, f: F) -> String
+    where
+        F: Fn(i64) -> Option<String>,
+    {
+        let mut replaced = String::with_capacity(line.len());
+        let mut last = 0;
+        for range in self.integers.find_iter(line) {
+            let token = &line[range.clone()];
+            replaced.push_str(&line[last..range.start]);
+            match token.parse().ok().and_then(&f) {
+                Some(s) => replaced.push_str(&s),
+                None => replaced.push_str(token),
+            }
+            last = range.end;
+        }
+        replaced.push_str(&line[last..]);
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digit_range_from_str() {
+        assert_eq!(
+            DigitRange::from_str("10"),
+            Ok(DigitRange { min: 10, max: 10 })
+        );
+        assert_eq!(
+            DigitRange::from_str("9-13"),
+            Ok(DigitRange { min: 9, max: 13 })
+        );
+
+        let wrong_format = |s: &str| Err(DigitRangeError::WrongFormat(s.to_string()));
+        assert_eq!(DigitRange::from_str(""), wrong_format(""));
+        assert_eq!(DigitRange::from_str("9-"), wrong_format("9-"));
+        assert_eq!(DigitRange::from_str("a-b"), wrong_format("a-b"));
+
+        let wrong_digits = |s: &str| Err(DigitRangeError::WrongDigits(s.to_string()));
+        assert_eq!(DigitRange::from_str("0"), wrong_digits("0"));
+        assert_eq!(DigitRange::from_str("13-9"), wrong_digits("13-9"));
+        assert_eq!(DigitRange::from_str("9-20"), wrong_digits("9-20"));
+    }
+
+    #[test]
+    fn replace_timestamps() {
+        let extractor = Extractor::new(DigitRange::for_precision(Precision::Second));
+        let replace = |line: &str| extractor.replace(line, |ts| Some(format!("<{}>", ts)));
+
+        assert_eq!(
+            replace("start=1560762129 end=1560765729"),
+            "start=<1560762129> end=<1560765729>"
+        );
+        assert_eq!(
+            replace("id=12345 ts=1560762129123 req42 at 1560762129"),
+            "id=12345 ts=1560762129123 req42 at <1560762129>"
+        );
+        assert_eq!(replace("[1560762129]"), "[<1560762129>]");
+        assert_eq!(replace("no timestamps"), "no timestamps");
+    }
+
+    #[test]
+    fn replace_keeps_rejected_timestamps() {
+        let extractor = Extractor::new(DigitRange::from_str("1-19").unwrap());
+        let line = extractor.replace("a=1 b=2", |ts| {
+            if ts == 1 {
+                Some("one".to_string())
+            } else {
+                None
+            }
+        });
+        assert_eq!(line, "a=one b=2");
+    }
+}
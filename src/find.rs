@@ -4,11 +4,60 @@ use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum FindError {
-    #[error("No matching item found.")]
-    NotFound,
+    #[error("No matching item found for '{0}'.")]
+    NotFound(String),
 
-    #[error("Ambiguous item given. candidates: {0:?}")]
-    Ambiguous(Vec<String>),
+    #[error("'{0}' is ambiguous: {}", .1.join(", "))]
+    Ambiguous(String, Vec<String>),
+
+    #[error("No matching item found for '{0}'. did you mean {}?", did_you_mean(.1))]
+    Suggestion(String, Vec<String>),
+}
+
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Quotes and joins names like `'a', 'b' or 'c'`.
+pub fn did_you_mean(names: &[String]) -> String {
+    let quoted: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => quoted.join(""),
+    }
+}
+
+/// Computes the levenshtein distance between given texts.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+fn suggest_names<E, I>(items: I, name: &str) -> Vec<String>
+where
+    E: ToString,
+    I: Iterator<Item = E>,
+{
+    let name = name.to_ascii_lowercase();
+    let distances: Vec<(usize, String)> = items
+        .map(|x| x.to_string().to_ascii_lowercase())
+        .map(|x| (edit_distance(&name, &x), x))
+        .filter(|(d, _)| *d <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+
+    let min_distance = distances.iter().map(|(d, _)| *d).min();
+    distances
+        .into_iter()
+        .filter(|(d, _)| Some(*d) == min_distance)
+        .map(|(_, x)| x)
+        .collect()
 }
 
 fn find_items<E, I>(items: I, name: &str) -> Vec<E>
@@ -22,22 +71,61 @@ where
         .collect()
 }
 
+fn find_exact<T, I>(mut items: I, name: &str) -> Option<T>
+where
+    T: ToString,
+    I: Iterator<Item = T>,
+{
+    items.find(|x| x.to_string().eq_ignore_ascii_case(name))
+}
+
+fn not_found<T, I>(items: I, name: &str) -> FindError
+where
+    T: Copy + ToString,
+    I: Iterator<Item = T>,
+{
+    // names starting with the text are suggested first, like `year` for `y` of exact matching.
+    let items: Vec<T> = items.collect();
+    let prefixed: Vec<String> = find_items(items.iter().copied(), name)
+        .into_iter()
+        .map(|x| x.to_string().to_ascii_lowercase())
+        .collect();
+    let suggestions = if prefixed.is_empty() {
+        suggest_names(items.into_iter(), name)
+    } else {
+        prefixed
+    };
+    if suggestions.is_empty() {
+        FindError::NotFound(name.to_string())
+    } else {
+        FindError::Suggestion(name.to_string(), suggestions)
+    }
+}
+
 fn find_by_name<T, I>(items: I, name: &str) -> Result<T, FindError>
 where
     T: Copy + ToString,
     I: Iterator<Item = T>,
 {
-    let found = find_items(items, &name);
+    let items: Vec<T> = items.collect();
+    let found = find_items(items.iter().copied(), &name);
     if found.len() == 1 {
         Ok(*found.first().unwrap())
     } else if found.is_empty() {
-        Err(FindError::NotFound)
+        Err(not_found(items.into_iter(), name))
     } else {
         let names = found.into_iter().map(|x| x.to_string()).collect();
-        Err(FindError::Ambiguous(names))
+        Err(FindError::Ambiguous(name.to_string(), names))
     }
 }
 
+/// Options to control how names are matched.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct FindOptions {
+    /// Requires exact names, and disables prefix matching.
+    pub strict: bool,
+}
+
 pub trait PossibleValues: Copy {
     type Iterator: Iterator<Item = Self>;
 
@@ -50,19 +138,144 @@ pub trait PossibleNames: PossibleValues + ToString {
             .map(|x| x.to_string().to_ascii_lowercase())
             .collect()
     }
+
+    /// Appends possible names to the given help text of an argument.
+    fn help_with_possible_names(help: &str) -> String {
+        format!(
+            "{} [possible values: {}]",
+            help,
+            Self::possible_names().join(", ")
+        )
+    }
 }
 
 pub trait FindByName: PossibleValues + ToString + FromStr {
     type Error: From<FindError>;
 
     fn find_by_name(name: &str) -> Result<Self, Self::Error> {
-        Self::from_str(name)
-            .or_else(|_| find_by_name(Self::possible_values(), name).map_err(Self::Error::from))
+        Self::find_by_name_with(name, FindOptions::default())
+    }
+
+    fn find_by_name_with(name: &str, options: FindOptions) -> Result<Self, Self::Error> {
+        let exact = Self::from_str(name)
+            .or_else(|_| Self::from_str(&name.to_ascii_lowercase()))
+            .ok()
+            .or_else(|| find_exact(Self::possible_values(), name));
+
+        match exact {
+            Some(x) => Ok(x),
+            None if options.strict => Err(not_found(Self::possible_values(), name).into()),
+            None => find_by_name(Self::possible_values(), name).map_err(Self::Error::from),
+        }
+    }
+
+    /// Finds an item by its canonical name in the possible names, ignoring the case.
+    /// Aliases like `ms` of `millisecond` are not accepted.
+    fn find_by_canonical_name(name: &str) -> Result<Self, Self::Error> {
+        find_exact(Self::possible_values(), name)
+            .ok_or_else(|| not_found(Self::possible_values(), name).into())
     }
 
     fn find_by_name_opt(maybe_name: Option<&str>) -> Result<Option<Self>, Self::Error> {
+        Self::find_by_name_opt_with(maybe_name, FindOptions::default())
+    }
+
+    fn find_by_name_opt_with(
+        maybe_name: Option<&str>,
+        options: FindOptions,
+    ) -> Result<Option<Self>, Self::Error> {
         maybe_name
-            .map(move |s| Self::find_by_name(s).map(Some))
+            .map(move |s| Self::find_by_name_with(s, options).map(Some))
             .unwrap_or_else(|| Ok(None))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_distance_basics() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("day", "day"), 0);
+        assert_eq!(edit_distance("", "day"), 3);
+        assert_eq!(edit_distance("day", ""), 3);
+        assert_eq!(edit_distance("tomorow", "tomorrow"), 1);
+        assert_eq!(edit_distance("yeasterday", "yesterday"), 1);
+        assert_eq!(edit_distance("huor", "hour"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggest_names_closest() {
+        let names = ["today", "tomorrow", "yesterday"];
+        assert_eq!(
+            suggest_names(names.iter(), "tomorow"),
+            vec!["tomorrow".to_string()]
+        );
+        assert_eq!(
+            suggest_names(names.iter(), "TOMORROW"),
+            vec!["tomorrow".to_string()]
+        );
+        assert!(suggest_names(names.iter(), "next week").is_empty());
+
+        let names = ["day", "dry", "year"];
+        assert_eq!(suggest_names(names.iter(), "dax"), vec!["day".to_string()]);
+        assert_eq!(
+            suggest_names(names.iter(), "dby"),
+            vec!["day".to_string(), "dry".to_string()]
+        );
+    }
+
+    #[test]
+    fn find_exact_ignore_case() {
+        let names = ["min", "minute"];
+        assert_eq!(find_exact(names.iter(), "MIN"), Some(&"min"));
+        assert_eq!(find_exact(names.iter(), "Minute"), Some(&"minute"));
+        assert_eq!(find_exact(names.iter(), "mi"), None);
+        assert_eq!(
+            find_by_name(names.iter(), "MIN"),
+            Err(FindError::Ambiguous(
+                "MIN".to_string(),
+                vec!["min".to_string(), "minute".to_string()]
+            ))
+        );
+    }
+
+    #[test]
+    fn suggestion_message() {
+        let e = FindError::Suggestion("tomorow".to_string(), vec!["tomorrow".to_string()]);
+        assert_eq!(
+            e.to_string(),
+            "No matching item found for 'tomorow'. did you mean 'tomorrow'?"
+        );
+
+        let e = FindError::Suggestion(
+            "dby".to_string(),
+            vec!["day".to_string(), "dry".to_string()],
+        );
+        assert_eq!(
+            e.to_string(),
+            "No matching item found for 'dby'. did you mean 'day' or 'dry'?"
+        );
+    }
+
+    #[test]
+    fn error_message() {
+        let e = FindError::NotFound("b".to_string());
+        assert_eq!(e.to_string(), "No matching item found for 'b'.");
+
+        let e = FindError::Ambiguous(
+            "m".to_string(),
+            vec![
+                "month".to_string(),
+                "minute".to_string(),
+                "millisecond".to_string(),
+            ],
+        );
+        assert_eq!(
+            e.to_string(),
+            "'m' is ambiguous: month, minute, millisecond"
+        );
+    }
+}
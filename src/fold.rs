@@ -0,0 +1,94 @@
+use chrono::LocalResult;
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use crate::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use crate::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FoldError {
+    #[error("Wrong fold. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for FoldError {
+    fn from(e: FindError) -> Self {
+        FoldError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for FoldError {
+    fn into_validation_error(self) -> String {
+        use FoldError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("fold", &e, Fold::possible_names()).to_string()
+            }
+        }
+    }
+}
+
+/// Which of the two instants to take for a local time which occurs twice,
+/// like 01:30 when clocks fall back from 02:00 to 01:00.
+/// The earlier one is taken by default, like `mktime` of POSIX.
+#[derive(Debug, Default, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum Fold {
+    #[default]
+    #[strum(serialize = "earlier")]
+    Earlier,
+
+    #[strum(serialize = "later")]
+    Later,
+}
+
+impl Fold {
+    /// Picks the instant of the local time. Returns `None` if the local time does not exist.
+    /// The two instants are compared, as some timezones return them in the reverse order.
+    pub fn pick<T: Ord>(self, local: LocalResult<T>) -> Option<T> {
+        match (local, self) {
+            (LocalResult::Single(t), _) => Some(t),
+            (LocalResult::Ambiguous(a, b), Fold::Earlier) => Some(a.min(b)),
+            (LocalResult::Ambiguous(a, b), Fold::Later) => Some(a.max(b)),
+            (LocalResult::None, _) => None,
+        }
+    }
+}
+
+impl PossibleNames for Fold {}
+
+impl PossibleValues for Fold {
+    type Iterator = FoldIter;
+
+    fn possible_values() -> Self::Iterator {
+        Fold::iter()
+    }
+}
+
+impl FindByName for Fold {
+    type Error = FoldError;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::LocalResult;
+
+    use crate::find::FindByName;
+    use crate::fold::Fold;
+
+    #[test]
+    fn find() {
+        assert_eq!(Fold::find_by_name("earlier"), Ok(Fold::Earlier));
+        assert_eq!(Fold::find_by_name("l"), Ok(Fold::Later));
+        assert!(Fold::find_by_name("first").is_err());
+    }
+
+    #[test]
+    fn pick() {
+        assert_eq!(Fold::Earlier.pick(LocalResult::Ambiguous(1, 2)), Some(1));
+        assert_eq!(Fold::Later.pick(LocalResult::Ambiguous(1, 2)), Some(2));
+        assert_eq!(Fold::Earlier.pick(LocalResult::Ambiguous(2, 1)), Some(1));
+        assert_eq!(Fold::Later.pick(LocalResult::Single(1)), Some(1));
+        assert_eq!(Fold::Earlier.pick(LocalResult::<i32>::None), None);
+    }
+}
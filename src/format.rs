@@ -0,0 +1,322 @@
+use std::fmt::Write;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{FixedOffset, TimeZone, Timelike, Utc};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use ut_cli::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+const MAX_SPECIFIER_LEN: usize = 5;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FormatError {
+    #[error("Unknown specifier: '{1}' at index {0} of format '{2}'.")]
+    UnknownSpecifier(usize, String, String),
+
+    #[error("Cannot format a datetime with format '{0}'.")]
+    Unformattable(String),
+}
+
+impl IntoValidationError for FormatError {
+    fn into_validation_error(self) -> String {
+        use FormatError::*;
+        let (problem, input) = match self {
+            UnknownSpecifier(i, spec, format) => (
+                format!(
+                    "Wrong datetime format: unknown specifier '{}' at index {}.",
+                    spec, i
+                ),
+                format,
+            ),
+            Unformattable(format) => (
+                "Wrong datetime format: cannot format a datetime.".to_string(),
+                format,
+            ),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted("text with chrono's strftime specifiers, like `%Y-%m-%d %H:%M:%S`")
+            .to_string()
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FormatStyleError {
+    #[error("Wrong format style. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for FormatStyleError {
+    fn from(e: FindError) -> Self {
+        FormatStyleError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for FormatStyleError {
+    fn into_validation_error(self) -> String {
+        use FormatStyleError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("style", &e, FormatStyle::possible_names())
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Named datetime formats, for those who do not remember strftime specifiers.
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum FormatStyle {
+    /// RFC 3339, with the fraction of a second if any.
+    #[strum(serialize = "iso")]
+    Iso,
+
+    #[strum(serialize = "log")]
+    Log,
+
+    /// The basic format of ISO 8601, which fits in file names.
+    #[strum(serialize = "compact")]
+    Compact,
+
+    #[strum(serialize = "us")]
+    Us,
+
+    #[strum(serialize = "eu")]
+    Eu,
+}
+
+impl FormatStyle {
+    pub fn format(self) -> &'static str {
+        match self {
+            FormatStyle::Iso => "%Y-%m-%dT%H:%M:%S%.f%:z",
+            FormatStyle::Log => "%Y-%m-%d %H:%M:%S",
+            FormatStyle::Compact => "%Y%m%dT%H%M%S%z",
+            FormatStyle::Us => "%m/%d/%Y",
+            FormatStyle::Eu => "%d/%m/%Y",
+        }
+    }
+}
+
+impl PossibleValues for FormatStyle {
+    type Iterator = FormatStyleIter;
+
+    fn possible_values() -> Self::Iterator {
+        FormatStyle::iter()
+    }
+}
+
+impl PossibleNames for FormatStyle {}
+
+impl FindByName for FormatStyle {
+    type Error = FormatStyleError;
+}
+
+fn is_valid_items(s: &str) -> bool {
+    StrftimeItems::new(s).all(|item| item != Item::Error)
+}
+
+/// Finds the shortest valid specifier which starts at the beginning of the text.
+fn specifier_len(s: &str) -> Option<usize> {
+    s.char_indices()
+        .skip(1)
+        .take(MAX_SPECIFIER_LEN - 1)
+        .map(|(i, c)| i + c.len_utf8())
+        .find(|len| is_valid_items(&s[..*len]))
+}
+
+/// Validates strftime format by formatting a fixed datetime.
+pub fn validate_format(format: &str) -> Result<(), FormatError> {
+    let mut pos = 0;
+    while let Some(i) = format[pos..].find('%') {
+        let start = pos + i;
+        match specifier_len(&format[start..]) {
+            Some(len) => pos = start + len,
+            None => {
+                let specifier: String = format[start..].chars().take(2).collect();
+                return Err(FormatError::UnknownSpecifier(
+                    start,
+                    specifier,
+                    format.to_string(),
+                ));
+            }
+        }
+    }
+
+    let dt = Utc.timestamp_opt(0, 0).unwrap();
+    let mut buf = String::new();
+    write!(buf, "{}", dt.format(format)).map_err(|_| FormatError::Unformattable(format.to_string()))
+}
+
+/// Replaces `%Z` specifiers in the format with the given label.
+pub fn replace_zone_name(format: &str, label: &str) -> String {
+    let label = label.replace('%', "%%");
+    let mut replaced = String::with_capacity(format.len());
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            replaced.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('Z') => replaced.push_str(&label),
+            Some(next) => {
+                replaced.push(c);
+                replaced.push(next);
+            }
+            None => replaced.push(c),
+        }
+    }
+    replaced
+}
+
+/// Commonly used specifiers and their descriptions.
+const FORMAT_SPECIFIERS: &[(&str, &str)] = &[
+    ("%Y", "Year with century."),
+    ("%y", "Year without century, 00-99."),
+    ("%m", "Month, 01-12."),
+    ("%b", "Abbreviated month name."),
+    ("%B", "Full month name."),
+    ("%d", "Day of month, 01-31."),
+    ("%e", "Day of month, space padded."),
+    ("%-d", "Day of month without padding."),
+    ("%_m", "Month, space padded."),
+    ("%a", "Abbreviated weekday name."),
+    ("%A", "Full weekday name."),
+    ("%j", "Day of year, 001-366."),
+    ("%H", "Hour, 00-23."),
+    ("%I", "Hour, 01-12."),
+    ("%p", "AM or PM."),
+    ("%M", "Minute, 00-59."),
+    ("%S", "Second, 00-60."),
+    ("%f", "Nanoseconds since the last whole second."),
+    ("%3f", "Milliseconds since the last whole second."),
+    ("%.3f", "Milliseconds with a leading dot."),
+    ("%.6f", "Microseconds with a leading dot."),
+    ("%.9f", "Nanoseconds with a leading dot."),
+    ("%z", "Offset from UTC."),
+    ("%:z", "Offset from UTC with a colon."),
+    ("%Z", "Timezone name, or `--zone-label`."),
+    ("%s", "Whole seconds since the epoch."),
+    ("%F", "Same as %Y-%m-%d."),
+    ("%T", "Same as %H:%M:%S."),
+    ("%+", "ISO 8601 / RFC 3339 datetime."),
+    ("%%", "A literal percent sign."),
+];
+
+/// Renders a reference table of the specifiers, with examples formatted from a fixed datetime.
+pub fn format_specifiers_help() -> String {
+    let dt = FixedOffset::east_opt(9 * 3600)
+        .and_then(|tz| tz.with_ymd_and_hms(2019, 6, 17, 18, 2, 9).single())
+        .and_then(|dt| dt.with_nanosecond(26_490_000))
+        .expect("the example datetime is valid");
+    let mut help = format!("{:<6}{:<42}{}\n", "SPEC", "DESCRIPTION", "EXAMPLE");
+    for (specifier, description) in FORMAT_SPECIFIERS {
+        let example = dt.format(specifier).to_string();
+        help.push_str(&format!("{:<6}{:<42}{}\n", specifier, description, example));
+    }
+    help
+}
+
+pub fn validate_format_argv(s: String) -> Result<(), String> {
+    validate_format(&s).map_err(|e| e.into_validation_error())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unknown(pos: usize, specifier: &str, format: &str) -> Result<(), FormatError> {
+        Err(FormatError::UnknownSpecifier(
+            pos,
+            specifier.to_string(),
+            format.to_string(),
+        ))
+    }
+
+    #[test]
+    fn styles() {
+        let dt = FixedOffset::east(9 * 3600)
+            .ymd(2019, 6, 17)
+            .and_hms(18, 2, 9);
+        let styles = [
+            (FormatStyle::Iso, "2019-06-17T18:02:09+09:00"),
+            (FormatStyle::Log, "2019-06-17 18:02:09"),
+            (FormatStyle::Compact, "20190617T180209+0900"),
+            (FormatStyle::Us, "06/17/2019"),
+            (FormatStyle::Eu, "17/06/2019"),
+        ];
+        for (style, expected) in styles.iter().copied() {
+            assert_eq!(validate_format(style.format()), Ok(()));
+            assert_eq!(dt.format(style.format()).to_string(), expected);
+        }
+
+        let dt = dt + chrono::Duration::milliseconds(123);
+        assert_eq!(
+            dt.format(FormatStyle::Iso.format()).to_string(),
+            "2019-06-17T18:02:09.123+09:00"
+        );
+        assert_eq!(FormatStyle::find_by_name("c"), Ok(FormatStyle::Compact));
+    }
+
+    #[test]
+    fn validate_valid_formats() {
+        assert_eq!(validate_format(""), Ok(()));
+        assert_eq!(validate_format("plain text"), Ok(()));
+        assert_eq!(validate_format("%Y-%m-%d %H:%M:%S (%Z)"), Ok(()));
+        assert_eq!(validate_format("%Y-%m-%d %H:%M:%S%.3f (%Z)"), Ok(()));
+        assert_eq!(validate_format("%m/%d/%Y"), Ok(()));
+        assert_eq!(validate_format("%+"), Ok(()));
+        assert_eq!(validate_format("%s"), Ok(()));
+        assert_eq!(validate_format("%-d %_m %e"), Ok(()));
+        assert_eq!(validate_format("%_H:%-M %3f %6f %9f"), Ok(()));
+        assert_eq!(validate_format("%:z %::z"), Ok(()));
+        assert_eq!(validate_format("100%%"), Ok(()));
+    }
+
+    #[test]
+    fn validate_invalid_formats() {
+        assert_eq!(
+            validate_format("%Y-%m-%d %Q"),
+            unknown(9, "%Q", "%Y-%m-%d %Q")
+        );
+        assert_eq!(validate_format("%"), unknown(0, "%", "%"));
+        assert_eq!(validate_format("%Y%"), unknown(2, "%", "%Y%"));
+        assert_eq!(validate_format("%!"), unknown(0, "%!", "%!"));
+        assert_eq!(validate_format("%.x"), unknown(0, "%.", "%.x"));
+        assert_eq!(validate_format("%_3f"), unknown(0, "%_", "%_3f"));
+    }
+
+    #[test]
+    fn replace_zone_name_specifiers() {
+        assert_eq!(
+            replace_zone_name("%Y-%m-%d %H:%M:%S (%Z)", "JST"),
+            "%Y-%m-%d %H:%M:%S (JST)"
+        );
+        assert_eq!(replace_zone_name("%Z %:z %Z", "UTC"), "UTC %:z UTC");
+        assert_eq!(replace_zone_name("100%%Z", "JST"), "100%%Z");
+        assert_eq!(replace_zone_name("%Z", "50%"), "50%%");
+        assert_eq!(replace_zone_name("%s", "JST"), "%s");
+    }
+
+    #[test]
+    fn format_specifiers() {
+        for (specifier, _) in FORMAT_SPECIFIERS {
+            assert_eq!(validate_format(specifier), Ok(()), "{}", specifier);
+        }
+
+        let help = format_specifiers_help();
+        let line = |specifier: &str| {
+            help.lines()
+                .find(|line| line.split_whitespace().next() == Some(specifier))
+                .map(|line| line.split_whitespace().last().unwrap().to_string())
+        };
+        assert_eq!(line("%Y"), Some("2019".to_string()));
+        assert_eq!(line("%.3f"), Some(".026".to_string()));
+        assert_eq!(line("%3f"), Some("026".to_string()));
+        assert_eq!(line("%_m"), Some("6".to_string()));
+        assert_eq!(line("%s"), Some("1560762129".to_string()));
+        assert_eq!(line("%:z"), Some("+09:00".to_string()));
+    }
+}
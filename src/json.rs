@@ -0,0 +1,370 @@
+use std::ops::Range;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum JsonError {
+    #[error("Wrong JSON: {0} at column {1}.")]
+    Syntax(&'static str, usize),
+
+    #[error("Key '{0}' is not found.")]
+    MissingKey(String),
+
+    #[error("Value of '{0}' is not an integer: {1}")]
+    NotInteger(String, String),
+}
+
+/// A JSON object in a line of JSON Lines.
+/// Only the top-level fields are parsed, nested values are kept as raw texts.
+#[derive(Debug)]
+pub struct JsonObject<'a> {
+    text: &'a str,
+    fields: Vec<(String, Range<usize>)>,
+    end: usize,
+}
+
+impl<'a> JsonObject<'a> {
+    pub fn parse(text: &'a str) -> Result<JsonObject<'a>, JsonError> {
+        let mut scanner = Scanner { text, pos: 0 };
+        let mut fields = Vec::new();
+
+        scanner.skip_whitespaces();
+        scanner.expect(b'{', "expected '{'")?;
+        scanner.skip_whitespaces();
+        if scanner.peek() == Some(b'}') {
+            scanner.pos += 1;
+        } else {
+            loop {
+                scanner.skip_whitespaces();
+                let key = scanner.string()?;
+                scanner.skip_whitespaces();
+                scanner.expect(b':', "expected ':'")?;
+                scanner.skip_whitespaces();
+                let value = scanner.value()?;
+                fields.push((key, value));
+
+                scanner.skip_whitespaces();
+                match scanner.next() {
+                    Some(b',') => continue,
+                    Some(b'}') => break,
+                    _ => return Err(scanner.error("expected ',' or '}'")),
+                }
+            }
+        }
+
+        let end = scanner.pos - 1;
+        scanner.skip_whitespaces();
+        if scanner.peek().is_some() {
+            return Err(scanner.error("unexpected text after the object"));
+        }
+        Ok(JsonObject { text, fields, end })
+    }
+
+    /// Returns the raw text of the value of the top-level key.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, range)| &self.text[range.clone()])
+    }
+
+    /// Returns the value of the top-level key as an integer.
+    pub fn get_i64(&self, key: &str) -> Result<i64, JsonError> {
+        let value = self
+            .get(key)
+            .ok_or_else(|| JsonError::MissingKey(key.to_string()))?;
+        value
+            .parse()
+            .map_err(|_| JsonError::NotInteger(key.to_string(), value.to_string()))
+    }
+
+    /// Returns the text of the object with the field set to the raw JSON value.
+    /// An existing field is replaced, otherwise the field is appended.
+    pub fn with_field(&self, key: &str, value: &str) -> String {
+        match self.fields.iter().find(|(k, _)| k == key) {
+            Some((_, range)) => format!(
+                "{}{}{}",
+                &self.text[..range.start],
+                value,
+                &self.text[range.end..]
+            ),
+            None => {
+                let separator = if self.fields.is_empty() { "" } else { "," };
+                format!(
+                    "{}{}{}:{}{}",
+                    &self.text[..self.end],
+                    separator,
+                    quote(key),
+                    value,
+                    &self.text[self.end..]
+                )
+            }
+        }
+    }
+}
+
+/// Quotes the text as a JSON string.
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let b = self.peek();
+        if b.is_some() {
+            self.pos += 1;
+        }
+        b
+    }
+
+    fn error(&self, problem: &'static str) -> JsonError {
+        JsonError::Syntax(problem, self.pos + 1)
+    }
+
+    fn expect(&mut self, expected: u8, problem: &'static str) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(problem))
+        }
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads a string and returns its unescaped content.
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"', "expected a string")?;
+        let mut s = String::new();
+        loop {
+            let start = self.pos;
+            let rest = &self.text[start..];
+            let len = rest
+                .find(|c: char| c == '"' || c == '\\' || c < ' ')
+                .ok_or_else(|| self.error("unterminated string"))?;
+            s.push_str(&rest[..len]);
+            self.pos += len;
+
+            match self.next() {
+                Some(b'"') => return Ok(s),
+                Some(b'\\') => s.push(self.escape()?),
+                _ => {
+                    self.pos -= 1;
+                    return Err(self.error("control character in string"));
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let c = match self.next() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let high = self.hex4()?;
+                let code = if (0xd800..0xdc00).contains(&high) {
+                    if !self.text[self.pos..].starts_with("\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.hex4()?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                return std::char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"));
+            }
+            _ => return Err(self.error("wrong escape")),
+        };
+        Ok(c)
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|s| s.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error("wrong unicode escape"))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("never happen"))
+    }
+
+    /// Skips a value and returns its range.
+    fn value(&mut self) -> Result<Range<usize>, JsonError> {
+        let start = self.pos;
+        match self.peek() {
+            Some(b'"') => {
+                self.string()?;
+            }
+            Some(b'{') | Some(b'[') => self.nested()?,
+            _ => {
+                let len = self.text[start..]
+                    .find(|c: char| c == ',' || c == '}' || c == ']' || c.is_ascii_whitespace())
+                    .unwrap_or(self.text.len() - start);
+                if len == 0 {
+                    return Err(self.error("expected a value"));
+                }
+                self.pos += len;
+            }
+        }
+        Ok(start..self.pos)
+    }
+
+    /// Skips an object or an array, including nested ones.
+    fn nested(&mut self) -> Result<(), JsonError> {
+        let mut closings = Vec::new();
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.string()?;
+                    continue;
+                }
+                Some(b'{') => closings.push(b'}'),
+                Some(b'[') => closings.push(b']'),
+                Some(b) if b == b'}' || b == b']' => {
+                    if closings.pop() != Some(b) {
+                        return Err(self.error("mismatched bracket"));
+                    }
+                    if closings.is_empty() {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                }
+                Some(_) => (),
+                None => return Err(self.error("unterminated value")),
+            }
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_object() {
+        let obj = JsonObject::parse(r#"{"ts": 1560762129, "msg": "a \"b\"", "n": null}"#).unwrap();
+        assert_eq!(obj.get("ts"), Some("1560762129"));
+        assert_eq!(obj.get("msg"), Some(r#""a \"b\"""#));
+        assert_eq!(obj.get("n"), Some("null"));
+        assert_eq!(obj.get("x"), None);
+
+        let obj = JsonObject::parse(" {} ").unwrap();
+        assert_eq!(obj.get("ts"), None);
+
+        // nested values are kept as raw texts, and their keys are not looked up.
+        let obj =
+            JsonObject::parse(r#"{"a":{"ts":1,"b":["}",[2]]},"c":[{"d":"]"}],"ts":3}"#).unwrap();
+        assert_eq!(obj.get("a"), Some(r#"{"ts":1,"b":["}",[2]]}"#));
+        assert_eq!(obj.get("c"), Some(r#"[{"d":"]"}]"#));
+        assert_eq!(obj.get("ts"), Some("3"));
+        assert_eq!(obj.get("a.ts"), None);
+
+        // keys are unescaped.
+        let obj = JsonObject::parse(r#"{"ts": 1, "😀": 2}"#).unwrap();
+        assert_eq!(obj.get("ts"), Some("1"));
+        assert_eq!(obj.get("\u{1f600}"), Some("2"));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |s: &str| JsonObject::parse(s).unwrap_err();
+        assert_eq!(error(""), JsonError::Syntax("expected '{'", 1));
+        assert_eq!(error("[1]"), JsonError::Syntax("expected '{'", 1));
+        assert_eq!(error(r#"{"ts" 1}"#), JsonError::Syntax("expected ':'", 7));
+        assert_eq!(
+            error(r#"{"ts": 1"#),
+            JsonError::Syntax("expected ',' or '}'", 9)
+        );
+        assert_eq!(
+            error(r#"{"ts": }"#),
+            JsonError::Syntax("expected a value", 8)
+        );
+        assert_eq!(
+            error(r#"{"ts: 1}"#),
+            JsonError::Syntax("unterminated string", 3)
+        );
+        assert_eq!(
+            error(r#"{"a": [1}}"#),
+            JsonError::Syntax("mismatched bracket", 9)
+        );
+        assert_eq!(
+            error(r#"{"a": 1} x"#),
+            JsonError::Syntax("unexpected text after the object", 10)
+        );
+    }
+
+    #[test]
+    fn get_integer() {
+        let obj = JsonObject::parse(r#"{"ts": -86400, "s": "1", "f": 1.5}"#).unwrap();
+        assert_eq!(obj.get_i64("ts"), Ok(-86400));
+        assert_eq!(
+            obj.get_i64("s"),
+            Err(JsonError::NotInteger("s".to_string(), "\"1\"".to_string()))
+        );
+        assert_eq!(
+            obj.get_i64("f"),
+            Err(JsonError::NotInteger("f".to_string(), "1.5".to_string()))
+        );
+        assert_eq!(
+            obj.get_i64("x"),
+            Err(JsonError::MissingKey("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn set_field() {
+        let obj = JsonObject::parse(r#"{"ts": 1, "a": [2] }"#).unwrap();
+        assert_eq!(
+            obj.with_field("b", "\"x\""),
+            r#"{"ts": 1, "a": [2] ,"b":"x"}"#
+        );
+        assert_eq!(obj.with_field("a", "3"), r#"{"ts": 1, "a": 3 }"#);
+
+        let obj = JsonObject::parse("{}").unwrap();
+        assert_eq!(obj.with_field("b", "1"), r#"{"b":1}"#);
+    }
+
+    #[test]
+    fn quote_string() {
+        assert_eq!(quote("2019-06-17 (UTC)"), r#""2019-06-17 (UTC)""#);
+        assert_eq!(quote("a\"b\\c\n\u{1}"), r#""a\"b\\c\n\u0001""#);
+    }
+}
@@ -0,0 +1,65 @@
+//! Building blocks of `ut`, a command line tool to handle unix timestamps.
+//!
+//! The modules here parse dates, deltas and timestamps, and apply deltas to datetimes.
+//! Errors are `thiserror` enums per module, like [`datetime::YmdError`] or
+//! [`precision::PrecisionError`]. They implement [`validate::IntoValidationError`] to
+//! describe the wrong input for command line arguments.
+//!
+//! Parse a date and take the start of it:
+//!
+//! ```
+//! use std::str::FromStr;
+//!
+//! use chrono::Utc;
+//! use ut_cli::datetime::Ymd;
+//!
+//! let ymd = Ymd::from_str("2019-06-17").unwrap();
+//! let date = ymd.into_date(&Utc).unwrap();
+//! assert_eq!(date.and_hms_opt(0, 0, 0).unwrap().timestamp(), 1_560_729_600);
+//! ```
+//!
+//! Build a delta and apply it:
+//!
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use ut_cli::timedelta::{ApplyDateTime, TimeDeltaBuilder};
+//!
+//! let delta = TimeDeltaBuilder::default().months(1).days(-1).build();
+//! let dt = Utc.timestamp_opt(1_560_729_600, 0).unwrap();
+//! let dt = delta.apply_datetime(dt).unwrap();
+//! assert_eq!(dt.to_rfc3339(), "2019-07-16T00:00:00+00:00");
+//! ```
+//!
+//! Convert a datetime into a timestamp with a precision, and back:
+//!
+//! ```
+//! use chrono::{TimeZone, Utc};
+//! use ut_cli::precision::Precision;
+//!
+//! let dt = Utc.timestamp_opt(1_560_762_129, 123_456_789).unwrap();
+//! let ms = Precision::MilliSecond.to_timestamp(dt).unwrap();
+//! assert_eq!(ms, 1_560_762_129_123);
+//!
+//! let back = Precision::MilliSecond.parse_timestamp(Utc, ms).unwrap();
+//! assert_eq!(back.timestamp_subsec_millis(), 123);
+//! ```
+
+pub mod datetime;
+pub mod delta;
+pub mod find;
+pub mod fold;
+pub mod offset;
+#[doc(hidden)]
+pub mod parse;
+#[doc(hidden)]
+pub mod pattern;
+pub mod precision;
+pub mod preset;
+pub mod provider;
+pub mod random;
+pub mod read;
+pub mod timedelta;
+pub mod timestamp;
+pub mod unit;
+pub mod validate;
+pub mod week;
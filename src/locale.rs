@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, Weekday};
+use thiserror::Error;
+
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum LocaleError {
+    #[error("Wrong locale: '{0}'. locale must be one of en, ja, de, fr and es.")]
+    WrongLocale(String),
+}
+
+impl IntoValidationError for LocaleError {
+    fn into_validation_error(self) -> String {
+        let LocaleError::WrongLocale(s) = self;
+        ValidationMessage::new("Wrong locale: the locale is not supported.", s)
+            .accepted("`en`, `ja`, `de`, `fr` or `es`, like `ja` or `ja_JP.UTF-8`")
+            .to_string()
+    }
+}
+
+/// A locale of weekday and month names. Other parts of outputs are not localized.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum Locale {
+    #[default]
+    En,
+    Ja,
+    De,
+    Fr,
+    Es,
+}
+
+struct Names {
+    weekdays: [&'static str; 7],
+    short_weekdays: [&'static str; 7],
+    months: [&'static str; 12],
+    short_months: [&'static str; 12],
+}
+
+// weekdays start from Monday.
+const EN: Names = Names {
+    weekdays: [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ],
+    short_weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    short_months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+};
+
+const JA: Names = Names {
+    weekdays: [
+        "月曜日",
+        "火曜日",
+        "水曜日",
+        "木曜日",
+        "金曜日",
+        "土曜日",
+        "日曜日",
+    ],
+    short_weekdays: ["月", "火", "水", "木", "金", "土", "日"],
+    months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+    short_months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+};
+
+const DE: Names = Names {
+    weekdays: [
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+        "Sonntag",
+    ],
+    short_weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    short_months: [
+        "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+    ],
+};
+
+const FR: Names = Names {
+    weekdays: [
+        "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    short_weekdays: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    months: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    short_months: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+};
+
+const ES: Names = Names {
+    weekdays: [
+        "lunes",
+        "martes",
+        "miércoles",
+        "jueves",
+        "viernes",
+        "sábado",
+        "domingo",
+    ],
+    short_weekdays: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    months: [
+        "enero",
+        "febrero",
+        "marzo",
+        "abril",
+        "mayo",
+        "junio",
+        "julio",
+        "agosto",
+        "septiembre",
+        "octubre",
+        "noviembre",
+        "diciembre",
+    ],
+    short_months: [
+        "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+    ],
+};
+
+impl Locale {
+    fn names(self) -> &'static Names {
+        match self {
+            Locale::En => &EN,
+            Locale::Ja => &JA,
+            Locale::De => &DE,
+            Locale::Fr => &FR,
+            Locale::Es => &ES,
+        }
+    }
+
+    pub fn weekday(self, weekday: Weekday) -> &'static str {
+        self.names().weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Replaces the weekday and month name specifiers (%a, %A, %b, %h and %B) in the format
+    /// with the names of the date, so that the format renders them in the locale.
+    /// In English, the format is kept as it is.
+    pub fn localize_format<D: Datelike>(self, fmt: &str, date: &D) -> String {
+        if self == Locale::En {
+            return fmt.to_string();
+        }
+
+        let names = self.names();
+        let weekday = date.weekday().num_days_from_monday() as usize;
+        let month = date.month0() as usize;
+        let mut localized = String::with_capacity(fmt.len());
+        let mut chars = fmt.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                localized.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('A') => localized.push_str(names.weekdays[weekday]),
+                Some('a') => localized.push_str(names.short_weekdays[weekday]),
+                Some('B') => localized.push_str(names.months[month]),
+                Some('b') | Some('h') => localized.push_str(names.short_months[month]),
+                Some(next) => {
+                    localized.push('%');
+                    localized.push(next);
+                }
+                None => localized.push('%'),
+            }
+        }
+        localized
+    }
+}
+
+impl FromStr for Locale {
+    type Err = LocaleError;
+
+    /// Accepts a language code, optionally with a territory and an encoding like `ja_JP.UTF-8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s
+            .split(['_', '-', '.'])
+            .next()
+            .unwrap_or_default();
+        match language.to_lowercase().as_str() {
+            "en" | "c" | "posix" => Ok(Locale::En),
+            "ja" => Ok(Locale::Ja),
+            "de" => Ok(Locale::De),
+            "fr" => Ok(Locale::Fr),
+            "es" => Ok(Locale::Es),
+            _ => Err(LocaleError::WrongLocale(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::locale::{Locale, LocaleError};
+
+    #[test]
+    fn from_str() {
+        assert_eq!(Locale::from_str("ja"), Ok(Locale::Ja));
+        assert_eq!(Locale::from_str("ja_JP.UTF-8"), Ok(Locale::Ja));
+        assert_eq!(Locale::from_str("de-DE"), Ok(Locale::De));
+        assert_eq!(Locale::from_str("C"), Ok(Locale::En));
+        assert_eq!(
+            Locale::from_str("xx"),
+            Err(LocaleError::WrongLocale("xx".to_string()))
+        );
+        assert_eq!(
+            Locale::from_str(""),
+            Err(LocaleError::WrongLocale("".to_string()))
+        );
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Locale::En.weekday(Weekday::Mon), "Monday");
+        assert_eq!(Locale::Ja.weekday(Weekday::Mon), "月曜日");
+        assert_eq!(Locale::Fr.weekday(Weekday::Sun), "dimanche");
+        assert_eq!(Locale::Es.weekday(Weekday::Wed), "miércoles");
+    }
+
+    #[test]
+    fn localize_format() {
+        let date = NaiveDate::from_ymd_opt(2019, 6, 17).unwrap();
+        let fmt = "%a %A %b %h %B %d %% %%A %";
+        assert_eq!(Locale::En.localize_format(fmt, &date), fmt);
+        assert_eq!(
+            Locale::De.localize_format(fmt, &date),
+            "Mo Montag Jun Jun Juni %d %% %%A %"
+        );
+        assert_eq!(
+            Locale::Ja.localize_format("%Y年%B%d日 (%a)", &date),
+            "%Y年6月%d日 (月)"
+        );
+        // names are literal in the format, and rendered as they are.
+        assert_eq!(
+            date.format(&Locale::Fr.localize_format("%A %-d %B %Y", &date))
+                .to_string(),
+            "lundi 17 juin 2019"
+        );
+    }
+}
@@ -1,49 +1,106 @@
 mod cmd;
+mod color;
 mod config;
-mod datetime;
-mod delta;
-mod find;
-mod offset;
-mod parse;
-mod precision;
-mod preset;
-mod provider;
-mod read;
-mod timedelta;
-mod unit;
-mod validate;
+mod exit;
+mod extract;
+mod format;
+mod json;
+mod locale;
+mod output;
+mod radix;
+mod record;
+mod snap;
+mod summary;
+mod table;
+mod template;
+mod verbosity;
+mod zone;
 
+use std::env;
+use std::ffi::OsString;
 use std::fmt::{Debug, Display};
+use std::io::{self, IsTerminal};
 use std::str::FromStr;
 
 use anyhow::Context;
-use chrono::{Local, TimeZone, Utc};
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
 use clap::{
     crate_authors, crate_description, crate_name, crate_version, App, AppSettings, Arg, ArgMatches,
+    ErrorKind,
 };
+use lazy_static::lazy_static;
 
-use crate::cmd::generate::GenerateRequest;
-use crate::config::Config;
-use crate::find::FindByName;
-use crate::offset::{Offset, OffsetError};
-use crate::precision::{Precision, PrecisionError};
-use crate::provider::{
-    DateTimeProvider, FixedOffsetProvider, FromTimeZone, LocalProvider, UtcProvider,
+use crate::cmd::generate::{GenerateRequest, GeneratedRecord};
+use crate::color::{ColorChoice, ColorChoiceError};
+use crate::config::{Config, ConfigError};
+use crate::exit::Failure;
+use crate::output::Output;
+use crate::record::{OutputFormat, OutputFormatError};
+use crate::verbosity::Verbosity;
+use ut_cli::find::{FindByName, FindOptions, PossibleNames};
+use ut_cli::offset::{Offset, OffsetError};
+use ut_cli::precision::{Precision, PrecisionError};
+use ut_cli::provider::{
+    DateTimeProvider, FixedNowProvider, FixedOffsetProvider, FromTimeZone, LocalProvider,
+    UtcProvider,
 };
-use crate::validate::{validate_argv, validate_argv_by_name};
+use ut_cli::timestamp::Timestamp;
+use ut_cli::validate::{parse_timestamp, validate_argv, validate_argv_by_name, validate_instant};
+use ut_cli::week::WeekStart;
 
-fn app() -> App<'static, 'static> {
+lazy_static! {
+    static ref PRECISION_HELP: String =
+        Precision::help_with_possible_names("Set the precision of output timestamp.");
+    static ref OUTPUT_HELP: String =
+        OutputFormat::help_with_possible_names("Print outputs in the format. [default: plain]");
+    static ref COLOR_HELP: String =
+        ColorChoice::help_with_possible_names("When to color the help and errors. [default: auto]");
+}
+
+/// Options of the app which take a value, so `-0` following them is the value.
+/// Keep in sync with the arguments of `app`.
+const VALUE_OPTIONS: &[&str] = &[
+    "--profile",
+    "--at",
+    "-o",
+    "--offset",
+    "--output",
+    "--color",
+    "-p",
+    "--precision",
+];
+
+/// The name and the alias of the parse subcommand.
+const PARSE_NAMES: &[&str] = &["parse", "p"];
+
+fn app(options: FindOptions) -> App<'static, 'static> {
     App::new(crate_name!())
         .author(crate_authors!())
         .version(crate_version!())
         .about(crate_description!())
-        .settings(&[
-            AppSettings::SubcommandRequiredElseHelp,
-            AppSettings::AllowNegativeNumbers,
-            AppSettings::ColoredHelp,
-        ])
-        .subcommand(cmd::generate::command("generate").alias("g"))
-        .subcommand(cmd::parse::command("parse").alias("p"))
+        .settings(&[AppSettings::AllowNegativeNumbers, AppSettings::ColoredHelp])
+        .subcommand(cmd::generate::command("generate", options).alias("g"))
+        .subcommand(cmd::parse::command(PARSE_NAMES[0], options).alias(PARSE_NAMES[1]))
+        .arg(
+            Arg::with_name("PROFILE")
+                .help("Use the given profile of the config file.")
+                .long("profile")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("AT")
+                .help("Freeze the current time to the given timestamp or RFC 3339 datetime.")
+                .long("at")
+                .value_name("TIMESTAMP|RFC3339")
+                .takes_value(true)
+                .allow_hyphen_values(true)
+                .validator(validate_instant),
+        )
+        .arg(
+            Arg::with_name("STRICT_NAMES")
+                .help("Require exact names for presets, units, delta units and precisions.")
+                .long("strict-names"),
+        )
         .arg(
             Arg::with_name("UTC")
                 .help("Use utc timezone.")
@@ -51,6 +108,12 @@ fn app() -> App<'static, 'static> {
                 .long("utc")
                 .conflicts_with_all(&["OFFSET"]),
         )
+        .arg(
+            Arg::with_name("UTC_NOW")
+                .help("Print the current utc timestamp. Same as `ut -u generate`.")
+                .long("utc-now")
+                .conflicts_with_all(&["OFFSET"]),
+        )
         .arg(
             Arg::with_name("OFFSET")
                 .help("Use given value as timezone offset.")
@@ -60,45 +123,330 @@ fn app() -> App<'static, 'static> {
                 .allow_hyphen_values(true)
                 .validator(validate_argv::<Offset, OffsetError>),
         )
+        .arg(
+            Arg::with_name("ROUND_OFFSET")
+                .help("Round the timezone offset to the nearest whole minute, and a half minute away from zero.")
+                .long("round-offset"),
+        )
+        .arg(
+            Arg::with_name("PRINT_OFFSET")
+                .help("Print the timezone offset in use to stderr, like `offset: +09:00`.")
+                .long("print-offset"),
+        )
+        .arg(
+            Arg::with_name("VERBOSE")
+                .help("Print details like the timezone, the precision and the parsed options to stderr. Use twice to trace each delta as it is applied.")
+                .short("v")
+                .long("verbose")
+                .multiple(true),
+        )
+        .arg(
+            Arg::with_name("DEBUG")
+                .help("Print each step of the computation to stderr, like the base, truncations and each delta as it is applied. Same as `-vv`.")
+                .long("debug"),
+        )
+        .arg(
+            Arg::with_name("QUIET")
+                .help("Do not print deprecation warnings and notices to stderr.")
+                .short("q")
+                .long("quiet")
+                .global(true)
+                .conflicts_with_all(&["VERBOSE", "DEBUG"]),
+        )
+        .arg(
+            Arg::with_name("NO_NEWLINE")
+                .help("Do not print the newline at the end of outputs.")
+                .short("n")
+                .long("no-newline"),
+        )
+        .arg(
+            Arg::with_name("OUTPUT")
+                .help(OUTPUT_HELP.as_str())
+                .next_line_help(true)
+                .long("output")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .validator(validate_argv_by_name::<OutputFormat, OutputFormatError>(options)),
+        )
+        .arg(
+            Arg::with_name("COLOR")
+                .help(COLOR_HELP.as_str())
+                .next_line_help(true)
+                .long("color")
+                .value_name("WHEN")
+                .takes_value(true)
+                .validator(validate_argv_by_name::<ColorChoice, ColorChoiceError>(options)),
+        )
         .arg(
             Arg::with_name("PRECISION")
-                .help("Set the precision of output timestamp.")
+                .help(PRECISION_HELP.as_str())
                 .next_line_help(true)
                 .short("p")
                 .long("precision")
                 .takes_value(true)
-                .validator(validate_argv_by_name::<Precision, PrecisionError>),
+                .validator(validate_argv_by_name::<Precision, PrecisionError>(options)),
         )
 }
 
-fn config() -> Config {
-    Config::from_env()
+fn config(main_matches: &ArgMatches) -> Result<Config, Box<dyn std::error::Error>> {
+    let profile = main_matches
+        .value_of("PROFILE")
+        .map(String::from)
+        .or_else(|| env::var("UT_PROFILE").ok());
+    Ok(Config::load(profile.as_deref())?)
 }
 
-fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let app = app();
-    let config = config();
-    let main_matches = app.get_matches();
+fn find_options() -> FindOptions {
+    // names are validated while parsing arguments, so the flag is looked up in advance.
+    // `args_os`, since `args` panics on arguments which are not UTF-8, which clap reports as errors.
+    let strict = env::args_os().any(|arg| arg == "--strict-names")
+        || env::var("UT_STRICT_NAMES").is_ok_and(|v| v == "1");
+    FindOptions { strict }
+}
+
+/// Rewrites `-0` of the parse subcommand into `--null`.
+/// clap takes `-0` as a negative number, which is allowed for timestamps like `-86400`.
+fn rewrite_null_flag(args: Vec<OsString>) -> Vec<OsString> {
+    let mut subcommand = None;
+    let mut prev: Option<OsString> = None;
+    let mut rewritten = Vec::with_capacity(args.len());
+    for (i, arg) in args.into_iter().enumerate() {
+        let options = match subcommand {
+            None => VALUE_OPTIONS,
+            Some(_) => cmd::parse::VALUE_OPTIONS,
+        };
+        let is_value = prev
+            .as_ref()
+            .and_then(|p| p.to_str())
+            .is_some_and(|p| options.contains(&p));
+        let is_option = arg.to_str().is_some_and(|s| s.starts_with('-'));
+        let arg = match subcommand {
+            Some(true) if arg == "-0" && !is_value => OsString::from("--null"),
+            None if i > 0 && !is_value && !is_option => {
+                subcommand = Some(arg.to_str().is_some_and(|s| PARSE_NAMES.contains(&s)));
+                arg
+            }
+            _ => arg,
+        };
+        if arg == "--" {
+            subcommand = Some(false);
+        }
+        prev = Some(arg.clone());
+        rewritten.push(arg);
+    }
+    rewritten
+}
+
+fn precision(
+    main_matches: &ArgMatches,
+    config: &Config,
+    options: FindOptions,
+) -> Result<Precision, Box<dyn std::error::Error>> {
     let maybe_precision = main_matches
         .value_of("PRECISION")
         .or_else(|| config.precision());
-    let precision = Precision::find_by_name_opt(maybe_precision)
-        .context("Precision error.")?
-        .unwrap_or_else(|| Precision::Second);
+    // `-p` is validated while parsing arguments, so the error is of the config.
+    Ok(Precision::find_by_name_opt_with(maybe_precision, options)
+        .map_err(ConfigError::WrongPrecision)?
+        .unwrap_or(Precision::Second))
+}
+
+fn output_format(
+    main_matches: &ArgMatches,
+    config: &Config,
+    options: FindOptions,
+) -> Result<OutputFormat, Box<dyn std::error::Error>> {
+    let maybe_format = main_matches.value_of("OUTPUT").or_else(|| config.output());
+    // `--output` is validated while parsing arguments, so the error is of the config.
+    Ok(OutputFormat::find_by_name_opt_with(maybe_format, options)
+        .map_err(ConfigError::WrongOutputFormat)?
+        .unwrap_or_default())
+}
+
+fn week_start(
+    config: &Config,
+    options: FindOptions,
+) -> Result<WeekStart, Box<dyn std::error::Error>> {
+    Ok(
+        WeekStart::find_by_name_opt_with(config.week_start(), options)
+            .map_err(ConfigError::WrongWeekStart)?
+            .unwrap_or(WeekStart::Monday),
+    )
+}
+
+/// Returns the frozen current time from `--at` or `UT_NOW`.
+/// Timestamps are read in the precision, like the other timestamps.
+fn frozen_now(
+    main_matches: &ArgMatches,
+    precision: Precision,
+) -> Result<Option<DateTime<FixedOffset>>, Box<dyn std::error::Error>> {
+    let at = main_matches
+        .value_of("AT")
+        .map(String::from)
+        .or_else(|| env::var("UT_NOW").ok().filter(|s| !s.is_empty()));
+    let at = match at {
+        Some(at) => at,
+        None => return Ok(None),
+    };
+    let now = match parse_timestamp(&at, false) {
+        Ok(timestamp) => Timestamp::new(timestamp, precision)
+            .to_datetime(Utc)
+            .context("Wrong current time.")?
+            .into(),
+        Err(_) => DateTime::parse_from_rfc3339(&at)
+            .with_context(|| format!("Wrong current time: '{}'.", at))?,
+    };
+    Ok(Some(now))
+}
+
+/// Exits with the help, as `SubcommandRequiredElseHelp` does.
+/// The setting is not used, since `--utc-now` runs without a subcommand.
+fn exit_with_help(options: FindOptions, color: ColorChoice) -> ! {
+    let bin_name = env::args_os()
+        .next()
+        .unwrap_or_else(|| crate_name!().into());
+    match app(options)
+        .global_setting(color.app_setting())
+        .get_matches_from_safe(vec![bin_name, "--help".into()])
+    {
+        Err(e) => clap::Error {
+            message: e.message,
+            kind: ErrorKind::MissingArgumentOrSubcommand,
+            info: None,
+        },
+        // `--help` always stops parsing, but a subcommand is still required if it did not.
+        Ok(_) => {
+            clap::Error::with_description("A subcommand is required.", ErrorKind::MissingSubcommand)
+        }
+    }
+    .exit()
+}
+
+/// Exits with a usage error, since `--utc-now` prints the time without running the subcommand.
+/// Clap cannot make an argument conflict with subcommands, so it is checked after parsing.
+fn exit_with_utc_now_conflict(subcommand: &str) -> ! {
+    clap::Error::with_description(
+        &format!(
+            "The argument '--utc-now' cannot be used with the subcommand '{}'",
+            subcommand
+        ),
+        ErrorKind::ArgumentConflict,
+    )
+    .exit()
+}
+
+/// Returns the verbosity by the flags of the app.
+fn verbosity(main_matches: &ArgMatches) -> Verbosity {
+    let verbose = if main_matches.is_present("DEBUG") {
+        2
+    } else {
+        main_matches.occurrences_of("VERBOSE")
+    };
+    Verbosity::new(verbose, main_matches.is_present("QUIET"))
+}
+
+/// Rounds the offset to a whole minute, with a warning if it is changed.
+fn round_offset(offset: Offset, output: &mut Output) -> io::Result<Offset> {
+    let rounded = offset.round_to_minute();
+    if rounded != offset {
+        let (from, to): (FixedOffset, FixedOffset) = (offset.into(), rounded.into());
+        output.notice(format_args!(
+            "Warning: rounded the offset {} to {}.",
+            from, to
+        ))?;
+    }
+    Ok(rounded)
+}
+
+/// Returns the offset of the provider's timezone at the current time.
+fn resolved_offset<O, Tz, P>(provider: &P) -> FixedOffset
+where
+    O: chrono::Offset,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    provider.now().offset().fix()
+}
 
-    if main_matches.is_present("UTC") {
+fn utc_now<P: DateTimeProvider<Utc>>(
+    provider: &P,
+    precision: Precision,
+    source_date_epoch: Option<i64>,
+) -> Result<i64, Box<dyn std::error::Error>> {
+    let now = match source_date_epoch {
+        Some(seconds) => FixedNowProvider::from_timestamp(Utc, seconds)
+            .ok_or_else(|| anyhow::anyhow!("Wrong SOURCE_DATE_EPOCH: {}", seconds))?
+            .now(),
+        None => provider.now(),
+    };
+    Ok(Timestamp::from_datetime(now, precision)?.value())
+}
+
+fn run(color: ColorChoice) -> Result<(), Box<dyn std::error::Error>> {
+    let options = find_options();
+    let app = app(options).global_setting(color.app_setting());
+    let args = rewrite_null_flag(env::args_os().collect());
+    let main_matches = app.get_matches_from(args);
+    let utc_now_requested = main_matches.is_present("UTC_NOW");
+    match main_matches.subcommand_name() {
+        None if !utc_now_requested => exit_with_help(options, color),
+        Some(subcommand) if utc_now_requested => exit_with_utc_now_conflict(subcommand),
+        _ => (),
+    }
+
+    let config = config(&main_matches)?;
+    let precision = precision(&main_matches, &config, options)?;
+
+    let (stdout, stderr) = (io::stdout(), io::stderr());
+    let (mut out, mut err) = (stdout.lock(), stderr.lock());
+    let mut output = Output::new(&mut out, &mut err)
+        .verbosity(verbosity(&main_matches))
+        .format(output_format(&main_matches, &config, options)?)
+        .no_newline(main_matches.is_present("NO_NEWLINE"));
+    output.info(format_args!("precision: {}", precision))?;
+
+    if utc_now_requested {
+        let provider: UtcProvider = UtcProvider::from_timezone(Utc);
+        output.info(format_args!("timezone: UTC"))?;
+        if main_matches.is_present("PRINT_OFFSET") {
+            output.warn(format_args!("offset: {}", resolved_offset(&provider)))?;
+        }
+        let timestamp = match frozen_now(&main_matches, precision)? {
+            Some(now) => utc_now(&FixedNowProvider::wrap(&provider, &now), precision, None)?,
+            None => utc_now(&provider, precision, config.source_date_epoch()?)?,
+        };
+        output.record(
+            &GeneratedRecord::new(timestamp, &precision.to_string()),
+            format_args!("{}\n", timestamp),
+        )?;
+        return Ok(output.flush()?);
+    }
+
+    let result = if main_matches.is_present("UTC") {
+        output.info(format_args!("timezone: UTC"))?;
         let provider: UtcProvider = UtcProvider::from_timezone(Utc);
-        run_with(&main_matches, provider, precision, &config)
+        run_with(&main_matches, provider, precision, &config, &mut output)
     } else if let Some(offset_text) = main_matches.value_of("OFFSET").or_else(|| config.offset()) {
-        let offset = Offset::from_str(offset_text)
-            .context("Wrong time offset.")?
-            .into();
+        // `-o` is validated while parsing arguments, so the error is of the config.
+        let offset = Offset::from_str(offset_text).map_err(ConfigError::WrongOffset)?;
+        let offset = if main_matches.is_present("ROUND_OFFSET") {
+            round_offset(offset, &mut output)?
+        } else {
+            offset
+        }
+        .into();
+        output.info(format_args!("timezone: fixed offset {}", offset))?;
         let provider: FixedOffsetProvider = FixedOffsetProvider::from_timezone(offset);
-        run_with(&main_matches, provider, precision, &config)
+        run_with(&main_matches, provider, precision, &config, &mut output)
     } else {
+        output.info(format_args!("timezone: local"))?;
         let provider: LocalProvider = LocalProvider::from_timezone(Local);
-        run_with(&main_matches, provider, precision, &config)
-    }
+        run_with(&main_matches, provider, precision, &config, &mut output)
+    };
+    // the rows of a table before an error are printed, as plain outputs are.
+    let flushed = output.flush();
+    result?;
+    Ok(flushed?)
 }
 
 fn run_with<O, Tz, P>(
@@ -106,6 +454,42 @@ fn run_with<O, Tz, P>(
     provider: P,
     precision: Precision,
     config: &Config,
+    output: &mut Output,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    O: chrono::Offset + Display + Sized,
+    Tz: TimeZone<Offset = O> + Debug,
+    P: DateTimeProvider<Tz>,
+{
+    // the frozen time takes precedence over SOURCE_DATE_EPOCH.
+    let now = frozen_now(main_matches, precision)?;
+    // the offset of a local timezone depends on the time, so it is taken at the frozen time.
+    let offset = match now {
+        Some(ref now) => resolved_offset(&FixedNowProvider::wrap(&provider, now)),
+        None => resolved_offset(&provider),
+    };
+    if main_matches.is_present("PRINT_OFFSET") {
+        output.warn(format_args!("offset: {}", offset))?;
+    } else {
+        output.info(format_args!("offset: {}", offset))?;
+    }
+    match now {
+        Some(now) => {
+            let provider = FixedNowProvider::wrap(&provider, &now);
+            run_command(main_matches, provider, precision, config, true, output)
+        }
+        None => run_command(main_matches, provider, precision, config, false, output),
+    }
+}
+
+/// Runs the subcommand. `SOURCE_DATE_EPOCH` is read by generate only, unless the current time is frozen.
+fn run_command<O, Tz, P>(
+    main_matches: &ArgMatches,
+    provider: P,
+    precision: Precision,
+    config: &Config,
+    frozen: bool,
+    output: &mut Output,
 ) -> Result<(), Box<dyn std::error::Error>>
 where
     O: chrono::Offset + Display + Sized,
@@ -113,24 +497,315 @@ where
     P: DateTimeProvider<Tz>,
 {
     match main_matches.subcommand() {
-        ("generate", generate_matches) => cmd::generate::run(GenerateRequest::new(
-            generate_matches.unwrap(),
-            provider,
-            precision,
-        )?),
-        ("parse", parse_matches) => cmd::parse::run(cmd::parse::ParseRequest::new(
-            parse_matches.unwrap(),
-            provider,
-            precision,
-            config.datetime_format(),
-        )?),
+        ("generate", generate_matches) => {
+            let source_date_epoch = if frozen {
+                None
+            } else {
+                config.source_date_epoch()?
+            };
+            let request = GenerateRequest::new(
+                generate_matches.unwrap(),
+                provider,
+                precision,
+                source_date_epoch,
+                week_start(config, find_options())?,
+                output,
+            )?;
+            cmd::generate::run(request, output)
+        }
+        ("parse", Some(parse_matches)) if parse_matches.is_present("LIST_FORMAT_SPECIFIERS") => {
+            write!(output.out(), "{}", format::format_specifiers_help())?;
+            Ok(output.flush()?)
+        }
+        ("parse", parse_matches) => {
+            let request = cmd::parse::ParseRequest::new(
+                parse_matches.unwrap(),
+                provider,
+                precision,
+                config.datetime_format(),
+                config.compare_zones(),
+                output,
+            )?;
+            cmd::parse::run(request, output)
+        }
         _ => panic!("never happen"),
     }
 }
 
+/// Returns whether the error is from writing to a closed pipe, like `ut ... | head`.
+fn is_broken_pipe(e: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(e);
+    while let Some(e) = source {
+        if let Some(io_error) = e.downcast_ref::<io::Error>() {
+            if io_error.kind() == io::ErrorKind::BrokenPipe {
+                return true;
+            }
+        }
+        source = e.source();
+    }
+    false
+}
+
 fn main() {
-    match run() {
+    let color = ColorChoice::from_env(find_options());
+    match run(color) {
         Ok(_) => (),
-        Err(e) => eprintln!("error: {}", e),
+        // the reader does not need more output, so it is not a failure.
+        Err(e) if is_broken_pipe(e.as_ref()) => (),
+        Err(e) => {
+            let prefix = color::red("error:", color.enabled(io::stderr().is_terminal()));
+            eprintln!("{} {}", prefix, e);
+            std::process::exit(Failure::of(e.as_ref()).exit_code());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_pipe() {
+        let e: Box<dyn std::error::Error> = Box::new(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert!(is_broken_pipe(e.as_ref()));
+
+        let e: Box<dyn std::error::Error> =
+            anyhow::Error::new(io::Error::from(io::ErrorKind::BrokenPipe))
+                .context("Wrong output.")
+                .into();
+        assert!(is_broken_pipe(e.as_ref()));
+
+        let e: Box<dyn std::error::Error> = Box::new(io::Error::from(io::ErrorKind::NotFound));
+        assert!(!is_broken_pipe(e.as_ref()));
+    }
+
+    #[test]
+    fn null_flag() {
+        let rewrite = |args: &[&str]| {
+            let args = args.iter().map(OsString::from).collect();
+            let rewritten = rewrite_null_flag(args);
+            rewritten
+                .iter()
+                .map(|arg| arg.to_str().unwrap())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        assert_eq!(rewrite(&["ut", "p", "-0"]), "ut p --null");
+        assert_eq!(
+            rewrite(&["ut", "-u", "parse", "--extract", "-0"]),
+            "ut -u parse --extract --null"
+        );
+        assert_eq!(
+            rewrite(&["ut", "-o", "-0", "p", "-f", "-0", "-0"]),
+            "ut -o -0 p -f -0 --null"
+        );
+        assert_eq!(
+            rewrite(&["ut", "p", "--timestamp", "-0"]),
+            "ut p --timestamp -0"
+        );
+        assert_eq!(rewrite(&["ut", "g", "-0"]), "ut g -0");
+        assert_eq!(rewrite(&["ut", "p", "--", "-0"]), "ut p -- -0");
+        // aliases of options are listed too.
+        assert_eq!(
+            rewrite(&["ut", "p", "--locale", "-0", "-0"]),
+            "ut p --locale -0 --null"
+        );
+        assert_eq!(
+            rewrite(&["ut", "p", "--offset-output", "-0", "-0"]),
+            "ut p --offset-output -0 --null"
+        );
+        assert_eq!(rewrite(&["ut", "--at", "-0", "g"]), "ut --at -0 g");
+
+        let m =
+            app(FindOptions::default()).get_matches_from(rewrite(&["ut", "p", "-0"]).split(' '));
+        let (_, parse_matches) = m.subcommand();
+        assert!(parse_matches.unwrap().is_present("NULL"));
+
+        // the listed options take values, so they are missing a value without one.
+        for option in VALUE_OPTIONS {
+            let e = app(FindOptions::default())
+                .get_matches_from_safe(["ut", option])
+                .unwrap_err();
+            assert_eq!(e.kind, ErrorKind::EmptyValue, "{}", option);
+        }
+        for option in cmd::parse::VALUE_OPTIONS {
+            let e = app(FindOptions::default())
+                .get_matches_from_safe(["ut", "p", option])
+                .unwrap_err();
+            assert_eq!(e.kind, ErrorKind::EmptyValue, "{}", option);
+        }
+    }
+
+    #[test]
+    fn offset_in_use() {
+        let offset = FixedOffset::east_opt(9 * 3600).unwrap();
+        let provider = FixedOffsetProvider::from_timezone(offset);
+        assert_eq!(resolved_offset(&provider).to_string(), "+09:00");
+
+        let provider: UtcProvider = UtcProvider::from_timezone(Utc);
+        assert_eq!(resolved_offset(&provider).to_string(), "+00:00");
+    }
+
+    #[test]
+    fn precision_flag_over_config() {
+        let config = Config::new(None, Some("millisecond"), None);
+
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "g"]);
+        assert_eq!(
+            precision(&m, &config, FindOptions::default()).unwrap(),
+            Precision::MilliSecond
+        );
+
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "-p", "second", "g"]);
+        assert_eq!(
+            precision(&m, &config, FindOptions::default()).unwrap(),
+            Precision::Second
+        );
+    }
+
+    #[test]
+    fn help_possible_values() {
+        let mut help = Vec::new();
+        app(FindOptions::default())
+            .setting(AppSettings::ColorNever)
+            .write_help(&mut help)
+            .unwrap();
+        let help = String::from_utf8(help).unwrap();
+
+        let lines: Vec<&str> = help
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| line.contains("[possible values:"))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "When to color the help and errors. [default: auto] [possible values: auto, always, never]",
+                "Print outputs in the format. [default: plain] [possible values: plain, json, csv, table]",
+                "Set the precision of output timestamp. [possible values: second, millisecond, microsecond, nanosecond]",
+            ]
+        );
+    }
+
+    fn fixed_now() -> FixedNowProvider<Utc> {
+        FixedNowProvider::new(Utc.timestamp_millis_opt(1_560_762_129_123).unwrap())
+    }
+
+    #[test]
+    fn utc_now_flag() {
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "--utc-now"]);
+        assert!(m.is_present("UTC_NOW"));
+        assert_eq!(m.subcommand_name(), None);
+
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "-p", "ms", "--utc-now"]);
+        let precision = precision(&m, &Config::default(), FindOptions::default()).unwrap();
+        assert_eq!(
+            utc_now(&fixed_now(), precision, None).unwrap(),
+            1_560_762_129_123
+        );
+        assert_eq!(
+            utc_now(&fixed_now(), Precision::Second, None).unwrap(),
+            1_560_762_129
+        );
+
+        // SOURCE_DATE_EPOCH is used instead of the current time.
+        assert_eq!(
+            utc_now(&fixed_now(), precision, Some(86400)).unwrap(),
+            86_400_000
+        );
+
+        assert!(app(FindOptions::default())
+            .get_matches_from_safe(vec!["ut", "--utc-now", "-o", "+09:00"])
+            .is_err());
+    }
+
+    #[test]
+    fn at_flag() {
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "--at", "86400", "g"]);
+        assert_eq!(
+            frozen_now(&m, Precision::Second).unwrap(),
+            Some(Utc.ymd(1970, 1, 2).and_hms(0, 0, 0).into())
+        );
+        assert_eq!(
+            frozen_now(&m, Precision::MilliSecond).unwrap(),
+            Some(Utc.ymd(1970, 1, 1).and_hms_milli(0, 1, 26, 400).into())
+        );
+
+        let m = app(FindOptions::default()).get_matches_from(vec![
+            "ut",
+            "--at",
+            "2019-06-17T18:02:09+09:00",
+            "g",
+        ]);
+        let now = frozen_now(&m, Precision::Second).unwrap().unwrap();
+        assert_eq!(now, Utc.ymd(2019, 6, 17).and_hms(9, 2, 9));
+        assert_eq!(now.offset(), &FixedOffset::east(9 * 3600));
+
+        assert!(app(FindOptions::default())
+            .get_matches_from_safe(vec!["ut", "--at", "tomorrow", "g"])
+            .is_err());
+    }
+
+    #[test]
+    fn round_offset_flag() {
+        let m = app(FindOptions::default()).get_matches_from(vec![
+            "ut",
+            "-o",
+            "+00:00:30",
+            "--round-offset",
+            "g",
+        ]);
+        assert!(m.is_present("ROUND_OFFSET"));
+
+        let offset = |s: &str| Offset::from_str(s).unwrap();
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err);
+        let mut round = |o| round_offset(o, &mut output).unwrap();
+        assert_eq!(round(offset("+00:00:30")), offset("+00:01"));
+        assert_eq!(round(offset("+00:00:29")), offset("+00:00"));
+        assert_eq!(round(offset("+09:00")), offset("+09:00"));
+        assert!(out.is_empty());
+        assert_eq!(err.iter().filter(|&&b| b == b'\n').count(), 2);
+    }
+
+    #[test]
+    fn precision_defaults() {
+        let m = app(FindOptions::default()).get_matches_from(vec!["ut", "g"]);
+        assert_eq!(
+            precision(&m, &Config::default(), FindOptions::default()).unwrap(),
+            Precision::Second
+        );
+    }
+
+    fn error_message(args: Vec<&str>) -> String {
+        let e = app(FindOptions::default())
+            .setting(AppSettings::ColorNever)
+            .get_matches_from_safe(args)
+            .unwrap_err();
+        e.message.split("\n\n").next().unwrap().to_string()
+    }
+
+    #[test]
+    fn validation_messages() {
+        assert_eq!(
+            error_message(vec!["ut", "-o", "24", "p"]),
+            "error: Invalid value for '--offset <OFFSET>': Wrong offset: hour must be between 0 and 23.
+    input: '24'
+    accepted: `HH`, `HHmm`, `HH:mm` or `HH:mm:ss` with an optional sign, like `9`, `+0900` or `-07:00`, or decimal hours like `+5.5`"
+        );
+        assert_eq!(
+            error_message(vec!["ut", "-o", "9:60", "p"]),
+            "error: Invalid value for '--offset <OFFSET>': Wrong offset: minute must be between 0 and 59.
+    input: '9:60'
+    accepted: `HH`, `HHmm`, `HH:mm` or `HH:mm:ss` with an optional sign, like `9`, `+0900` or `-07:00`, or decimal hours like `+5.5`"
+        );
+        assert_eq!(
+            error_message(vec!["ut", "-p", "x", "p"]),
+            "error: Invalid value for '--precision <PRECISION>': Wrong precision: no matching name.
+    input: 'x'
+    accepted: a possible name, or its unique prefix
+    possible names: [second, millisecond, microsecond, nanosecond]"
+        );
     }
 }
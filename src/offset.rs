@@ -1,14 +1,17 @@
+use std::cmp::Ordering;
 use std::str::FromStr;
 
 use chrono::FixedOffset;
-use regex::{Captures, Regex};
 use thiserror::Error;
 
-use crate::validate::{validate_number, IntoValidationError};
+use crate::pattern::{self, Groups};
+use crate::validate::{validate_number, IntoValidationError, ValidationMessage};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum OffsetError {
-    #[error("Wrong hms text: '{0}'. text must be in `Hmmss` or `HH:mm:ss` format.")]
+    #[error(
+        "Wrong offset text: '{0}'. text must be in `HH`, `HHmm`, `HH:mm`, `HH:mm:ss` or decimal hours format."
+    )]
     WrongFormat(String),
 
     #[error("Wrong hour: '{0}'. hour must be between 0 and 23.")]
@@ -16,6 +19,12 @@ pub enum OffsetError {
 
     #[error("Wrong minute: '{0}'. minute must be between 0 and 59.")]
     WrongMinute(String),
+
+    #[error("Wrong second: '{0}'. second must be between 0 and 59.")]
+    WrongSecond(String),
+
+    #[error("Wrong fraction of hours: '{0}'. fraction must be a whole number of minutes, like .5 or .75.")]
+    WrongFraction(String),
 }
 
 #[cfg(test)]
@@ -43,11 +52,34 @@ impl OffsetError {
             _ => false,
         }
     }
+
+    pub fn is_wrong_second(&self) -> bool {
+        matches!(self, OffsetError::WrongSecond(_))
+    }
+
+    pub fn is_wrong_fraction(&self) -> bool {
+        matches!(self, OffsetError::WrongFraction(_))
+    }
 }
 
 impl IntoValidationError for OffsetError {
     fn into_validation_error(self) -> String {
-        format!("{}", self)
+        use OffsetError::*;
+        let (problem, input) = match self {
+            WrongFormat(s) => ("Wrong offset: text is not in an offset format.", s),
+            WrongHour(s) => ("Wrong offset: hour must be between 0 and 23.", s),
+            WrongMinute(s) => ("Wrong offset: minute must be between 0 and 59.", s),
+            WrongSecond(s) => ("Wrong offset: second must be between 0 and 59.", s),
+            WrongFraction(s) => (
+                "Wrong offset: fraction of hours must be a whole number of minutes.",
+                s,
+            ),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted(
+                "`HH`, `HHmm`, `HH:mm` or `HH:mm:ss` with an optional sign, like `9`, `+0900` or `-07:00`, or decimal hours like `+5.5`",
+            )
+            .to_string()
     }
 }
 
@@ -78,54 +110,146 @@ impl From<&str> for OffsetSign {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialOrd, PartialEq)]
+/// A timezone offset. Offsets are compared by `total_seconds`, so `+00:00` equals `-00:00`.
+#[derive(Debug, Copy, Clone)]
 pub struct Offset {
     sign: OffsetSign,
     h: i32,
     m: i32,
+    s: i32,
+}
+
+impl Offset {
+    fn from_total_seconds(total: i32) -> Offset {
+        let sign = if total < 0 {
+            OffsetSign::Minus
+        } else {
+            OffsetSign::Plus
+        };
+        let abs = total.abs();
+        Offset {
+            sign,
+            h: abs / 3600,
+            m: abs % 3600 / 60,
+            s: abs % 60,
+        }
+    }
+
+    /// Parses decimal hours with an optional sign, like `+5.5` for `+05:30`.
+    /// The fraction must be a whole number of minutes, so `.75` is accepted and `.01` is not.
+    fn from_decimal_hours(text: &str) -> Result<Offset, OffsetError> {
+        let wrong_format = || OffsetError::WrongFormat(text.to_string());
+        let (sign, unsigned) = if text.starts_with(['+', '-']) {
+            (OffsetSign::from(&text[..1]), &text[1..])
+        } else {
+            (OffsetSign::None, text)
+        };
+        let (hours, fraction) = unsigned.split_once('.').ok_or_else(wrong_format)?;
+        let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+        if !is_digits(hours) || hours.len() > 2 || !is_digits(fraction) {
+            return Err(wrong_format());
+        }
+
+        let h = hours
+            .parse()
+            .map_err(|_| OffsetError::WrongHour(text.to_string()))?;
+        validate_number(h, 0, 23, || OffsetError::WrongHour(text.to_string()))?;
+
+        // a fraction of whole minutes has at most 2 digits without trailing zeros, as 60 = 2^2 * 3 * 5.
+        let wrong_fraction = || OffsetError::WrongFraction(text.to_string());
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > 2 {
+            return Err(wrong_fraction());
+        }
+        let scale = 10_i32.pow(fraction.len() as u32);
+        let numerator = fraction.parse::<i32>().unwrap_or(0) * 60;
+        if numerator % scale != 0 {
+            return Err(wrong_fraction());
+        }
+
+        Ok(Offset {
+            sign,
+            h,
+            m: numerator / scale,
+            s: 0,
+        })
+    }
+
+    pub fn total_seconds(&self) -> i32 {
+        self.sign.apply(self.h * 3600 + self.m * 60 + self.s)
+    }
+
+    /// Rounds the offset to the nearest whole minute, and a half minute away from zero.
+    /// So `+00:00:29` is rounded to `+00:00`, and `+00:00:30` to `+00:01`.
+    /// Offsets from `23:59:30` are rounded to `23:59`, since an offset must be shorter than a day.
+    pub fn round_to_minute(self) -> Offset {
+        let total = self.total_seconds();
+        let minutes = (total.abs() + 30) / 60;
+        let minutes = minutes.min(23 * 60 + 59);
+        Offset::from_total_seconds(total.signum() * minutes * 60)
+    }
+}
+
+impl PartialEq for Offset {
+    fn eq(&self, other: &Self) -> bool {
+        self.total_seconds() == other.total_seconds()
+    }
+}
+
+impl Eq for Offset {}
+
+impl PartialOrd for Offset {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Offset {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.total_seconds().cmp(&other.total_seconds())
+    }
 }
 
 impl FromStr for Offset {
     type Err = OffsetError;
 
     fn from_str(text: &str) -> Result<Self, Self::Err> {
-        fn offset_from_captures(captures: Captures, text: &str) -> Result<Offset, OffsetError> {
+        fn offset_from_captures(captures: Groups, text: &str) -> Result<Offset, OffsetError> {
             let sign = captures
                 .get(1)
-                .map(|m| OffsetSign::from(m.as_str()))
+                .map(OffsetSign::from)
                 .unwrap_or(OffsetSign::None);
 
             let h = captures
                 .get(2)
                 .or_else(|| captures.get(4))
-                .map(|s| s.as_str().parse())
+                .map(str::parse)
                 .unwrap_or_else(|| Ok(0))
-                .map_err(|e| {
-                    OffsetError::WrongHour(format!("Parse error. error:{:?}, text:{}", e, text))
-                })?;
-            validate_number(h, 0, 23, || {
-                OffsetError::WrongHour(format!("Wrong number. text:{}", text))
-            })?;
+                .map_err(|_| OffsetError::WrongHour(text.to_string()))?;
+            validate_number(h, 0, 23, || OffsetError::WrongHour(text.to_string()))?;
 
             let m = captures
                 .get(3)
                 .or_else(|| captures.get(5))
-                .map(|s| s.as_str().parse())
+                .map(str::parse)
                 .unwrap_or_else(|| Ok(0))
-                .map_err(|e| {
-                    OffsetError::WrongMinute(format!("Parse error. error:{:?}, text:{}", e, text))
-                })?;
-            validate_number(m, 0, 59, || {
-                OffsetError::WrongMinute(format!("Wrong number. text:{}", text))
-            })?;
-
-            Ok(Offset { sign, h, m })
-        }
+                .map_err(|_| OffsetError::WrongMinute(text.to_string()))?;
+            validate_number(m, 0, 59, || OffsetError::WrongMinute(text.to_string()))?;
+
+            let s = captures
+                .get(6)
+                .map(str::parse)
+                .unwrap_or_else(|| Ok(0))
+                .map_err(|_| OffsetError::WrongSecond(text.to_string()))?;
+            validate_number(s, 0, 59, || OffsetError::WrongSecond(text.to_string()))?;
 
-        let re = Regex::new(r"^([-+])?(?:(\d{2})(\d{2})|(\d{1,2})(?:[:](\d{1,2}))?)$")
-            .expect("wrong regex pattern");
+            Ok(Offset { sign, h, m, s })
+        }
 
-        re.captures(text)
+        if text.contains('.') {
+            return Offset::from_decimal_hours(text);
+        }
+        pattern::offset(text)
             .ok_or_else(|| OffsetError::WrongFormat(text.to_string()))
             .and_then(|captures| offset_from_captures(captures, text))
     }
@@ -133,7 +257,8 @@ impl FromStr for Offset {
 
 impl Into<FixedOffset> for Offset {
     fn into(self) -> FixedOffset {
-        FixedOffset::east(self.sign.apply(self.h * 3600 + self.m * 60))
+        FixedOffset::east_opt(self.total_seconds())
+            .expect("an offset is validated to be less than 24 hours when parsed")
     }
 }
 
@@ -145,7 +270,7 @@ mod tests {
     use crate::validate::validate_argv;
 
     fn offset(sign: OffsetSign, h: i32, m: i32) -> Offset {
-        Offset { sign, h, m }
+        Offset { sign, h, m, s: 0 }
     }
 
     #[test]
@@ -168,6 +293,74 @@ mod tests {
         let r = Offset::from_str("23:60");
         assert!(r.is_err());
         assert!(r.err().unwrap().is_wrong_minute());
+
+        assert_eq!(
+            Offset::from_str("+05:45:30").map(|o| o.total_seconds()),
+            Ok(5 * 3600 + 45 * 60 + 30)
+        );
+        assert_eq!(
+            Offset::from_str("-00:00:30").map(|o| o.total_seconds()),
+            Ok(-30)
+        );
+        let r = Offset::from_str("00:00:60");
+        assert!(r.is_err());
+        assert!(r.err().unwrap().is_wrong_second());
+    }
+
+    #[test]
+    fn offset_from_decimal_hours() {
+        use OffsetSign::*;
+        let fixed = |s: &str| {
+            let offset: FixedOffset = Offset::from_str(s).unwrap().into();
+            offset.to_string()
+        };
+        assert_eq!(fixed("+5.5"), "+05:30");
+        assert_eq!(fixed("+9.5"), "+09:30");
+        assert_eq!(fixed("5.75"), "+05:45");
+        assert_eq!(fixed("-3.25"), "-03:15");
+        assert_eq!(fixed("-0.5"), "-00:30");
+        assert_eq!(fixed("+23.95"), "+23:57");
+        assert_eq!(fixed("+9.0"), "+09:00");
+        assert_eq!(fixed("+5.500"), "+05:30");
+        assert_eq!(Offset::from_str("+9.5"), Ok(offset(Plus, 9, 30)));
+
+        // .1 hours is exactly 6 minutes, as fractions are computed in decimal.
+        assert_eq!(fixed("+5.1"), "+05:06");
+
+        // 36 seconds.
+        let r = Offset::from_str("+5.01");
+        assert!(r.is_err());
+        assert!(r.err().unwrap().is_wrong_fraction());
+        // 7.5 minutes.
+        assert!(Offset::from_str("+5.125").unwrap_err().is_wrong_fraction());
+        assert!(Offset::from_str("+5.333").unwrap_err().is_wrong_fraction());
+
+        assert!(Offset::from_str("24.5").unwrap_err().is_wrong_hour());
+        for text in &[
+            "+.5", "+5.", "5.5.5", "+005.5", "+5.-5", "+5,5", "++5.5", "5.5h",
+        ] {
+            assert!(
+                Offset::from_str(text).unwrap_err().is_wrong_format(),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn round_to_minute() {
+        let rounded = |s: &str| {
+            let offset: FixedOffset = Offset::from_str(s).unwrap().round_to_minute().into();
+            offset.to_string()
+        };
+        assert_eq!(rounded("+00:00:29"), "+00:00");
+        assert_eq!(rounded("+00:00:30"), "+00:01");
+        assert_eq!(rounded("-00:00:30"), "-00:01");
+        assert_eq!(rounded("-00:00:29"), "+00:00");
+        assert_eq!(rounded("+09:18:59"), "+09:19");
+        assert_eq!(rounded("+05:45"), "+05:45");
+        assert_eq!(rounded("+23:59:30"), "+23:59");
+        assert_eq!(rounded("-23:59:59"), "-23:59");
     }
 
     #[test]
@@ -181,6 +374,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn offset_ord() {
+        let offset = |s: &str| Offset::from_str(s).unwrap();
+        assert_eq!(offset("-01:00").total_seconds(), -3600);
+        assert_eq!(offset("+05:45").total_seconds(), 5 * 3600 + 45 * 60);
+
+        assert!(offset("-01:00") < offset("+00:00"));
+        assert!(offset("+00:00") < offset("+05:45"));
+        assert!(offset("-00:30") < offset("+00:00"));
+        assert!(offset("-10") < offset("-01:00"));
+        assert_eq!(offset("+00:00"), offset("-00:00"));
+        assert_eq!(offset("9"), offset("+0900"));
+
+        let mut offsets = vec![
+            offset("+05:45"),
+            offset("0"),
+            offset("-01:00"),
+            offset("-00:00"),
+        ];
+        offsets.sort();
+        offsets.dedup();
+        assert_eq!(
+            offsets,
+            vec![offset("-01:00"), offset("0"), offset("+05:45")]
+        );
+    }
+
     #[test]
     fn validate() {
         let validate_argv = |s: &str| validate_argv::<Offset, OffsetError>(s.to_string());
@@ -204,5 +424,8 @@ mod tests {
         assert!(validate_argv(":").is_err());
         assert!(validate_argv("24").is_err());
         assert!(validate_argv("23:60").is_err());
+
+        assert!(validate_argv("+5.5").is_ok());
+        assert!(validate_argv("+5.01").is_err());
     }
 }
@@ -0,0 +1,259 @@
+use std::fmt::Arguments;
+use std::io::{self, Write};
+
+use crate::record::{self, OutputFormat, Record};
+use crate::table::{Table, BUFFERED_ROWS};
+use crate::verbosity::Verbosity;
+
+/// A writer which holds back a trailing newline until more bytes are written,
+/// so that the newline of the last line is not written at all.
+struct NoNewline<'a> {
+    inner: &'a mut dyn Write,
+    enabled: bool,
+    pending: bool,
+}
+
+impl<'a> Write for NoNewline<'a> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled {
+            return self.inner.write(buf);
+        }
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pending {
+            self.inner.write_all(b"\n")?;
+            self.pending = false;
+        }
+        match buf.split_last() {
+            Some((b'\n', line)) => {
+                self.inner.write_all(line)?;
+                self.pending = true;
+            }
+            _ => self.inner.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writers of a command. Outputs go to `out`, and warnings and notices go to `err`.
+pub struct Output<'a> {
+    out: NoNewline<'a>,
+    err: &'a mut dyn Write,
+    verbosity: Verbosity,
+    format: OutputFormat,
+    has_header: bool,
+    table: Option<Table>,
+}
+
+impl<'a> Output<'a> {
+    pub fn new(out: &'a mut dyn Write, err: &'a mut dyn Write) -> Output<'a> {
+        Output {
+            out: NoNewline {
+                inner: out,
+                enabled: false,
+                pending: false,
+            },
+            err,
+            verbosity: Verbosity::default(),
+            format: OutputFormat::default(),
+            has_header: false,
+            table: None,
+        }
+    }
+
+    /// Sets which lines are written to `err`. Warnings are always written.
+    pub fn verbosity(self, verbosity: Verbosity) -> Output<'a> {
+        Output { verbosity, ..self }
+    }
+
+    /// Sets how records are written to `out`.
+    pub fn format(self, format: OutputFormat) -> Output<'a> {
+        Output { format, ..self }
+    }
+
+    /// Writes outputs without the newline at the end, like `echo -n`.
+    pub fn no_newline(mut self, enabled: bool) -> Output<'a> {
+        self.out.enabled = enabled;
+        self
+    }
+
+    pub fn output_format(&self) -> OutputFormat {
+        self.format
+    }
+
+    pub fn out(&mut self) -> &mut dyn Write {
+        &mut self.out
+    }
+
+    /// Writes the record to `out` in the output format, or the plain text in plain.
+    /// In CSV, the header is written before the first record.
+    /// In a table, records are buffered to align the columns, until `flush` or the limit of the buffer.
+    pub fn record<R: Record>(&mut self, record: &R, plain: Arguments) -> io::Result<()> {
+        let to_io = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        match self.format {
+            OutputFormat::Plain => write!(self.out(), "{}", plain),
+            OutputFormat::Json => {
+                writeln!(self.out(), "{}", record::json(record).map_err(to_io)?)
+            }
+            OutputFormat::Csv => {
+                if !self.has_header {
+                    writeln!(self.out(), "{}", record::csv_header::<R>())?;
+                    self.has_header = true;
+                }
+                writeln!(self.out(), "{}", record::csv(record).map_err(to_io)?)
+            }
+            OutputFormat::Table => {
+                let row = record::cells(record).map_err(to_io)?;
+                self.table
+                    .get_or_insert_with(|| Table::new(R::HEADER, BUFFERED_ROWS))
+                    .push(row, &mut self.out)
+            }
+        }
+    }
+
+    /// Writes a line to `err`, like a warning or an error of an input.
+    pub fn warn(&mut self, args: Arguments) -> io::Result<()> {
+        writeln!(self.err, "{}", args)
+    }
+
+    /// Writes a line to `err`, unless quiet.
+    pub fn notice(&mut self, args: Arguments) -> io::Result<()> {
+        self.log(Verbosity::Normal, args)
+    }
+
+    /// Writes a line to `err` with `-v`.
+    pub fn info(&mut self, args: Arguments) -> io::Result<()> {
+        self.log(Verbosity::Verbose, args)
+    }
+
+    /// Writes a line to `err` with `-vv`.
+    pub fn trace(&mut self, args: Arguments) -> io::Result<()> {
+        self.log(Verbosity::Trace, args)
+    }
+
+    fn log(&mut self, level: Verbosity, args: Arguments) -> io::Result<()> {
+        if self.verbosity >= level {
+            self.warn(args)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Writes the buffered rows of a table, and flushes the writers.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if let Some(ref mut table) = self.table {
+            table.flush(&mut self.out)?;
+        }
+        self.out.flush()?;
+        self.err.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use crate::output::Output;
+    use crate::record::{OutputFormat, Record};
+    use crate::verbosity::Verbosity;
+
+    #[test]
+    fn quiet() {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err);
+        writeln!(output.out(), "1560762129").unwrap();
+        output.warn(format_args!("Warning: {}", 1)).unwrap();
+        output.notice(format_args!("Hint: {}", 2)).unwrap();
+        assert_eq!(out, b"1560762129\n");
+        assert_eq!(err, b"Warning: 1\nHint: 2\n");
+
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err).verbosity(Verbosity::Quiet);
+        output.warn(format_args!("Warning: {}", 1)).unwrap();
+        output.notice(format_args!("Hint: {}", 2)).unwrap();
+        output.info(format_args!("precision: {}", 3)).unwrap();
+        assert_eq!(err, b"Warning: 1\n");
+    }
+
+    #[test]
+    fn verbose() {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err);
+        output.info(format_args!("precision: {}", 1)).unwrap();
+        assert!(err.is_empty());
+
+        let levels = [
+            (Verbosity::Verbose, "1\n2\n"),
+            (Verbosity::Trace, "1\n2\n3\n"),
+        ];
+        for (verbosity, expected) in levels.iter().copied() {
+            let (mut out, mut err) = (Vec::new(), Vec::new());
+            let mut output = Output::new(&mut out, &mut err).verbosity(verbosity);
+            output.notice(format_args!("{}", 1)).unwrap();
+            output.info(format_args!("{}", 2)).unwrap();
+            output.trace(format_args!("{}", 3)).unwrap();
+            assert_eq!(String::from_utf8(err).unwrap(), expected);
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Sample {
+        timestamp: i64,
+    }
+
+    impl Record for Sample {
+        const HEADER: &'static [&'static str] = &["timestamp"];
+    }
+
+    #[test]
+    fn records() {
+        let formats = [
+            (OutputFormat::Plain, "1\n2\n"),
+            (OutputFormat::Json, "{\"timestamp\":1}\n{\"timestamp\":2}\n"),
+            (OutputFormat::Csv, "timestamp\n1\n2\n"),
+            (OutputFormat::Table, "timestamp\n        1\n        2\n"),
+        ];
+        for (format, expected) in formats.iter().copied() {
+            let (mut out, mut err) = (Vec::new(), Vec::new());
+            let mut output = Output::new(&mut out, &mut err).format(format);
+            for timestamp in 1..=2 {
+                output
+                    .record(&Sample { timestamp }, format_args!("{}\n", timestamp))
+                    .unwrap();
+            }
+            output.flush().unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn no_newline() {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err).no_newline(true);
+        writeln!(output.out(), "1").unwrap();
+        output.out().flush().unwrap();
+        write!(output.out(), "").unwrap();
+        write!(output.out(), "2\n\n3").unwrap();
+        writeln!(output.out()).unwrap();
+        output.warn(format_args!("Warning: {}", 1)).unwrap();
+        output.flush().unwrap();
+        assert_eq!(out, b"1\n2\n\n3");
+        assert_eq!(err, b"Warning: 1\n");
+
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let mut output = Output::new(&mut out, &mut err)
+            .format(OutputFormat::Csv)
+            .no_newline(true);
+        for timestamp in 1..=2 {
+            output
+                .record(&Sample { timestamp }, format_args!("{}\n", timestamp))
+                .unwrap();
+        }
+        assert_eq!(out, b"timestamp\n1\n2");
+    }
+}
@@ -1,11 +1,9 @@
 use std::fmt::Debug;
 use std::str::FromStr;
 
-use regex::Match;
-
-pub fn extract_number<E: Debug, T: FromStr<Err = E>>(maybe_match: Option<Match>) -> T {
-    maybe_match
-        .map(|m| m.as_str().parse().expect("must be a number text."))
+pub fn extract_number<E: Debug, T: FromStr<Err = E>>(maybe_text: Option<&str>) -> T {
+    maybe_text
+        .map(|s| s.parse().expect("must be a number text."))
         .unwrap()
 }
 
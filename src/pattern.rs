@@ -0,0 +1,616 @@
+//! Patterns of argument texts, like `2019-06-17`, `+09:00` or `1y2mon3d`.
+//!
+//! With the `regex` feature (default), the patterns are matched by the `regex` crate.
+//! Without it, small hand-written scanners match the same texts and capture the same groups,
+//! so parsers built on the patterns behave the same in both builds.
+//! Digits are ASCII digits only, like `[0-9]`.
+
+use std::ops::Range;
+
+/// Groups of a match, by the group numbers of the pattern. The group 0 is the whole text.
+#[derive(Debug, PartialEq)]
+pub struct Groups<'a>(Vec<Option<&'a str>>);
+
+impl<'a> Groups<'a> {
+    pub fn get(&self, i: usize) -> Option<&'a str> {
+        self.0.get(i).copied().flatten()
+    }
+}
+
+/// `YYYYmmdd`, or `YYYY-mm-dd` with a four or two-digit year. `-` can be `/`.
+pub const YMD: &str =
+    r"^(?:([0-9]{4})([0-9]{2})([0-9]{2})|([0-9]{4}|[0-9]{2})[-/]([0-9]{1,2})[-/]([0-9]{1,2}))$";
+
+/// `HHMMSS` or `HH:MM:SS`.
+pub const HMS: &str =
+    r"^(?:([0-9]{2})([0-9]{2})([0-9]{2})|([0-9]{1,2})[:]([0-9]{1,2})[:]([0-9]{1,2}))$";
+
+/// `+HHMM`, `+HH`, `+HH:MM` or `+HH:MM:SS`. The sign is optional.
+pub const OFFSET: &str =
+    r"^([-+])?(?:([0-9]{2})([0-9]{2})|([0-9]{1,2})(?:[:]([0-9]{1,2})(?:[:]([0-9]{2}))?)?)$";
+
+/// A delta item like `-3d`.
+pub const DELTA_ITEM: &str = r"^([-+]?[0-9]+)([a-zA-Z]+)$";
+
+/// Delta items without separators, like `1y2mon3d`.
+pub const DELTA_ITEMS: &str = r"^(?:[-+]?[0-9]+[a-zA-Z]+)+$";
+
+/// An ISO 8601 duration like `P1Y2M10DT2H30M1.5S`.
+pub const ISO8601: &str = r"^([-+])?P(?:([0-9]+)Y)?(?:([0-9]+)M)?(?:([0-9]+)W)?(?:([0-9]+)D)?(?:T(?:([0-9]+)H)?(?:([0-9]+)M)?(?:([0-9]+)(?:[.,]([0-9]{1,3}))?S)?)?$";
+
+/// `N` or `MIN-MAX`.
+pub const DIGIT_RANGE: &str = r"^([0-9]+)(?:-([0-9]+))?$";
+
+#[cfg(feature = "regex")]
+mod re {
+    use regex::Regex;
+
+    use super::Groups;
+
+    fn captures<'a>(pattern: &str, text: &'a str) -> Option<Groups<'a>> {
+        Regex::new(pattern)
+            .expect("wrong regex pattern")
+            .captures(text)
+            .map(|caps| Groups(caps.iter().map(|m| m.map(|m| m.as_str())).collect()))
+    }
+
+    pub fn ymd(text: &str) -> Option<Groups<'_>> {
+        captures(super::YMD, text)
+    }
+
+    pub fn hms(text: &str) -> Option<Groups<'_>> {
+        captures(super::HMS, text)
+    }
+
+    pub fn offset(text: &str) -> Option<Groups<'_>> {
+        captures(super::OFFSET, text)
+    }
+
+    pub fn delta_item(text: &str) -> Option<Groups<'_>> {
+        captures(super::DELTA_ITEM, text)
+    }
+
+    /// Splits the text into delta items, or returns `None` if the text is not `DELTA_ITEMS`.
+    pub fn delta_items(text: &str) -> Option<Vec<&str>> {
+        if !Regex::new(super::DELTA_ITEMS)
+            .expect("wrong regex pattern")
+            .is_match(text)
+        {
+            return None;
+        }
+
+        let re = Regex::new(r"[-+]?[0-9]+[a-zA-Z]+").expect("wrong regex pattern");
+        Some(re.find_iter(text).map(|m| m.as_str()).collect())
+    }
+
+    pub fn iso8601(text: &str) -> Option<Groups<'_>> {
+        captures(super::ISO8601, text)
+    }
+
+    pub fn digit_range(text: &str) -> Option<Groups<'_>> {
+        captures(super::DIGIT_RANGE, text)
+    }
+
+    pub fn integers(min: usize, max: usize) -> Regex {
+        Regex::new(&format!(r"\b[0-9]{{{},{}}}\b", min, max)).expect("wrong regex pattern")
+    }
+}
+
+/// Hand-written scanners of the patterns. Each one follows its regex group by group.
+#[cfg(any(test, not(feature = "regex")))]
+mod scan {
+    use std::ops::Range;
+
+    use super::Groups;
+
+    struct Scanner<'a> {
+        text: &'a str,
+        pos: usize,
+    }
+
+    impl<'a> Scanner<'a> {
+        fn new(text: &'a str) -> Scanner<'a> {
+            Scanner { text, pos: 0 }
+        }
+
+        fn is_end(&self) -> bool {
+            self.pos == self.text.len()
+        }
+
+        fn end(&self) -> Option<()> {
+            if self.is_end() {
+                Some(())
+            } else {
+                None
+            }
+        }
+
+        /// Takes a char of `chars`, which are ASCII.
+        fn eat(&mut self, chars: &str) -> Option<&'a str> {
+            let b = *self.text.as_bytes().get(self.pos)?;
+            if chars.as_bytes().contains(&b) {
+                self.pos += 1;
+                Some(&self.text[self.pos - 1..self.pos])
+            } else {
+                None
+            }
+        }
+
+        /// Takes all bytes while `f` holds for them. Returns an empty text if none.
+        fn take_while<F: Fn(u8) -> bool>(&mut self, f: F) -> &'a str {
+            let start = self.pos;
+            while self.text.as_bytes().get(self.pos).is_some_and(|&b| f(b)) {
+                self.pos += 1;
+            }
+            &self.text[start..self.pos]
+        }
+
+        /// Takes all digits. As the next char is not a digit, `[0-9]{min,max}` must match all of them.
+        fn digits(&mut self, min: usize, max: usize) -> Option<&'a str> {
+            let digits = self.take_while(|b| b.is_ascii_digit());
+            if min <= digits.len() && digits.len() <= max {
+                Some(digits)
+            } else {
+                None
+            }
+        }
+
+        fn letters(&mut self) -> Option<&'a str> {
+            let letters = self.take_while(|b| b.is_ascii_alphabetic());
+            if letters.is_empty() {
+                None
+            } else {
+                Some(letters)
+            }
+        }
+
+        /// Takes an optional group like `(?:([0-9]+)Y)?`, or nothing if it does not match.
+        fn number_with(&mut self, unit: &str) -> Option<&'a str> {
+            let start = self.pos;
+            let number = self
+                .digits(1, usize::MAX)
+                .filter(|_| self.eat(unit).is_some());
+            if number.is_none() {
+                self.pos = start;
+            }
+            number
+        }
+
+        /// Takes `[-+]?[0-9]+[a-zA-Z]+`.
+        fn delta_item(&mut self) -> Option<(&'a str, &'a str)> {
+            let start = self.pos;
+            self.eat("-+");
+            self.digits(1, usize::MAX)?;
+            let value = &self.text[start..self.pos];
+            self.letters().map(|unit| (value, unit))
+        }
+    }
+
+    fn groups<'a>(text: &'a str, groups: &[Option<&'a str>]) -> Groups<'a> {
+        Groups(
+            std::iter::once(Some(text))
+                .chain(groups.iter().copied())
+                .collect(),
+        )
+    }
+
+    pub fn ymd(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let y = sc.digits(2, 8)?;
+        if y.len() == 8 && sc.is_end() {
+            let (y, md) = y.split_at(4);
+            let (m, d) = md.split_at(2);
+            return Some(groups(text, &[Some(y), Some(m), Some(d), None, None, None]));
+        }
+        if y.len() != 4 && y.len() != 2 {
+            return None;
+        }
+        sc.eat("-/")?;
+        let m = sc.digits(1, 2)?;
+        sc.eat("-/")?;
+        let d = sc.digits(1, 2)?;
+        sc.end()?;
+        Some(groups(text, &[None, None, None, Some(y), Some(m), Some(d)]))
+    }
+
+    pub fn hms(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let h = sc.digits(1, 6)?;
+        if h.len() == 6 && sc.is_end() {
+            let (h, ms) = h.split_at(2);
+            let (m, s) = ms.split_at(2);
+            return Some(groups(text, &[Some(h), Some(m), Some(s), None, None, None]));
+        }
+        if h.len() > 2 {
+            return None;
+        }
+        sc.eat(":")?;
+        let m = sc.digits(1, 2)?;
+        sc.eat(":")?;
+        let s = sc.digits(1, 2)?;
+        sc.end()?;
+        Some(groups(text, &[None, None, None, Some(h), Some(m), Some(s)]))
+    }
+
+    pub fn offset(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let sign = sc.eat("-+");
+        let h = sc.digits(1, 4)?;
+        if h.len() == 4 {
+            sc.end()?;
+            let (h, m) = h.split_at(2);
+            return Some(groups(text, &[sign, Some(h), Some(m), None, None, None]));
+        }
+        if h.len() > 2 {
+            return None;
+        }
+        let mut m = None;
+        let mut s = None;
+        if sc.eat(":").is_some() {
+            m = Some(sc.digits(1, 2)?);
+            if sc.eat(":").is_some() {
+                s = Some(sc.digits(2, 2)?);
+            }
+        }
+        sc.end()?;
+        Some(groups(text, &[sign, None, None, Some(h), m, s]))
+    }
+
+    pub fn delta_item(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let (value, unit) = sc.delta_item()?;
+        sc.end()?;
+        Some(groups(text, &[Some(value), Some(unit)]))
+    }
+
+    pub fn delta_items(text: &str) -> Option<Vec<&str>> {
+        let mut sc = Scanner::new(text);
+        let mut items = Vec::new();
+        loop {
+            let start = sc.pos;
+            sc.delta_item()?;
+            items.push(&text[start..sc.pos]);
+            if sc.is_end() {
+                return Some(items);
+            }
+        }
+    }
+
+    pub fn iso8601(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let mut caps = [None; 9];
+        caps[0] = sc.eat("-+");
+        sc.eat("P")?;
+        for (i, unit) in ["Y", "M", "W", "D"].iter().enumerate() {
+            caps[i + 1] = sc.number_with(unit);
+        }
+        if sc.eat("T").is_some() {
+            caps[5] = sc.number_with("H");
+            caps[6] = sc.number_with("M");
+
+            let start = sc.pos;
+            let seconds = sc.digits(1, usize::MAX).and_then(|seconds| {
+                let fraction = match sc.eat(".,") {
+                    Some(_) => Some(sc.digits(1, 3)?),
+                    None => None,
+                };
+                sc.eat("S").map(|_| (seconds, fraction))
+            });
+            match seconds {
+                Some((seconds, fraction)) => {
+                    caps[7] = Some(seconds);
+                    caps[8] = fraction;
+                }
+                None => sc.pos = start,
+            }
+        }
+        sc.end()?;
+        Some(groups(text, &caps))
+    }
+
+    pub fn digit_range(text: &str) -> Option<Groups<'_>> {
+        let mut sc = Scanner::new(text);
+        let min = sc.digits(1, usize::MAX)?;
+        let max = match sc.eat("-") {
+            Some(_) => Some(sc.digits(1, usize::MAX)?),
+            None => None,
+        };
+        sc.end()?;
+        Some(groups(text, &[Some(min), max]))
+    }
+
+    /// Word chars of `\b`. Unlike the regex, marks and connector punctuations other than `_`
+    /// are not word chars, and numbers like `²` are.
+    fn is_word(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    pub fn integers(text: &str, min: usize, max: usize) -> Vec<Range<usize>> {
+        let bytes = text.as_bytes();
+        let mut ranges = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            if !bytes[pos].is_ascii_digit() {
+                pos += 1;
+                continue;
+            }
+            let start = pos;
+            while pos < bytes.len() && bytes[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            let bounded = !text[..start].chars().next_back().is_some_and(is_word)
+                && !text[pos..].chars().next().is_some_and(is_word);
+            if bounded && min <= pos - start && pos - start <= max {
+                ranges.push(start..pos);
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(feature = "regex")]
+pub use re::{delta_item, delta_items, digit_range, hms, iso8601, offset, ymd};
+#[cfg(not(feature = "regex"))]
+pub use scan::{delta_item, delta_items, digit_range, hms, iso8601, offset, ymd};
+
+/// Finds integers of `min` to `max` digits which are words by themselves, like
+/// `\b[0-9]{min,max}\b`.
+pub struct Integers {
+    #[cfg(feature = "regex")]
+    re: regex::Regex,
+    #[cfg(not(feature = "regex"))]
+    digits: (usize, usize),
+}
+
+impl Integers {
+    pub fn new(min: usize, max: usize) -> Integers {
+        Integers {
+            #[cfg(feature = "regex")]
+            re: re::integers(min, max),
+            #[cfg(not(feature = "regex"))]
+            digits: (min, max),
+        }
+    }
+
+    /// Returns the byte ranges of the integers in the text.
+    pub fn find_iter(&self, text: &str) -> Vec<Range<usize>> {
+        #[cfg(feature = "regex")]
+        return self.re.find_iter(text).map(|m| m.range()).collect();
+        #[cfg(not(feature = "regex"))]
+        return scan::integers(text, self.digits.0, self.digits.1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pattern::{delta_items, ymd, Integers};
+
+    #[test]
+    fn groups() {
+        let groups = ymd("2019-06-17").unwrap();
+        assert_eq!(groups.get(0), Some("2019-06-17"));
+        assert_eq!(groups.get(1), None);
+        assert_eq!(groups.get(4), Some("2019"));
+        assert_eq!(groups.get(6), Some("17"));
+        assert_eq!(groups.get(7), None);
+
+        assert_eq!(ymd("２０１９-06-17"), None);
+    }
+
+    #[test]
+    fn delta_items_and_integers() {
+        assert_eq!(delta_items("1y-2mon+3d"), Some(vec!["1y", "-2mon", "+3d"]));
+        assert_eq!(delta_items("1y2"), None);
+
+        let integers = Integers::new(2, 3);
+        assert_eq!(
+            integers.find_iter("1 12 123 1234 a12 12a (12)"),
+            vec![2..4, 5..8, 23..25]
+        );
+    }
+
+    /// Runs the same matrix on both of the regex and the scanners.
+    #[cfg(feature = "regex")]
+    mod matrix {
+        use crate::pattern::{re, scan, Groups};
+
+        type Pattern = fn(&str) -> Option<Groups>;
+
+        fn assert_same(re: Pattern, scan: Pattern, texts: &[&str]) {
+            for text in texts {
+                assert_eq!(re(text), scan(text), "text: {:?}", text);
+            }
+        }
+
+        const COMMON: &[&str] = &["", " ", "-", "+", ":", "a", "1", "12", "１２", "12 ", " 12"];
+
+        #[test]
+        fn ymd() {
+            assert_same(re::ymd, scan::ymd, COMMON);
+            assert_same(
+                re::ymd,
+                scan::ymd,
+                &[
+                    "20190617",
+                    "2019061",
+                    "201906170",
+                    "2019-06-17",
+                    "2019/6/7",
+                    "2019-06/17",
+                    "19-06-17",
+                    "190-06-17",
+                    "9-06-17",
+                    "2019-006-17",
+                    "2019-06-017",
+                    "2019-06-",
+                    "2019--17",
+                    "2019-06-17-",
+                    "2019_06_17",
+                    "2019-0a-17",
+                    "20190617Z",
+                ],
+            );
+        }
+
+        #[test]
+        fn hms() {
+            assert_same(re::hms, scan::hms, COMMON);
+            assert_same(
+                re::hms,
+                scan::hms,
+                &[
+                    "090209",
+                    "09020",
+                    "0902090",
+                    "09:02:09",
+                    "9:2:9",
+                    "09:02",
+                    "009:02:09",
+                    "09:02:009",
+                    "09:02:",
+                    "09::09",
+                    "09-02-09",
+                    "24:60:60",
+                    "9:02:09 ",
+                ],
+            );
+        }
+
+        #[test]
+        fn offset() {
+            assert_same(re::offset, scan::offset, COMMON);
+            assert_same(
+                re::offset,
+                scan::offset,
+                &[
+                    "0900",
+                    "+0900",
+                    "-0930",
+                    "+09",
+                    "-9",
+                    "+09:00",
+                    "9:3",
+                    "+09:30:15",
+                    "+09:30:5",
+                    "+09:30:155",
+                    "+09:",
+                    "+09:30:",
+                    "090",
+                    "+09000",
+                    "++09",
+                    "+-09",
+                    "+9:00",
+                    "+09:000",
+                    "+24:00",
+                    "0900:00",
+                ],
+            );
+        }
+
+        #[test]
+        fn delta_item() {
+            assert_same(re::delta_item, scan::delta_item, COMMON);
+            assert_same(
+                re::delta_item,
+                scan::delta_item,
+                &[
+                    "3d",
+                    "-3d",
+                    "+12hour",
+                    "1y2d",
+                    "d",
+                    "-d",
+                    "3",
+                    "3d ",
+                    "3_d",
+                    "99999999999ms",
+                    "+-3d",
+                    "3dé",
+                ],
+            );
+        }
+
+        #[test]
+        fn delta_items() {
+            let texts = [
+                "", "1y", "1y2mon3d", "-1d+12h", "1c2dec", "1y2", "1y 2d", "y", "+", "1y-", "1yé",
+            ];
+            for text in texts.iter() {
+                assert_eq!(
+                    re::delta_items(text),
+                    scan::delta_items(text),
+                    "text: {:?}",
+                    text
+                );
+            }
+        }
+
+        #[test]
+        fn iso8601() {
+            assert_same(re::iso8601, scan::iso8601, COMMON);
+            assert_same(
+                re::iso8601,
+                scan::iso8601,
+                &[
+                    "P",
+                    "PT",
+                    "-P1D",
+                    "+P1Y2M10DT2H30M",
+                    "P1W",
+                    "P1M2Y",
+                    "P1DT",
+                    "PT1H2M3S",
+                    "PT1.5S",
+                    "PT1,123S",
+                    "PT1.1234S",
+                    "PT1.S",
+                    "PT.5S",
+                    "PT1.5",
+                    "PT1M",
+                    "P1M",
+                    "P1Y1M1W1DT1H1M1S",
+                    "PY",
+                    "P1",
+                    "PT1",
+                    "P1DT1D",
+                    "PTT1H",
+                    "P1D ",
+                    "p1d",
+                    "P01Y",
+                    "PT1H1H",
+                ],
+            );
+        }
+
+        #[test]
+        fn digit_range() {
+            assert_same(re::digit_range, scan::digit_range, COMMON);
+            assert_same(
+                re::digit_range,
+                scan::digit_range,
+                &[
+                    "10", "9-13", "9-", "-13", "9--13", "9-13-", "a-b", "0", "9 - 13",
+                ],
+            );
+        }
+
+        #[test]
+        fn integers() {
+            let texts = [
+                "",
+                "1560762129",
+                "ts=1560762129, ms=1560762129123",
+                "id=12345 ts=1560762129123 req42 at 1560762129",
+                "[1560762129]",
+                "a1560762129 1560762129b _1560762129 1560762129_",
+                "é1560762129 1560762129日 1560762129.5 -1560762129",
+                "15607621291560762129",
+            ];
+            let re = re::integers(9, 13);
+            for text in texts.iter() {
+                let found: Vec<_> = re.find_iter(text).map(|m| m.range()).collect();
+                assert_eq!(found, scan::integers(text, 9, 13), "text: {:?}", text);
+            }
+        }
+    }
+}
@@ -1,15 +1,23 @@
-use chrono::{DateTime, TimeZone};
+use std::convert::TryFrom;
+
+use chrono::{DateTime, Duration, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
 use crate::find::{FindByName, FindError, PossibleNames, PossibleValues};
-use crate::validate::IntoValidationError;
+use crate::validate::{IntoValidationError, ValidationMessage};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum PrecisionError {
     #[error("Wrong precision. error:{0}")]
     WrongName(FindError),
+
+    #[error("Timestamp out of range: {0}. timestamps in {1} must be between {2} and {3}.")]
+    OutOfRange(i64, Precision, i64, i64),
+
+    #[error("Cannot represent {0} as a timestamp in {1}.")]
+    Unrepresentable(String, Precision),
 }
 
 impl From<FindError> for PrecisionError {
@@ -21,14 +29,12 @@ impl From<FindError> for PrecisionError {
 impl IntoValidationError for PrecisionError {
     fn into_validation_error(self) -> String {
         use PrecisionError::*;
-        match &self {
-            WrongName(e) => match e {
-                FindError::NotFound => {
-                    let names = Precision::possible_names();
-                    format!("{} possible names: [{}]", self, names.join(", "))
-                }
-                _ => format!("{}", self),
-            },
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("precision", &e, Precision::possible_names())
+                    .to_string()
+            }
+            OutOfRange(..) | Unrepresentable(..) => self.to_string(),
         }
     }
 }
@@ -38,22 +44,116 @@ pub enum Precision {
     #[strum(serialize = "second")]
     Second,
 
-    #[strum(serialize = "millisecond", serialize = "ms")]
+    /// `m` is kept for millisecond, as it was before microsecond was added.
+    #[strum(serialize = "millisecond", serialize = "ms", serialize = "m")]
     MilliSecond,
+
+    #[strum(serialize = "microsecond", serialize = "us")]
+    MicroSecond,
+
+    #[strum(serialize = "nanosecond", serialize = "ns")]
+    NanoSecond,
 }
 
 impl Precision {
-    pub fn parse_timestamp<Tz: TimeZone>(self, tz: Tz, timestamp: i64) -> DateTime<Tz> {
+    fn units_per_second(self) -> i64 {
         match self {
-            Precision::Second => tz.timestamp(timestamp, 0),
-            Precision::MilliSecond => tz.timestamp_millis(timestamp),
+            Precision::Second => 1,
+            Precision::MilliSecond => 1_000,
+            Precision::MicroSecond => 1_000_000,
+            Precision::NanoSecond => 1_000_000_000,
         }
     }
 
-    pub fn to_timestamp<Tz: TimeZone>(self, dt: DateTime<Tz>) -> i64 {
+    /// Returns the duration of one tick, like 1ms for millisecond.
+    pub fn tick_duration(self) -> Duration {
+        Duration::nanoseconds(1_000_000_000 / self.units_per_second())
+    }
+
+    /// Returns the number of fractional digits of a second, like 3 for millisecond.
+    pub fn subsec_digits(self) -> usize {
         match self {
-            Precision::Second => dt.timestamp(),
-            Precision::MilliSecond => dt.timestamp_millis(),
+            Precision::Second => 0,
+            Precision::MilliSecond => 3,
+            Precision::MicroSecond => 6,
+            Precision::NanoSecond => 9,
+        }
+    }
+
+    /// Drops the fraction of the datetime finer than the precision, like `09:02:09.999` to `09:02:09` for second.
+    pub fn trim<Tz: TimeZone>(self, dt: DateTime<Tz>) -> DateTime<Tz> {
+        let tick = self.tick_duration().num_nanoseconds().unwrap_or(1) as u32;
+        // subtracts the fraction instead of setting it, which would resolve the local time again.
+        let nanos = dt.nanosecond();
+        dt - Duration::nanoseconds(i64::from(nanos % tick))
+    }
+
+    /// Returns the last time of a day which can be represented, like `23:59:59.999` for millisecond.
+    pub fn end_of_day(self) -> NaiveTime {
+        NaiveTime::from_hms_opt(23, 59, 59).expect("valid time")
+            + (Duration::seconds(1) - self.tick_duration())
+    }
+
+    /// Returns the range of timestamps which can be represented as datetimes.
+    pub fn timestamp_range(self) -> (i64, i64) {
+        let min = self
+            .to_timestamp(DateTime::<Utc>::MIN_UTC)
+            .unwrap_or(i64::MIN);
+        let max = self
+            .to_timestamp(DateTime::<Utc>::MAX_UTC)
+            .unwrap_or(i64::MAX);
+        (min, max)
+    }
+
+    pub fn parse_timestamp<Tz: TimeZone>(
+        self,
+        tz: Tz,
+        timestamp: i64,
+    ) -> Result<DateTime<Tz>, PrecisionError> {
+        let (min, max) = self.timestamp_range();
+        if timestamp < min || timestamp > max {
+            return Err(PrecisionError::OutOfRange(timestamp, self, min, max));
+        }
+
+        let n = self.units_per_second();
+        let secs = timestamp.div_euclid(n);
+        let nanos = timestamp.rem_euclid(n) * (1_000_000_000 / n);
+        let naive = NaiveDateTime::from_timestamp_opt(secs, nanos as u32)
+            .ok_or(PrecisionError::OutOfRange(timestamp, self, min, max))?;
+        Ok(tz.from_utc_datetime(&naive))
+    }
+
+    /// Parses a fractional timestamp like `1560762129.5`. The fraction is rounded to nanoseconds.
+    pub fn parse_fractional_timestamp<Tz: TimeZone>(
+        self,
+        tz: Tz,
+        timestamp: f64,
+    ) -> Result<DateTime<Tz>, PrecisionError> {
+        let whole = timestamp.floor();
+        let dt = self.parse_timestamp(tz, whole as i64)?;
+        let nanos_per_unit = self.tick_duration().num_nanoseconds().unwrap_or(0) as f64;
+        let nanos = ((timestamp - whole) * nanos_per_unit).round() as i64;
+        dt.checked_add_signed(Duration::nanoseconds(nanos))
+            .ok_or_else(|| PrecisionError::Unrepresentable(timestamp.to_string(), self))
+    }
+
+    pub fn to_timestamp<Tz: TimeZone>(self, dt: DateTime<Tz>) -> Result<i64, PrecisionError> {
+        let n = self.units_per_second();
+        let subsec = i64::from(dt.timestamp_subsec_nanos()) / (1_000_000_000 / n);
+        let timestamp = i128::from(dt.timestamp()) * i128::from(n) + i128::from(subsec);
+        i64::try_from(timestamp)
+            .map_err(|_| PrecisionError::Unrepresentable(format!("{:?}", dt), self))
+    }
+
+    /// Guesses the precision from the number of digits of the timestamp.
+    /// Timestamps with 12 or more digits are regarded as milliseconds.
+    pub fn detect(timestamp: i64) -> Precision {
+        const MIN_MILLISECOND_TIMESTAMP: i64 = 100_000_000_000;
+
+        if timestamp.checked_abs().unwrap_or(i64::MAX) >= MIN_MILLISECOND_TIMESTAMP {
+            Precision::MilliSecond
+        } else {
+            Precision::Second
         }
     }
 
@@ -61,6 +161,8 @@ impl Precision {
         match self {
             Precision::Second => "%Y-%m-%d %H:%M:%S (%Z)",
             Precision::MilliSecond => "%Y-%m-%d %H:%M:%S%.3f (%Z)",
+            Precision::MicroSecond => "%Y-%m-%d %H:%M:%S%.6f (%Z)",
+            Precision::NanoSecond => "%Y-%m-%d %H:%M:%S%.9f (%Z)",
         }
     }
 }
@@ -82,11 +184,55 @@ impl FindByName for Precision {
 #[cfg(test)]
 mod tests {
     use chrono::offset::TimeZone;
-    use chrono::Utc;
+    use chrono::{Duration, Utc};
 
-    use crate::find::{FindByName, FindError};
+    use crate::find::{FindByName, FindError, PossibleNames};
     use crate::precision::{Precision, PrecisionError};
 
+    #[test]
+    fn end_of_day() {
+        let end = |p: Precision| p.end_of_day().format("%H:%M:%S%.9f").to_string();
+        assert_eq!(end(Precision::Second), "23:59:59.000000000");
+        assert_eq!(end(Precision::MilliSecond), "23:59:59.999000000");
+        assert_eq!(end(Precision::MicroSecond), "23:59:59.999999000");
+        assert_eq!(end(Precision::NanoSecond), "23:59:59.999999999");
+    }
+
+    #[test]
+    fn trim() {
+        let dt = Utc.timestamp_opt(1_560_762_129, 123_456_789).unwrap();
+        let trim = |p: Precision| p.trim(dt).format("%T%.9f").to_string();
+        assert_eq!(trim(Precision::Second), "09:02:09.000000000");
+        assert_eq!(trim(Precision::MilliSecond), "09:02:09.123000000");
+        assert_eq!(trim(Precision::MicroSecond), "09:02:09.123456000");
+        assert_eq!(trim(Precision::NanoSecond), "09:02:09.123456789");
+    }
+
+    #[test]
+    fn tick_duration() {
+        assert_eq!(Precision::Second.tick_duration(), Duration::seconds(1));
+        assert_eq!(
+            Precision::MilliSecond.tick_duration(),
+            Duration::milliseconds(1)
+        );
+        assert_eq!(
+            Precision::MicroSecond.tick_duration(),
+            Duration::microseconds(1)
+        );
+        assert_eq!(
+            Precision::NanoSecond.tick_duration(),
+            Duration::nanoseconds(1)
+        );
+    }
+
+    #[test]
+    fn subsec_digits() {
+        assert_eq!(Precision::Second.subsec_digits(), 0);
+        assert_eq!(Precision::MilliSecond.subsec_digits(), 3);
+        assert_eq!(Precision::MicroSecond.subsec_digits(), 6);
+        assert_eq!(Precision::NanoSecond.subsec_digits(), 9);
+    }
+
     #[test]
     fn find_by_name_second() {
         assert_eq!(Precision::find_by_name("second"), Ok(Precision::Second));
@@ -100,31 +246,72 @@ mod tests {
             Ok(Precision::MilliSecond)
         );
         assert_eq!(Precision::find_by_name("m"), Ok(Precision::MilliSecond));
+        assert_eq!(Precision::find_by_name("M"), Ok(Precision::MilliSecond));
+        assert_eq!(Precision::find_by_name("mil"), Ok(Precision::MilliSecond));
         assert_eq!(Precision::find_by_name("ms"), Ok(Precision::MilliSecond));
+        assert_eq!(Precision::find_by_name("mic"), Ok(Precision::MicroSecond));
+        assert_eq!(Precision::possible_names()[1], "millisecond");
+    }
+
+    #[test]
+    fn find_by_name_exact_ignore_case() {
+        assert_eq!(Precision::find_by_name("MS"), Ok(Precision::MilliSecond));
+        assert_eq!(Precision::find_by_name("SECOND"), Ok(Precision::Second));
+        assert_eq!(
+            Precision::find_by_name("MilliSecond"),
+            Ok(Precision::MilliSecond)
+        );
     }
 
     #[test]
     fn find_by_name_not_supported() {
         assert_eq!(
             Precision::find_by_name("year"),
-            Err(PrecisionError::WrongName(FindError::NotFound))
+            Err(PrecisionError::WrongName(FindError::NotFound(
+                "year".to_string()
+            )))
         );
         assert_eq!(
             Precision::find_by_name("min"),
-            Err(PrecisionError::WrongName(FindError::NotFound))
+            Err(PrecisionError::WrongName(FindError::NotFound(
+                "min".to_string()
+            )))
         );
     }
 
+    #[test]
+    fn find_by_name_suggestion() {
+        assert_eq!(
+            Precision::find_by_name("secnod"),
+            Err(PrecisionError::WrongName(FindError::Suggestion(
+                "secnod".to_string(),
+                vec!["second".to_string()]
+            )))
+        );
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Precision::detect(0), Precision::Second);
+        assert_eq!(Precision::detect(1560762129), Precision::Second);
+        assert_eq!(Precision::detect(-1560762129), Precision::Second);
+        assert_eq!(Precision::detect(99_999_999_999), Precision::Second);
+        assert_eq!(Precision::detect(100_000_000_000), Precision::MilliSecond);
+        assert_eq!(Precision::detect(1560762129123), Precision::MilliSecond);
+        assert_eq!(Precision::detect(-1560762129123), Precision::MilliSecond);
+        assert_eq!(Precision::detect(i64::MIN), Precision::MilliSecond);
+    }
+
     #[test]
     fn parse_timestamp_second() {
         assert_eq!(
             Precision::Second.parse_timestamp(Utc, 0),
-            Utc.ymd(1970, 1, 1).and_hms(0, 0, 0)
+            Ok(Utc.ymd(1970, 1, 1).and_hms(0, 0, 0))
         );
 
         assert_eq!(
             Precision::Second.parse_timestamp(Utc, 1560762129123),
-            Utc.ymd(51428, 8, 1).and_hms(11, 52, 3)
+            Ok(Utc.ymd(51428, 8, 1).and_hms(11, 52, 3))
         );
     }
 
@@ -132,12 +319,108 @@ mod tests {
     fn parse_timestamp_millisecond() {
         assert_eq!(
             Precision::MilliSecond.parse_timestamp(Utc, 0),
-            Utc.ymd(1970, 1, 1).and_hms_milli(0, 0, 0, 0)
+            Ok(Utc.ymd(1970, 1, 1).and_hms_milli(0, 0, 0, 0))
         );
 
         assert_eq!(
             Precision::MilliSecond.parse_timestamp(Utc, 1560762129123),
-            Utc.ymd(2019, 6, 17).and_hms_milli(9, 2, 9, 123)
+            Ok(Utc.ymd(2019, 6, 17).and_hms_milli(9, 2, 9, 123))
+        );
+    }
+
+    #[test]
+    fn parse_timestamp_microsecond() {
+        let p = Precision::MicroSecond;
+        assert_eq!(
+            p.parse_timestamp(Utc, 1560762129123456),
+            Ok(Utc.ymd(2019, 6, 17).and_hms_micro(9, 2, 9, 123_456))
+        );
+        assert_eq!(
+            p.parse_timestamp(Utc, -1),
+            Ok(Utc.ymd(1969, 12, 31).and_hms_micro(23, 59, 59, 999_999))
+        );
+
+        let (min, max) = (-8_334_632_851_200_000_000, 8_210_298_412_799_999_999);
+        assert_eq!(p.timestamp_range(), (min, max));
+        assert_eq!(
+            p.parse_timestamp(Utc, max),
+            Ok(Utc.ymd(262143, 12, 31).and_hms_micro(23, 59, 59, 999_999))
+        );
+        assert_eq!(
+            p.parse_timestamp(Utc, min),
+            Ok(Utc.ymd(-262144, 1, 1).and_hms(0, 0, 0))
+        );
+        assert_eq!(
+            p.parse_timestamp(Utc, max + 1),
+            Err(PrecisionError::OutOfRange(max + 1, p, min, max))
+        );
+        assert_eq!(
+            p.parse_timestamp(Utc, min - 1),
+            Err(PrecisionError::OutOfRange(min - 1, p, min, max))
         );
+        assert_eq!(
+            p.parse_timestamp(Utc, i64::MAX)
+                .unwrap_err()
+                .to_string(),
+            "Timestamp out of range: 9223372036854775807. timestamps in microsecond must be between -8334632851200000000 and 8210298412799999999."
+        );
+    }
+
+    #[test]
+    fn parse_timestamp_nanosecond() {
+        let p = Precision::NanoSecond;
+        assert_eq!(p.timestamp_range(), (i64::MIN, i64::MAX));
+        assert_eq!(
+            p.parse_timestamp(Utc, i64::MAX),
+            Ok(Utc.ymd(2262, 4, 11).and_hms_nano(23, 47, 16, 854_775_807))
+        );
+        assert_eq!(
+            p.parse_timestamp(Utc, i64::MIN),
+            Ok(Utc.ymd(1677, 9, 21).and_hms_nano(0, 12, 43, 145_224_192))
+        );
+
+        let dt = Utc.ymd(2262, 4, 11).and_hms_nano(23, 47, 16, 854_775_807);
+        assert_eq!(p.to_timestamp(dt), Ok(i64::MAX));
+        let dt = Utc.ymd(2262, 4, 11).and_hms_nano(23, 47, 16, 854_775_808);
+        assert!(p.to_timestamp(dt).is_err());
+        let dt = Utc.ymd(1677, 9, 21).and_hms_nano(0, 12, 43, 145_224_192);
+        assert_eq!(p.to_timestamp(dt), Ok(i64::MIN));
+        let dt = Utc.ymd(1677, 9, 21).and_hms_nano(0, 12, 43, 145_224_191);
+        assert!(p.to_timestamp(dt).is_err());
+    }
+
+    #[test]
+    fn parse_fractional_timestamp() {
+        assert_eq!(
+            Precision::Second.parse_fractional_timestamp(Utc, 1560762129.5),
+            Ok(Utc.ymd(2019, 6, 17).and_hms_milli(9, 2, 9, 500))
+        );
+        assert_eq!(
+            Precision::Second.parse_fractional_timestamp(Utc, -0.25),
+            Ok(Utc.ymd(1969, 12, 31).and_hms_milli(23, 59, 59, 750))
+        );
+        assert_eq!(
+            Precision::MilliSecond.parse_fractional_timestamp(Utc, 1560762129123.5),
+            Ok(Utc.ymd(2019, 6, 17).and_hms_micro(9, 2, 9, 123_500))
+        );
+        assert_eq!(
+            Precision::Second.parse_fractional_timestamp(Utc, 1560762129.0),
+            Precision::Second.parse_timestamp(Utc, 1560762129)
+        );
+        assert!(Precision::Second
+            .parse_fractional_timestamp(Utc, 1e30)
+            .is_err());
+    }
+
+    #[test]
+    fn parse_timestamp_out_of_range() {
+        let (min, max) = Precision::Second.timestamp_range();
+        assert!(Precision::Second.parse_timestamp(Utc, max).is_ok());
+        assert!(Precision::Second.parse_timestamp(Utc, max + 1).is_err());
+        assert!(Precision::Second.parse_timestamp(Utc, min).is_ok());
+        assert!(Precision::Second.parse_timestamp(Utc, min - 1).is_err());
+        assert!(Precision::MilliSecond
+            .parse_timestamp(Utc, i64::MIN)
+            .is_err());
     }
 }
@@ -6,8 +6,9 @@ use strum_macros::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
 use crate::find::{FindByName, FindError, PossibleNames, PossibleValues};
-use crate::provider::DateTimeProvider;
-use crate::validate::IntoValidationError;
+use crate::provider::{DateTimeProvider, ProviderError};
+use crate::validate::{IntoValidationError, ValidationMessage};
+use crate::week::WeekStart;
 
 #[derive(Error, Debug, PartialEq)]
 pub enum PresetError {
@@ -24,14 +25,11 @@ impl From<FindError> for PresetError {
 impl IntoValidationError for PresetError {
     fn into_validation_error(self) -> String {
         use PresetError::*;
-        match &self {
-            WrongName(e) => match e {
-                FindError::NotFound => {
-                    let names = Preset::possible_names();
-                    format!("{} possible names: [{}]", self, names.join(", "))
-                }
-                _ => format!("{}", self),
-            },
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("preset", &e, Preset::possible_names())
+                    .to_string()
+            }
         }
     }
 }
@@ -46,18 +44,30 @@ pub enum Preset {
 
     #[strum(serialize = "yesterday")]
     Yesterday,
+
+    #[strum(serialize = "start-of-week")]
+    StartOfWeek,
+
+    #[strum(serialize = "end-of-week")]
+    EndOfWeek,
 }
 
 impl Preset {
-    pub fn as_date<P, Tz>(self, provider: &P) -> Date<Tz>
+    pub fn as_date<P, Tz>(
+        self,
+        provider: &P,
+        week_start: WeekStart,
+    ) -> Result<Date<Tz>, ProviderError>
     where
         P: DateTimeProvider<Tz>,
         Tz: TimeZone + Debug,
     {
         match self {
-            Preset::Today => provider.today(),
+            Preset::Today => Ok(provider.today()),
             Preset::Tomorrow => provider.tomorrow(),
             Preset::Yesterday => provider.yesterday(),
+            Preset::StartOfWeek => provider.start_of_week(week_start),
+            Preset::EndOfWeek => provider.end_of_week(week_start),
         }
     }
 }
@@ -75,3 +85,33 @@ impl PossibleNames for Preset {}
 impl FindByName for Preset {
     type Error = PresetError;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::find::{FindByName, FindError};
+    use crate::preset::{Preset, PresetError};
+
+    #[test]
+    fn find_by_name() {
+        assert_eq!(Preset::find_by_name("today"), Ok(Preset::Today));
+        assert_eq!(Preset::find_by_name("tom"), Ok(Preset::Tomorrow));
+        assert_eq!(Preset::find_by_name("y"), Ok(Preset::Yesterday));
+        assert_eq!(Preset::find_by_name("s"), Ok(Preset::StartOfWeek));
+        assert_eq!(Preset::find_by_name("end"), Ok(Preset::EndOfWeek));
+
+        assert_eq!(
+            Preset::find_by_name("to"),
+            Err(PresetError::WrongName(FindError::Ambiguous(
+                "to".to_string(),
+                vec!["today".to_string(), "tomorrow".to_string()]
+            )))
+        );
+    }
+
+    #[test]
+    fn find_by_name_exact_ignore_case() {
+        assert_eq!(Preset::find_by_name("TODAY"), Ok(Preset::Today));
+        assert_eq!(Preset::find_by_name("Tomorrow"), Ok(Preset::Tomorrow));
+        assert_eq!(Preset::find_by_name("YESTERDAY"), Ok(Preset::Yesterday));
+    }
+}
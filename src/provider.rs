@@ -1,17 +1,74 @@
 pub use std::fmt::Debug;
 
-use chrono::{Date, DateTime, TimeZone};
+use chrono::{Date, DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Weekday};
+use thiserror::Error;
 
-use crate::timedelta::{ApplyDateTime, TimeDeltaBuilder};
+use crate::datetime::resolve_local_datetime;
+use crate::precision::Precision;
+use crate::week::WeekStart;
 
 mod fixed;
+mod fixed_now;
 mod local;
 mod utc;
 
 pub use fixed::FixedOffsetProvider;
+pub use fixed_now::FixedNowProvider;
 pub use local::LocalProvider;
 pub use utc::UtcProvider;
 
+#[derive(Error, Debug, PartialEq)]
+pub enum ProviderError {
+    #[error("Can't get {0} of {1}. the date is out of range.")]
+    OutOfRange(&'static str, String),
+
+    #[error("Can't get {0} of {1}. no time of the day exists in the timezone.")]
+    Nonexistent(&'static str, String),
+}
+
+/// Moves the date by the days on the calendar, or fails with the operation and the base date.
+pub fn add_days<Tz: TimeZone>(
+    operation: &'static str,
+    date: Date<Tz>,
+    days: i64,
+) -> Result<Date<Tz>, ProviderError> {
+    let base = date.naive_local();
+    date.checked_add_signed(Duration::days(days))
+        .ok_or_else(|| ProviderError::OutOfRange(operation, base.to_string()))
+}
+
+/// Returns the first instant of the date.
+/// It is the earlier one if midnight is repeated, and the earliest valid one after midnight
+/// if midnight is skipped, like on days when DST starts at midnight.
+pub fn start_of_day<Tz: TimeZone>(
+    operation: &'static str,
+    tz: &Tz,
+    date: NaiveDate,
+) -> Result<DateTime<Tz>, ProviderError> {
+    let start = resolve_local_datetime(tz, date.and_time(NaiveTime::MIN));
+    // the first valid time after midnight is on another date if the whole day is skipped.
+    if start.naive_local().date() == date {
+        Ok(start)
+    } else {
+        Err(ProviderError::Nonexistent(operation, date.to_string()))
+    }
+}
+
+/// Returns the last instant before the start of the next period, in the precision.
+fn end_of_period<Tz: TimeZone>(
+    operation: &'static str,
+    tz: &Tz,
+    base: NaiveDate,
+    next: Option<NaiveDate>,
+    precision: Precision,
+) -> Result<DateTime<Tz>, ProviderError> {
+    let next = next.ok_or_else(|| ProviderError::OutOfRange(operation, base.to_string()))?;
+    start_of_day(operation, tz, next)
+        .map_err(|_| ProviderError::Nonexistent(operation, base.to_string()))?
+        .checked_sub_signed(precision.tick_duration())
+        .ok_or_else(|| ProviderError::OutOfRange(operation, base.to_string()))
+}
+
 pub trait DateTimeProvider<Tz: TimeZone + Debug> {
     fn timezone(&self) -> Tz;
 
@@ -21,12 +78,52 @@ pub trait DateTimeProvider<Tz: TimeZone + Debug> {
         self.now().date()
     }
 
-    fn tomorrow(&self) -> Date<Tz> {
-        add_days(self.today(), 1)
+    // days are moved on the calendar, since a day is not always 24 hours long around DST transitions.
+    fn tomorrow(&self) -> Result<Date<Tz>, ProviderError> {
+        add_days("tomorrow", self.today(), 1)
+    }
+
+    fn yesterday(&self) -> Result<Date<Tz>, ProviderError> {
+        add_days("yesterday", self.today(), -1)
+    }
+
+    fn weekday(&self) -> Weekday {
+        self.today().weekday()
     }
 
-    fn yesterday(&self) -> Date<Tz> {
-        add_days(self.today(), -1)
+    fn start_of_week(&self, week_start: WeekStart) -> Result<Date<Tz>, ProviderError> {
+        let days = week_start.days_from(self.weekday());
+        add_days("start of week", self.today(), -i64::from(days))
+    }
+
+    /// Returns the last date of the week.
+    fn end_of_week(&self, week_start: WeekStart) -> Result<Date<Tz>, ProviderError> {
+        add_days("end of week", self.start_of_week(week_start)?, 6)
+    }
+
+    /// Returns the last instant of today, like `23:59:59.999` for millisecond.
+    fn end_of_day(&self, precision: Precision) -> Result<DateTime<Tz>, ProviderError> {
+        let today = self.today().naive_local();
+        let next = today.succ_opt();
+        end_of_period("end of day", &self.timezone(), today, next, precision)
+    }
+
+    /// Returns the last instant of the month.
+    fn end_of_month(&self, precision: Precision) -> Result<DateTime<Tz>, ProviderError> {
+        let today = self.today().naive_local();
+        let next = if today.month() == 12 {
+            NaiveDate::from_ymd_opt(today.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(today.year(), today.month() + 1, 1)
+        };
+        end_of_period("end of month", &self.timezone(), today, next, precision)
+    }
+
+    /// Returns the last instant of the year.
+    fn end_of_year(&self, precision: Precision) -> Result<DateTime<Tz>, ProviderError> {
+        let today = self.today().naive_local();
+        let next = NaiveDate::from_ymd_opt(today.year() + 1, 1, 1);
+        end_of_period("end of year", &self.timezone(), today, next, precision)
     }
 }
 
@@ -36,10 +133,230 @@ pub trait FromTimeZone<Tz: TimeZone + Debug> {
         Self: DateTimeProvider<Tz>;
 }
 
-fn add_days<Tz: TimeZone>(date: Date<Tz>, days: i32) -> Date<Tz> {
-    let delta = TimeDeltaBuilder::default().days(days).build();
-    delta
-        .apply_datetime(date.and_hms(0, 0, 0))
-        .unwrap_or_else(|| panic!("can't add days. date={:?}, days={}", date, days))
-        .date()
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+
+    use crate::datetime::test_zones::{RepeatedMidnight, SkippedDay, SkippedMidnight};
+    use crate::precision::Precision;
+    use crate::provider::{start_of_day, DateTimeProvider, FixedNowProvider, ProviderError};
+    use crate::week::WeekStart;
+
+    fn provider(day: u32) -> FixedNowProvider<Utc> {
+        FixedNowProvider::new(Utc.ymd(2019, 6, day).and_hms(11, 22, 33))
+    }
+
+    #[test]
+    fn week_from_monday() {
+        // 2019-06-17 is a monday.
+        let week = |day| {
+            let p = provider(day);
+            (
+                p.start_of_week(WeekStart::Monday).unwrap(),
+                p.end_of_week(WeekStart::Monday).unwrap(),
+            )
+        };
+        assert_eq!(provider(15).weekday(), Weekday::Sat);
+        assert_eq!(week(15), (Utc.ymd(2019, 6, 10), Utc.ymd(2019, 6, 16)));
+        assert_eq!(provider(16).weekday(), Weekday::Sun);
+        assert_eq!(week(16), (Utc.ymd(2019, 6, 10), Utc.ymd(2019, 6, 16)));
+        assert_eq!(provider(17).weekday(), Weekday::Mon);
+        assert_eq!(week(17), (Utc.ymd(2019, 6, 17), Utc.ymd(2019, 6, 23)));
+    }
+
+    #[test]
+    fn week_from_sunday() {
+        let week = |day| {
+            let p = provider(day);
+            (
+                p.start_of_week(WeekStart::Sunday).unwrap(),
+                p.end_of_week(WeekStart::Sunday).unwrap(),
+            )
+        };
+        assert_eq!(week(15), (Utc.ymd(2019, 6, 9), Utc.ymd(2019, 6, 15)));
+        assert_eq!(week(16), (Utc.ymd(2019, 6, 16), Utc.ymd(2019, 6, 22)));
+        assert_eq!(week(17), (Utc.ymd(2019, 6, 16), Utc.ymd(2019, 6, 22)));
+    }
+
+    fn provider_at(date: NaiveDate) -> FixedNowProvider<Utc> {
+        FixedNowProvider::new(Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN)))
+    }
+
+    #[test]
+    fn out_of_range() {
+        let max = provider_at(NaiveDate::MAX);
+        assert_eq!(
+            max.tomorrow(),
+            Err(ProviderError::OutOfRange(
+                "tomorrow",
+                NaiveDate::MAX.to_string()
+            ))
+        );
+        assert_eq!(
+            max.yesterday().map(|d| d.naive_local()),
+            Ok(NaiveDate::MAX.pred_opt().unwrap())
+        );
+
+        let min = provider_at(NaiveDate::MIN);
+        assert_eq!(
+            min.yesterday(),
+            Err(ProviderError::OutOfRange(
+                "yesterday",
+                NaiveDate::MIN.to_string()
+            ))
+        );
+        assert_eq!(
+            min.tomorrow().map(|d| d.naive_local()),
+            Ok(NaiveDate::MIN.succ_opt().unwrap())
+        );
+    }
+
+    #[test]
+    fn week_out_of_range() {
+        // the week runs past the last representable date, but starts before it.
+        let max = provider_at(NaiveDate::MAX);
+        assert!(max.start_of_week(WeekStart::Monday).is_ok());
+        assert!(matches!(
+            max.end_of_week(WeekStart::Monday),
+            Err(ProviderError::OutOfRange("end of week", _))
+        ));
+
+        // the week starts before the first representable date.
+        let min = provider_at(NaiveDate::MIN);
+        assert_eq!(min.weekday(), Weekday::Tue);
+        assert!(matches!(
+            min.start_of_week(WeekStart::Monday),
+            Err(ProviderError::OutOfRange("start of week", _))
+        ));
+    }
+
+    fn end_of(
+        f: fn(&FixedNowProvider<Utc>, Precision) -> Result<DateTime<Utc>, ProviderError>,
+        ymd: (i32, u32, u32),
+        precision: Precision,
+    ) -> String {
+        let date = NaiveDate::from_ymd_opt(ymd.0, ymd.1, ymd.2).unwrap();
+        let dt = f(&provider_at(date), precision).unwrap();
+        dt.format("%Y-%m-%d %H:%M:%S%.f").to_string()
+    }
+
+    #[test]
+    fn end_of_day() {
+        let end_of_day = |precision| end_of(DateTimeProvider::end_of_day, (2019, 6, 17), precision);
+        assert_eq!(end_of_day(Precision::Second), "2019-06-17 23:59:59");
+        assert_eq!(
+            end_of_day(Precision::MilliSecond),
+            "2019-06-17 23:59:59.999"
+        );
+        assert_eq!(
+            end_of_day(Precision::MicroSecond),
+            "2019-06-17 23:59:59.999999"
+        );
+        assert_eq!(
+            end_of_day(Precision::NanoSecond),
+            "2019-06-17 23:59:59.999999999"
+        );
+
+        // the next day is out of range.
+        assert!(matches!(
+            provider_at(NaiveDate::MAX).end_of_day(Precision::Second),
+            Err(ProviderError::OutOfRange("end of day", _))
+        ));
+    }
+
+    #[test]
+    fn end_of_month() {
+        let end_of_month = |ymd, precision| end_of(DateTimeProvider::end_of_month, ymd, precision);
+        assert_eq!(
+            end_of_month((2019, 6, 17), Precision::Second),
+            "2019-06-30 23:59:59"
+        );
+        assert_eq!(
+            end_of_month((2019, 12, 1), Precision::MilliSecond),
+            "2019-12-31 23:59:59.999"
+        );
+        assert_eq!(
+            end_of_month((2020, 2, 10), Precision::MicroSecond),
+            "2020-02-29 23:59:59.999999"
+        );
+        assert_eq!(
+            end_of_month((2019, 2, 28), Precision::NanoSecond),
+            "2019-02-28 23:59:59.999999999"
+        );
+    }
+
+    #[test]
+    fn end_of_year() {
+        let end_of_year = |ymd, precision| end_of(DateTimeProvider::end_of_year, ymd, precision);
+        assert_eq!(
+            end_of_year((2019, 12, 31), Precision::Second),
+            "2019-12-31 23:59:59"
+        );
+        assert_eq!(
+            end_of_year((2020, 1, 1), Precision::MilliSecond),
+            "2020-12-31 23:59:59.999"
+        );
+        assert_eq!(
+            end_of_year((2020, 2, 29), Precision::MicroSecond),
+            "2020-12-31 23:59:59.999999"
+        );
+        assert_eq!(
+            end_of_year((2020, 6, 17), Precision::NanoSecond),
+            "2020-12-31 23:59:59.999999999"
+        );
+
+        assert!(matches!(
+            provider_at(NaiveDate::MAX).end_of_year(Precision::Second),
+            Err(ProviderError::OutOfRange("end of year", _))
+        ));
+    }
+
+    fn start<Tz: TimeZone<Offset = FixedOffset>>(
+        tz: Tz,
+        date: &str,
+    ) -> Result<String, ProviderError> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        start_of_day("start of day", &tz, date).map(|dt| dt.to_rfc3339())
+    }
+
+    #[test]
+    fn start_of_day_in_gap() {
+        // like America/Sao_Paulo, DST started at the midnight of 2018-11-04.
+        let tz = SkippedMidnight::new();
+        assert_eq!(
+            start(tz, "2018-11-04"),
+            Ok("2018-11-04T01:00:00-02:00".to_string())
+        );
+        assert_eq!(
+            start(tz, "2018-11-03"),
+            Ok("2018-11-03T00:00:00-03:00".to_string())
+        );
+    }
+
+    #[test]
+    fn start_of_day_repeated() {
+        // DST ends at 01:00, so the hour from midnight is repeated.
+        let tz = RepeatedMidnight::new();
+        assert_eq!(
+            start(tz, "2019-04-07"),
+            Ok("2019-04-07T00:00:00-02:00".to_string())
+        );
+    }
+
+    #[test]
+    fn start_of_day_skipped() {
+        // like Pacific/Apia, which skipped 2011-12-30.
+        let tz = SkippedDay::new();
+        assert_eq!(
+            start(tz, "2011-12-30"),
+            Err(ProviderError::Nonexistent(
+                "start of day",
+                "2011-12-30".to_string()
+            ))
+        );
+        assert_eq!(
+            start(tz, "2011-12-31"),
+            Ok("2011-12-31T00:00:00+14:00".to_string())
+        );
+    }
 }
@@ -0,0 +1,68 @@
+use chrono::offset::TimeZone;
+use chrono::DateTime;
+
+use crate::provider::{DateTimeProvider, Debug};
+
+/// A provider whose current time is frozen, like `SOURCE_DATE_EPOCH` or `--at`.
+pub struct FixedNowProvider<Tz: TimeZone> {
+    now: DateTime<Tz>,
+}
+
+impl<Tz: TimeZone> FixedNowProvider<Tz> {
+    pub fn new(now: DateTime<Tz>) -> Self {
+        FixedNowProvider { now }
+    }
+
+    pub fn from_timestamp(tz: Tz, seconds: i64) -> Option<Self> {
+        tz.timestamp_opt(seconds, 0).single().map(Self::new)
+    }
+}
+
+impl<Tz: TimeZone + Debug> FixedNowProvider<Tz> {
+    /// Freezes the current time of the provider to the instant, keeping its timezone.
+    pub fn wrap<P, Tz2>(provider: &P, instant: &DateTime<Tz2>) -> Self
+    where
+        P: DateTimeProvider<Tz>,
+        Tz2: TimeZone,
+    {
+        Self::new(instant.with_timezone(&provider.timezone()))
+    }
+}
+
+impl<Tz: TimeZone + Debug> DateTimeProvider<Tz> for FixedNowProvider<Tz> {
+    fn timezone(&self) -> Tz {
+        self.now.timezone()
+    }
+
+    fn now(&self) -> DateTime<Tz> {
+        self.now.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone, Utc};
+
+    use crate::provider::{DateTimeProvider, FixedNowProvider, FixedOffsetProvider, FromTimeZone};
+
+    #[test]
+    fn wrap() {
+        let jst = FixedOffset::east(9 * 3600);
+        let provider: FixedOffsetProvider = FixedOffsetProvider::from_timezone(jst);
+        let instant = Utc.ymd(2019, 6, 17).and_hms(20, 0, 0);
+        let provider = FixedNowProvider::wrap(&provider, &instant);
+
+        assert_eq!(provider.timezone(), jst);
+        assert_eq!(provider.now(), instant);
+        assert_eq!(provider.today(), jst.ymd(2019, 6, 18));
+        assert_eq!(provider.yesterday().unwrap(), jst.ymd(2019, 6, 17));
+        assert_eq!(provider.tomorrow().unwrap(), jst.ymd(2019, 6, 19));
+    }
+
+    #[test]
+    fn from_timestamp() {
+        let provider = FixedNowProvider::from_timestamp(Utc, 86400).unwrap();
+        assert_eq!(provider.now(), Utc.ymd(1970, 1, 2).and_hms(0, 0, 0));
+        assert!(FixedNowProvider::from_timestamp(Utc, i64::MAX).is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use ut_cli::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RadixError {
+    #[error("Wrong radix. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for RadixError {
+    fn from(e: FindError) -> Self {
+        RadixError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for RadixError {
+    fn into_validation_error(self) -> String {
+        use RadixError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("radix", &e, Radix::possible_names()).to_string()
+            }
+        }
+    }
+}
+
+/// The base to print timestamps in.
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum Radix {
+    #[strum(serialize = "dec")]
+    Decimal,
+
+    #[strum(serialize = "hex")]
+    Hexadecimal,
+}
+
+impl Radix {
+    /// Formats the timestamp, like `0x5D0775D1` in hexadecimal.
+    pub fn format(self, timestamp: i64) -> String {
+        match self {
+            Radix::Decimal => timestamp.to_string(),
+            Radix::Hexadecimal if timestamp < 0 => format!("-0x{:X}", timestamp.unsigned_abs()),
+            Radix::Hexadecimal => format!("0x{:X}", timestamp),
+        }
+    }
+}
+
+impl PossibleValues for Radix {
+    type Iterator = RadixIter;
+
+    fn possible_values() -> Self::Iterator {
+        Radix::iter()
+    }
+}
+
+impl PossibleNames for Radix {}
+
+impl FindByName for Radix {
+    type Error = RadixError;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Radix;
+    use ut_cli::find::FindByName;
+    use ut_cli::validate::parse_timestamp;
+
+    #[test]
+    fn find_by_name() {
+        assert_eq!(Radix::find_by_name("dec"), Ok(Radix::Decimal));
+        assert_eq!(Radix::find_by_name("h"), Ok(Radix::Hexadecimal));
+        assert!(Radix::find_by_name("oct").is_err());
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(Radix::Decimal.format(1560770001), "1560770001");
+        assert_eq!(Radix::Hexadecimal.format(1560770001), "0x5D0775D1");
+        assert_eq!(Radix::Hexadecimal.format(0), "0x0");
+        assert_eq!(Radix::Hexadecimal.format(-255), "-0xFF");
+        assert_eq!(Radix::Hexadecimal.format(i64::MIN), "-0x8000000000000000");
+    }
+
+    #[test]
+    fn round_trip() {
+        for timestamp in &[0, 1560770001, -86400, i64::MAX, i64::MIN] {
+            for radix in &[Radix::Decimal, Radix::Hexadecimal] {
+                let text = radix.format(*timestamp);
+                assert_eq!(parse_timestamp(&text, true), Ok(*timestamp), "{}", text);
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+/// A small pseudo random number generator, SplitMix64.
+/// The same seed generates the same numbers on any platform, so outputs can be reproduced.
+/// It is not for cryptography.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number between `min` and `max` inclusive, each of which is equally likely.
+    /// Panics if `min` is greater than `max`.
+    pub fn between(&mut self, min: i64, max: i64) -> i64 {
+        assert!(min <= max, "min must not be greater than max");
+        let span = (i128::from(max) - i128::from(min) + 1) as u128;
+        if span > u128::from(u64::MAX) {
+            return self.next_u64() as i64;
+        }
+        // rejects the numbers of the last partial span, which would be more likely than the others.
+        let span = span as u64;
+        let limit = u64::MAX - u64::MAX % span;
+        loop {
+            let n = self.next_u64();
+            if n < limit {
+                return (i128::from(min) + i128::from(n % span)) as i64;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::random::SplitMix64;
+
+    #[test]
+    fn next_u64() {
+        // the reference values of SplitMix64 with seed 0.
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+
+        let (mut a, mut b) = (SplitMix64::new(42), SplitMix64::new(42));
+        assert_eq!(a.next_u64(), b.next_u64());
+        assert_ne!(a.next_u64(), SplitMix64::new(43).next_u64());
+    }
+
+    #[test]
+    fn between() {
+        let mut rng = SplitMix64::new(1);
+        let values: Vec<i64> = (0..1000).map(|_| rng.between(-2, 2)).collect();
+        assert!(values.iter().all(|v| (-2..=2).contains(v)));
+        for v in -2..=2 {
+            assert!(values.contains(&v));
+        }
+
+        assert_eq!(rng.between(5, 5), 5);
+        // the full range of i64.
+        rng.between(i64::MIN, i64::MAX);
+        assert!(rng.between(i64::MAX - 1, i64::MAX) >= i64::MAX - 1);
+        assert!(rng.between(i64::MIN, i64::MIN + 1) <= i64::MIN + 1);
+    }
+}
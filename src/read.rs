@@ -1,6 +1,8 @@
-use std::io::{self, Read};
-use std::num::ParseIntError;
+use std::convert::Infallible;
+use std::io::{self, BufRead};
+use std::num::{ParseFloatError, ParseIntError};
 use std::str::FromStr;
+use std::string::FromUtf8Error;
 
 use thiserror::Error;
 
@@ -11,6 +13,22 @@ pub enum ReadError {
 
     #[error("Parse int error. error:{0}")]
     ParseInt(ParseIntError),
+
+    #[error("Parse float error. error:{0}")]
+    ParseFloat(ParseFloatError),
+
+    #[error("Empty input.")]
+    Empty,
+
+    #[error("UTF-8 error. error:{0}")]
+    Utf8(FromUtf8Error),
+
+    #[error("Wrong token #{index}: '{token}'. error:{source}")]
+    Token {
+        index: usize,
+        token: String,
+        source: Box<ReadError>,
+    },
 }
 
 impl From<io::Error> for ReadError {
@@ -19,38 +37,253 @@ impl From<io::Error> for ReadError {
     }
 }
 
+impl From<Infallible> for ReadError {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
+
 impl From<ParseIntError> for ReadError {
     fn from(e: ParseIntError) -> Self {
         ReadError::ParseInt(e)
     }
 }
 
-pub fn read_next<R, T, E>(src: R) -> Result<T, ReadError>
+impl From<ParseFloatError> for ReadError {
+    fn from(e: ParseFloatError) -> Self {
+        ReadError::ParseFloat(e)
+    }
+}
+
+impl From<FromUtf8Error> for ReadError {
+    fn from(e: FromUtf8Error) -> Self {
+        ReadError::Utf8(e)
+    }
+}
+
+/// A UTF-8 BOM, which some Windows tools put at the head of the output.
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// How tokens and records in the input are delimited.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Delimiter {
+    /// Tokens by ASCII whitespace, and records by newlines.
+    Whitespace,
+
+    /// Both tokens and records by NUL, like `xargs -0`.
+    Nul,
+}
+
+impl Delimiter {
+    fn is_token_delimiter(self, b: u8) -> bool {
+        match self {
+            Delimiter::Whitespace => b.is_ascii_whitespace(),
+            Delimiter::Nul => b == b'\0',
+        }
+    }
+
+    fn record_delimiter(self) -> u8 {
+        match self {
+            Delimiter::Whitespace => b'\n',
+            Delimiter::Nul => b'\0',
+        }
+    }
+
+    /// Returns the terminator of each output.
+    pub fn terminator(self) -> char {
+        self.record_delimiter() as char
+    }
+}
+
+/// An iterator over tokens separated by ASCII whitespace, including `\r` of CRLF, or by NUL.
+/// A BOM at the head of the input is skipped.
+/// It stops at the end of the input, or after the first error.
+pub struct Tokens<R> {
+    src: R,
+    delimiter: Delimiter,
+    first: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Tokens<R> {
+    fn read_token(&mut self) -> Result<Option<String>, ReadError> {
+        let delimiter = self.delimiter;
+        loop {
+            skip_while(&mut self.src, |b| delimiter.is_token_delimiter(b), None)?;
+
+            let mut token = Vec::new();
+            skip_while(
+                &mut self.src,
+                |b| !delimiter.is_token_delimiter(b),
+                Some(&mut token),
+            )?;
+            if std::mem::replace(&mut self.first, false) && token.starts_with(BOM) {
+                token.drain(..BOM.len());
+                if token.is_empty() {
+                    // the BOM was followed by whitespace.
+                    continue;
+                }
+            }
+
+            return if token.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(String::from_utf8(token)?))
+            };
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Tokens<R> {
+    type Item = Result<String, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let r = self.read_token().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+/// Returns an iterator over tokens separated by ASCII whitespace.
+pub fn read_tokens<R: BufRead>(src: R) -> Tokens<R> {
+    read_tokens_with(src, Delimiter::Whitespace)
+}
+
+/// Returns an iterator over tokens separated by the delimiter.
+pub fn read_tokens_with<R: BufRead>(src: R, delimiter: Delimiter) -> Tokens<R> {
+    Tokens {
+        src,
+        delimiter,
+        first: true,
+        done: false,
+    }
+}
+
+/// An iterator over records, like lines, without their delimiters.
+/// `\r` of CRLF, and a BOM at the head of the input are also removed.
+/// It stops at the end of the input, or after the first error.
+pub struct Records<R> {
+    src: R,
+    delimiter: Delimiter,
+    first: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Records<R> {
+    fn read_record(&mut self) -> Result<Option<String>, ReadError> {
+        let delimiter = self.delimiter.record_delimiter();
+        let mut record = Vec::new();
+        if self.src.read_until(delimiter, &mut record)? == 0 {
+            return Ok(None);
+        }
+
+        if record.last() == Some(&delimiter) {
+            record.pop();
+            if self.delimiter == Delimiter::Whitespace && record.last() == Some(&b'\r') {
+                record.pop();
+            }
+        }
+        if std::mem::replace(&mut self.first, false) && record.starts_with(BOM) {
+            record.drain(..BOM.len());
+        }
+        Ok(Some(String::from_utf8(record)?))
+    }
+}
+
+impl<R: BufRead> Iterator for Records<R> {
+    type Item = Result<String, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let r = self.read_record().transpose();
+        self.done = !matches!(r, Some(Ok(_)));
+        r
+    }
+}
+
+/// Returns an iterator over records, which are lines, or texts separated by NUL.
+pub fn read_records<R: BufRead>(src: R, delimiter: Delimiter) -> Records<R> {
+    Records {
+        src,
+        delimiter,
+        first: true,
+        done: false,
+    }
+}
+
+/// Returns an iterator which parses each token.
+/// Parse failures are reported with the zero-based index of the token.
+pub fn read_all<R, T, E>(src: R) -> impl Iterator<Item = Result<T, ReadError>>
 where
-    R: Read,
+    R: BufRead,
     T: FromStr<Err = E>,
     E: Into<ReadError>,
 {
-    let s: String = src
-        .bytes()
-        .map(|r| r.map(|b| b as char))
-        .skip_while(|r| {
-            r.as_ref()
-                .map(|c| c.is_whitespace())
-                .unwrap_or_else(|_| false)
+    read_tokens(src).enumerate().map(|(index, r)| {
+        r.and_then(|token| {
+            token.parse().map_err(|e: E| ReadError::Token {
+                index,
+                token,
+                source: Box::new(e.into()),
+            })
         })
-        .take_while(|r| {
-            r.as_ref()
-                .map(|c| !c.is_whitespace())
-                .unwrap_or_else(|_| true)
-        })
-        .collect::<Result<_, _>>()?;
+    })
+}
 
+/// Reads the next token separated by ASCII whitespace, and parses it.
+/// Returns `ReadError::Empty` if there are no more tokens.
+/// Pass `&mut` reader to read following tokens from the rest of the input.
+pub fn read_next<R, T, E>(src: R) -> Result<T, ReadError>
+where
+    R: BufRead,
+    T: FromStr<Err = E>,
+    E: Into<ReadError>,
+{
+    let s = read_tokens(src).next().ok_or(ReadError::Empty)??;
     s.parse().map_err(|e: E| e.into())
 }
 
+/// Consumes bytes while `pred` holds, and copies them into `dst` if given.
+fn skip_while<R, F>(src: &mut R, pred: F, mut dst: Option<&mut Vec<u8>>) -> io::Result<()>
+where
+    R: BufRead,
+    F: Fn(u8) -> bool,
+{
+    loop {
+        let (len, done) = {
+            let buf = match src.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if buf.is_empty() {
+                return Ok(());
+            }
+
+            let len = buf.iter().position(|&b| !pred(b)).unwrap_or(buf.len());
+            if let Some(dst) = dst.as_mut() {
+                dst.extend_from_slice(&buf[..len]);
+            }
+            (len, len < buf.len())
+        };
+        src.consume(len);
+        if done {
+            return Ok(());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::io::BufReader;
+
     use super::*;
 
     #[test]
@@ -69,5 +302,180 @@ mod tests {
 
         let r: Result<i64, ReadError> = read_next(" 11111 22222 ".as_bytes());
         assert_eq!(Some(11111), r.ok());
+
+        let r: Result<i64, ReadError> = read_next("\r\n\t12345\r\n".as_bytes());
+        assert_eq!(Some(12345), r.ok());
+
+        let r: Result<i64, ReadError> = read_next("x".as_bytes());
+        assert!(matches!(r, Err(ReadError::ParseInt(_))));
+
+        let r: Result<f64, ReadError> = read_next(" 1560762129.5\n".as_bytes());
+        assert_eq!(Some(1560762129.5), r.ok());
+
+        let r: Result<f64, ReadError> = read_next("1.5.0".as_bytes());
+        assert!(matches!(r, Err(ReadError::ParseFloat(_))));
+    }
+
+    #[test]
+    fn read_empty() {
+        let read = |input: &[u8]| read_next::<_, String, _>(input);
+        assert!(matches!(read(b""), Err(ReadError::Empty)));
+        assert!(matches!(read(b"   "), Err(ReadError::Empty)));
+        assert!(matches!(read(b" \r\n\t\n"), Err(ReadError::Empty)));
+        assert!(matches!(read(&[]), Err(ReadError::Empty)));
+
+        let mut reader = "1 ".as_bytes();
+        assert_eq!(read_next::<_, i64, _>(&mut reader).ok(), Some(1));
+        assert!(matches!(
+            read_next::<_, i64, _>(&mut reader),
+            Err(ReadError::Empty)
+        ));
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = |s: &str| {
+            read_tokens(s.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(tokens("1 2 3"), vec!["1", "2", "3"]);
+        assert_eq!(tokens("1 2 3 \n\t "), vec!["1", "2", "3"]);
+        assert_eq!(tokens("  1    2\t\t3"), vec!["1", "2", "3"]);
+        assert_eq!(tokens("1\n\n\r\n2\n"), vec!["1", "2"]);
+        assert!(tokens("").is_empty());
+        assert!(tokens(" \n\t ").is_empty());
+    }
+
+    #[test]
+    fn tokens_crlf_and_bom() {
+        let tokens = |s: &str| {
+            read_tokens(s.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(tokens("1\r\n2\r\n3\r\n"), vec!["1", "2", "3"]);
+        assert_eq!(tokens("\u{feff}1\r\n2\r\n"), vec!["1", "2"]);
+        assert_eq!(tokens("\u{feff}\r\n1\r\n"), vec!["1"]);
+        assert!(tokens("\u{feff}").is_empty());
+        assert!(tokens("\u{feff}\r\n").is_empty());
+
+        // only the BOM at the head of the input is skipped.
+        assert_eq!(tokens("1 \u{feff}2"), vec!["1", "\u{feff}2"]);
+
+        let values = read_all::<_, i64, _>("\u{feff}1560762129\r\n1560765729\r\n".as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![1560762129, 1560765729]);
+
+        let r: Result<i64, ReadError> = read_next("\u{feff}12345\r\n".as_bytes());
+        assert_eq!(Some(12345), r.ok());
+    }
+
+    #[test]
+    fn tokens_delimited_by_nul() {
+        let tokens = |s: &str| {
+            read_tokens_with(s.as_bytes(), Delimiter::Nul)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        assert_eq!(tokens("1\x002\x003\x00"), vec!["1", "2", "3"]);
+        assert_eq!(tokens("\x00\x001\x00\x002"), vec!["1", "2"]);
+        assert_eq!(tokens("a b\nc\x00d"), vec!["a b\nc", "d"]);
+        assert_eq!(tokens("\u{feff}1\x002"), vec!["1", "2"]);
+        assert!(tokens("").is_empty());
+        assert!(tokens("\x00\x00").is_empty());
+    }
+
+    #[test]
+    fn records() {
+        let records = |s: &str, delimiter| {
+            read_records(s.as_bytes(), delimiter)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+
+        use Delimiter::*;
+        assert_eq!(records("a b\nc\n", Whitespace), vec!["a b", "c"]);
+        assert_eq!(records("a b\r\n\r\nc", Whitespace), vec!["a b", "", "c"]);
+        assert_eq!(
+            records("\u{feff}a\n\u{feff}b", Whitespace),
+            vec!["a", "\u{feff}b"]
+        );
+        assert_eq!(records("a\nb\x00c\r\n\x00", Nul), vec!["a\nb", "c\r\n"]);
+        assert_eq!(records("\u{feff}a\x00b", Nul), vec!["a", "b"]);
+        assert!(records("", Whitespace).is_empty());
+        assert!(records("", Nul).is_empty());
+
+        assert_eq!(Whitespace.terminator(), '\n');
+        assert_eq!(Nul.terminator(), '\0');
+    }
+
+    #[test]
+    fn tokens_stop_after_error() {
+        let mut tokens = read_tokens(&[b'1', b' ', 0xe6, b' ', b'2'][..]);
+        assert_eq!(tokens.next().unwrap().unwrap(), "1");
+        assert!(matches!(tokens.next(), Some(Err(ReadError::Utf8(_)))));
+        assert!(tokens.next().is_none());
+    }
+
+    #[test]
+    fn read_all_values() {
+        let values = read_all::<_, i64, _>(" 1\t-2\n3 ".as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, vec![1, -2, 3]);
+
+        let values = read_all::<_, i64, _>("".as_bytes()).collect::<Vec<_>>();
+        assert!(values.is_empty());
+
+        let e = read_all::<_, i64, _>("1 2 x 4".as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert!(matches!(
+            &e,
+            ReadError::Token { index: 2, token, source }
+                if token == "x" && matches!(**source, ReadError::ParseInt(_))
+        ));
+        assert_eq!(
+            e.to_string(),
+            "Wrong token #2: 'x'. error:Parse int error. error:invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn read_utf8() {
+        let r: Result<String, ReadError> = read_next(" 日本語\u{a0}テキスト x".as_bytes());
+        assert_eq!(Some("日本語\u{a0}テキスト".to_string()), r.ok());
+
+        let r: Result<String, ReadError> = read_next(&[b' ', 0xe6, 0x97, b' '][..]);
+        assert!(matches!(r, Err(ReadError::Utf8(_))));
+    }
+
+    #[test]
+    fn read_large_input() {
+        // larger than the buffer of BufReader.
+        let padding = " \n".repeat(2 * 1024 * 1024);
+        let input = format!("{}12345{}", padding, padding);
+        let r: Result<i64, ReadError> = read_next(BufReader::new(input.as_bytes()));
+        assert_eq!(Some(12345), r.ok());
+
+        let token = "日本".repeat(1024 * 1024);
+        let input = format!("{} {}", token, padding);
+        let r: Result<String, ReadError> = read_next(BufReader::new(input.as_bytes()));
+        assert_eq!(Some(token), r.ok());
+
+        let input = (0..500_000)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut reader = BufReader::new(input.as_bytes());
+        let sum = (0..500_000)
+            .map(|_| read_next::<_, i64, _>(&mut reader).unwrap())
+            .sum::<i64>();
+        assert_eq!(sum, 499_999 * 500_000 / 2);
     }
 }
@@ -0,0 +1,573 @@
+use std::fmt::{self, Display};
+
+use serde::ser::{self, Impossible, Serialize, SerializeStruct};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use crate::json::quote;
+use crate::table::Cell;
+use ut_cli::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum OutputFormatError {
+    #[error("Wrong output format. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for OutputFormatError {
+    fn from(e: FindError) -> Self {
+        OutputFormatError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for OutputFormatError {
+    fn into_validation_error(self) -> String {
+        use OutputFormatError::*;
+        match self {
+            WrongName(e) => ValidationMessage::from_find_error(
+                "output format",
+                &e,
+                OutputFormat::possible_names(),
+            )
+            .to_string(),
+        }
+    }
+}
+
+/// How the outputs of commands are printed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum OutputFormat {
+    /// The text of each command, like a timestamp or a formatted datetime.
+    #[default]
+    #[strum(serialize = "plain")]
+    Plain,
+
+    /// A JSON object per record, in a line.
+    #[strum(serialize = "json")]
+    Json,
+
+    /// A header row, then a row per record.
+    #[strum(serialize = "csv")]
+    Csv,
+
+    /// A header row, then a row per record, in aligned columns.
+    #[strum(serialize = "table")]
+    Table,
+}
+
+impl PossibleValues for OutputFormat {
+    type Iterator = OutputFormatIter;
+
+    fn possible_values() -> Self::Iterator {
+        OutputFormat::iter()
+    }
+}
+
+impl PossibleNames for OutputFormat {}
+
+impl FindByName for OutputFormat {
+    type Error = OutputFormatError;
+}
+
+/// A record of an output, which is a flat struct of numbers and texts.
+/// The fields are printed in the order of their declaration.
+pub trait Record: Serialize {
+    /// Names of the CSV columns, which are the names of the fields.
+    const HEADER: &'static [&'static str];
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum RecordError {
+    #[error("Cannot print the record: {0}")]
+    Unsupported(String),
+}
+
+impl ser::Error for RecordError {
+    fn custom<T: Display>(msg: T) -> Self {
+        RecordError::Unsupported(msg.to_string())
+    }
+}
+
+/// A value of a field.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    Unsigned(u64),
+    Float(f64),
+    Text(String),
+}
+
+impl Value {
+    fn json(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Float(f) if !f.is_finite() => "null".to_string(),
+            Value::Text(s) => quote(s),
+            v => v.to_string(),
+        }
+    }
+
+    fn csv(&self) -> String {
+        match self {
+            Value::Text(s) => csv_quote(s),
+            v => v.to_string(),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Unsigned(u) => write!(f, "{}", u),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Quotes the field of CSV if it has a comma, a quote or a line break, like RFC 4180.
+pub fn csv_quote(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Returns the fields of the record with their names, in the order of the declaration.
+fn fields<R: Record>(record: &R) -> Result<Vec<(&'static str, Value)>, RecordError> {
+    record.serialize(FieldsSerializer)
+}
+
+/// Renders the record as a JSON object.
+pub fn json<R: Record>(record: &R) -> Result<String, RecordError> {
+    let fields = fields(record)?
+        .iter()
+        .map(|(name, value)| format!("{}:{}", quote(name), value.json()))
+        .collect::<Vec<_>>();
+    Ok(format!("{{{}}}", fields.join(",")))
+}
+
+/// Renders the header row of CSV of the record type.
+pub fn csv_header<R: Record>() -> String {
+    R::HEADER
+        .iter()
+        .map(|name| csv_quote(name))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Renders the record as a row of CSV.
+pub fn csv<R: Record>(record: &R) -> Result<String, RecordError> {
+    Ok(fields(record)?
+        .iter()
+        .map(|(_, value)| value.csv())
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Renders the record as cells of a table, where numbers are aligned to the right.
+pub fn cells<R: Record>(record: &R) -> Result<Vec<Cell>, RecordError> {
+    Ok(fields(record)?
+        .into_iter()
+        .map(|(_, value)| {
+            let numeric = matches!(
+                value,
+                Value::Integer(_) | Value::Unsigned(_) | Value::Float(_)
+            );
+            Cell::new(value.to_string(), numeric)
+        })
+        .collect())
+}
+
+/// Serializes a struct into its fields.
+struct FieldsSerializer;
+
+struct Fields(Vec<(&'static str, Value)>);
+
+/// Serializes a scalar into a value.
+struct ValueSerializer;
+
+fn unsupported<T>(kind: &str) -> Result<T, RecordError> {
+    Err(RecordError::Unsupported(format!(
+        "{} is not a field of a flat record.",
+        kind
+    )))
+}
+
+macro_rules! unsupported_methods {
+    ($ok:ty; $($method:ident($($arg:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ok, RecordError> {
+                unsupported(stringify!($method))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for FieldsSerializer {
+    type Ok = Vec<(&'static str, Value)>;
+    type Error = RecordError;
+    type SerializeSeq = Impossible<Self::Ok, RecordError>;
+    type SerializeTuple = Impossible<Self::Ok, RecordError>;
+    type SerializeTupleStruct = Impossible<Self::Ok, RecordError>;
+    type SerializeTupleVariant = Impossible<Self::Ok, RecordError>;
+    type SerializeMap = Impossible<Self::Ok, RecordError>;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Self::Ok, RecordError>;
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, RecordError> {
+        Ok(Fields(Vec::with_capacity(len)))
+    }
+
+    unsupported_methods! {
+        Self::Ok;
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<Self::Ok, RecordError> {
+        unsupported("an option")
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, RecordError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, RecordError> {
+        unsupported("an enum")
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, RecordError> {
+        unsupported("a sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, RecordError> {
+        unsupported("a tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, RecordError> {
+        unsupported("a tuple struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, RecordError> {
+        unsupported("an enum")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, RecordError> {
+        unsupported("a map")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, RecordError> {
+        unsupported("an enum")
+    }
+}
+
+impl SerializeStruct for Fields {
+    type Ok = Vec<(&'static str, Value)>;
+    type Error = RecordError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), RecordError> {
+        self.0.push((key, value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, RecordError> {
+        Ok(self.0)
+    }
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = RecordError;
+    type SerializeSeq = Impossible<Value, RecordError>;
+    type SerializeTuple = Impossible<Value, RecordError>;
+    type SerializeTupleStruct = Impossible<Value, RecordError>;
+    type SerializeTupleVariant = Impossible<Value, RecordError>;
+    type SerializeMap = Impossible<Value, RecordError>;
+    type SerializeStruct = Impossible<Value, RecordError>;
+    type SerializeStructVariant = Impossible<Value, RecordError>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, RecordError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, RecordError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, RecordError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, RecordError> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, RecordError> {
+        Ok(Value::Integer(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, RecordError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, RecordError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, RecordError> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, RecordError> {
+        Ok(Value::Unsigned(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, RecordError> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, RecordError> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, RecordError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, RecordError> {
+        Ok(Value::Text(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<Value, RecordError> {
+        unsupported("bytes")
+    }
+
+    fn serialize_none(self) -> Result<Value, RecordError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Value, RecordError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, RecordError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Value, RecordError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Value, RecordError> {
+        Ok(Value::Text(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Value, RecordError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Value, RecordError> {
+        unsupported("an enum with a value")
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, RecordError> {
+        unsupported("a sequence")
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, RecordError> {
+        unsupported("a tuple")
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, RecordError> {
+        unsupported("a tuple struct")
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, RecordError> {
+        unsupported("an enum with values")
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, RecordError> {
+        unsupported("a map")
+    }
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStruct, RecordError> {
+        unsupported("a nested struct")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, RecordError> {
+        unsupported("an enum with fields")
+    }
+}
+
+/// Checks that the header of the record type is the names of its fields.
+#[cfg(test)]
+pub fn assert_header<R: Record>(record: &R) {
+    let names: Vec<&str> = fields(record).unwrap().iter().map(|(n, _)| *n).collect();
+    assert_eq!(names, R::HEADER);
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use crate::record::{assert_header, cells, csv, csv_header, csv_quote, json, Record};
+    use crate::table::Cell;
+
+    #[derive(Serialize)]
+    struct Sample {
+        timestamp: i64,
+        datetime: String,
+        fraction: Option<f64>,
+    }
+
+    impl Record for Sample {
+        const HEADER: &'static [&'static str] = &["timestamp", "datetime", "fraction"];
+    }
+
+    #[test]
+    fn render() {
+        let sample = Sample {
+            timestamp: 1_560_762_129,
+            datetime: "2019-06-17, \"09:02:09\"".to_string(),
+            fraction: None,
+        };
+        assert_header(&sample);
+        assert_eq!(
+            json(&sample).unwrap(),
+            r#"{"timestamp":1560762129,"datetime":"2019-06-17, \"09:02:09\"","fraction":null}"#
+        );
+        assert_eq!(csv_header::<Sample>(), "timestamp,datetime,fraction");
+        assert_eq!(
+            csv(&sample).unwrap(),
+            r#"1560762129,"2019-06-17, ""09:02:09""","#
+        );
+
+        let sample = Sample {
+            fraction: Some(0.5),
+            datetime: "2019-06-17".to_string(),
+            ..sample
+        };
+        assert_eq!(csv(&sample).unwrap(), "1560762129,2019-06-17,0.5");
+        assert_eq!(
+            cells(&sample).unwrap(),
+            vec![
+                Cell::new("1560762129".to_string(), true),
+                Cell::new("2019-06-17".to_string(), false),
+                Cell::new("0.5".to_string(), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn quote_fields() {
+        assert_eq!(csv_quote("plain"), "plain");
+        assert_eq!(csv_quote("a,b"), "\"a,b\"");
+        assert_eq!(csv_quote("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn unsupported() {
+        #[derive(Serialize)]
+        struct Nested {
+            sample: Vec<i64>,
+        }
+        impl Record for Nested {
+            const HEADER: &'static [&'static str] = &["sample"];
+        }
+        assert!(json(&Nested { sample: vec![1] }).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use ut_cli::delta::{DeltaItem, DeltaItemError};
+use ut_cli::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use ut_cli::timedelta::{ApplyDateTime, TimeDeltaBuilder};
+use ut_cli::unit::TimeUnit;
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SnapError {
+    #[error("Wrong interval. error:{0}")]
+    WrongInterval(DeltaItemError),
+
+    #[error("Non-positive interval: '{0}'. interval must be greater than 0.")]
+    NonPositiveInterval(String),
+}
+
+impl IntoValidationError for SnapError {
+    fn into_validation_error(self) -> String {
+        use SnapError::*;
+        match self {
+            WrongInterval(e) => e.into_validation_error(),
+            NonPositiveInterval(s) => {
+                ValidationMessage::new("Wrong interval: interval must be greater than 0.", s)
+                    .accepted("a positive number followed by a unit, like `15m` or `6h`")
+                    .to_string()
+            }
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SnapModeError {
+    #[error("Wrong snap mode. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for SnapModeError {
+    fn from(e: FindError) -> Self {
+        SnapModeError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for SnapModeError {
+    fn into_validation_error(self) -> String {
+        use SnapModeError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("snap mode", &e, SnapMode::possible_names())
+                    .to_string()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum SnapMode {
+    #[strum(serialize = "floor")]
+    Floor,
+
+    #[strum(serialize = "round")]
+    Round,
+
+    #[strum(serialize = "ceil")]
+    Ceil,
+}
+
+impl PossibleValues for SnapMode {
+    type Iterator = SnapModeIter;
+
+    fn possible_values() -> Self::Iterator {
+        SnapMode::iter()
+    }
+}
+
+impl PossibleNames for SnapMode {}
+
+impl FindByName for SnapMode {
+    type Error = SnapModeError;
+}
+
+/// An interval to snap datetimes to, like `15m`.
+/// `m` is minutes here, as months are rarely snapped to; they are still accepted as `mon`.
+/// Multiples of the interval are measured from the start of the enclosing unit.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Snap {
+    interval: DeltaItem,
+}
+
+impl Snap {
+    pub fn apply<Tz: TimeZone>(self, dt: DateTime<Tz>, mode: SnapMode) -> Option<DateTime<Tz>> {
+        let unit = self.interval.unit();
+        let n = self.interval.value();
+
+        let (base, index) = match unit.enclosing() {
+            Some(enclosing) => (enclosing.truncate(dt.clone()).ok()?, unit_index(&dt, unit)),
+            None => (
+                TimeUnit::Year.truncate(dt.clone()).ok()?.with_year(0)?,
+                unit_index(&dt, unit),
+            ),
+        };
+        let floor = add_units(base.clone(), unit, index.div_euclid(n) * n)?;
+        if mode == SnapMode::Floor || floor == dt {
+            return Some(floor);
+        }
+
+        let next = add_units(floor.clone(), unit, n)?;
+        let next = match unit.enclosing() {
+            Some(enclosing) => add_units(base, enclosing, 1)?.min(next),
+            None => next,
+        };
+
+        match mode {
+            SnapMode::Round if dt.clone() - floor.clone() < next.clone() - dt => Some(floor),
+            _ => Some(next),
+        }
+    }
+}
+
+impl FromStr for Snap {
+    type Err = SnapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let minutes = s.strip_suffix('m').and_then(|v| i32::from_str(v).ok());
+        let interval = match minutes {
+            Some(value) => DeltaItem::new(TimeUnit::Minute, value),
+            None => DeltaItem::from_str(s).map_err(SnapError::WrongInterval)?,
+        };
+        if interval.value() > 0 {
+            Ok(Snap { interval })
+        } else {
+            Err(SnapError::NonPositiveInterval(s.to_string()))
+        }
+    }
+}
+
+fn unit_index<Tz: TimeZone>(dt: &DateTime<Tz>, unit: TimeUnit) -> i32 {
+    match unit {
+        TimeUnit::Century => dt.year().div_euclid(100),
+        TimeUnit::Decade => dt.year().div_euclid(10),
+        TimeUnit::Year => dt.year(),
+        TimeUnit::Month => dt.month0() as i32,
+        TimeUnit::Day => dt.day0() as i32,
+        TimeUnit::Hour => dt.hour() as i32,
+        TimeUnit::Minute => dt.minute() as i32,
+        TimeUnit::Second => dt.second() as i32,
+        TimeUnit::MilliSecond => dt.timestamp_subsec_millis() as i32,
+    }
+}
+
+fn add_units<Tz: TimeZone>(dt: DateTime<Tz>, unit: TimeUnit, value: i32) -> Option<DateTime<Tz>> {
+    DeltaItem::new(unit, value)
+        .apply_timedelta_builder(TimeDeltaBuilder::default())
+        .build()
+        .apply_datetime(dt)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::offset::TimeZone;
+    use chrono::{DateTime, Utc};
+
+    use super::{Snap, SnapError, SnapMode};
+
+    fn snap(s: &str, dt: DateTime<Utc>, mode: SnapMode) -> DateTime<Utc> {
+        Snap::from_str(s).unwrap().apply(dt, mode).unwrap()
+    }
+
+    fn base_date() -> DateTime<Utc> {
+        Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 444)
+    }
+
+    #[test]
+    fn snap_from_str() {
+        assert!(Snap::from_str("15min").is_ok());
+        assert_eq!(Snap::from_str("15m"), Snap::from_str("15min"));
+        assert!(Snap::from_str("+2h").is_ok());
+
+        assert_eq!(
+            Snap::from_str("0min"),
+            Err(SnapError::NonPositiveInterval("0min".to_string()))
+        );
+        assert_eq!(
+            Snap::from_str("-15min"),
+            Err(SnapError::NonPositiveInterval("-15min".to_string()))
+        );
+        assert!(Snap::from_str("15").is_err());
+        assert_eq!(
+            Snap::from_str("0m"),
+            Err(SnapError::NonPositiveInterval("0m".to_string()))
+        );
+    }
+
+    #[test]
+    fn snap_minutes() {
+        let t = |h, m, s| Utc.ymd(2019, 6, 17).and_hms(h, m, s);
+
+        assert_eq!(snap("15m", t(11, 22, 0), SnapMode::Floor), t(11, 15, 0));
+        assert_eq!(snap("15m", t(11, 22, 30), SnapMode::Round), t(11, 30, 0));
+
+        assert_eq!(snap("15min", base_date(), SnapMode::Floor), t(11, 15, 0));
+        assert_eq!(snap("15min", base_date(), SnapMode::Round), t(11, 30, 0));
+        assert_eq!(snap("15min", base_date(), SnapMode::Ceil), t(11, 30, 0));
+
+        assert_eq!(snap("15min", t(11, 22, 0), SnapMode::Floor), t(11, 15, 0));
+        assert_eq!(snap("15min", t(11, 22, 0), SnapMode::Round), t(11, 15, 0));
+        assert_eq!(snap("15min", t(11, 23, 0), SnapMode::Round), t(11, 30, 0));
+        assert_eq!(snap("15min", t(11, 22, 30), SnapMode::Round), t(11, 30, 0));
+        assert_eq!(snap("15min", t(11, 15, 0), SnapMode::Ceil), t(11, 15, 0));
+
+        // next multiple is limited by the start of the next hour.
+        assert_eq!(snap("25min", t(11, 55, 0), SnapMode::Floor), t(11, 50, 0));
+        assert_eq!(snap("25min", t(11, 55, 0), SnapMode::Ceil), t(12, 0, 0));
+    }
+
+    #[test]
+    fn snap_other_units() {
+        assert_eq!(
+            snap("6h", base_date(), SnapMode::Floor),
+            Utc.ymd(2019, 6, 17).and_hms(6, 0, 0)
+        );
+        assert_eq!(
+            snap("100ms", base_date(), SnapMode::Round),
+            Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 400)
+        );
+        assert_eq!(
+            snap("10d", base_date(), SnapMode::Floor),
+            Utc.ymd(2019, 6, 11).and_hms(0, 0, 0)
+        );
+        assert_eq!(
+            snap("3mon", base_date(), SnapMode::Ceil),
+            Utc.ymd(2019, 7, 1).and_hms(0, 0, 0)
+        );
+        assert_eq!(
+            snap("10y", base_date(), SnapMode::Round),
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 0)
+        );
+    }
+}
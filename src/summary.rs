@@ -0,0 +1,79 @@
+use std::fmt::{Display, Formatter};
+
+/// Counts of a batch run, like `converted 12345 timestamps (3 errors)`.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct Summary {
+    converted: usize,
+    errors: usize,
+}
+
+impl Summary {
+    pub fn converted(&mut self) {
+        self.converted += 1;
+    }
+
+    /// Counts an input which is skipped or kept as it is.
+    pub fn error(&mut self) {
+        self.errors += 1;
+    }
+
+    /// Returns true if nothing is converted or skipped, like a run on an empty input.
+    pub fn is_empty(&self) -> bool {
+        self.converted == 0 && self.errors == 0
+    }
+
+    /// Adds the counts of the other summary, like the one of a line.
+    pub fn merge(&mut self, other: Summary) {
+        self.converted += other.converted;
+        self.errors += other.errors;
+    }
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, word)
+    } else {
+        format!("{} {}s", n, word)
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "converted {} ({})",
+            plural(self.converted, "timestamp"),
+            plural(self.errors, "error")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::summary::Summary;
+
+    #[test]
+    fn display() {
+        let mut summary = Summary::default();
+        assert!(summary.is_empty());
+        assert_eq!(summary.to_string(), "converted 0 timestamps (0 errors)");
+
+        summary.error();
+        assert!(!summary.is_empty());
+        summary.converted();
+        assert_eq!(summary.to_string(), "converted 1 timestamp (1 error)");
+
+        summary.merge(Summary {
+            converted: 12344,
+            errors: 2,
+        });
+        assert_eq!(
+            summary,
+            Summary {
+                converted: 12345,
+                errors: 3
+            }
+        );
+        assert_eq!(summary.to_string(), "converted 12345 timestamps (3 errors)");
+    }
+}
@@ -0,0 +1,219 @@
+use std::io::{self, Write};
+
+use unicode_width::UnicodeWidthStr;
+
+/// Rows buffered to compute the widths of columns. Later rows are streamed.
+pub const BUFFERED_ROWS: usize = 1000;
+
+/// Spaces between columns.
+const GAP: &str = "  ";
+
+/// A cell of a table. Numbers are aligned to the right, and texts to the left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    text: String,
+    numeric: bool,
+}
+
+impl Cell {
+    pub fn new(text: String, numeric: bool) -> Cell {
+        Cell { text, numeric }
+    }
+}
+
+/// A table of records with aligned columns.
+/// Rows are buffered up to the limit to compute the widths of the columns by their display widths,
+/// then the buffered rows are printed and the rest are streamed in the widths.
+/// A streamed cell wider than its column is not truncated, so the row is ragged.
+#[derive(Debug)]
+pub struct Table {
+    header: Vec<String>,
+    rows: Vec<Vec<Cell>>,
+    widths: Vec<usize>,
+    numeric: Vec<bool>,
+    limit: usize,
+    streaming: bool,
+}
+
+impl Table {
+    pub fn new(header: &[&str], limit: usize) -> Table {
+        Table {
+            header: header.iter().map(|name| name.to_string()).collect(),
+            rows: Vec::new(),
+            widths: header.iter().map(|name| name.width()).collect(),
+            numeric: Vec::new(),
+            limit,
+            streaming: false,
+        }
+    }
+
+    /// Adds a row, which is buffered until the limit, or printed while streaming.
+    pub fn push(&mut self, row: Vec<Cell>, out: &mut dyn Write) -> io::Result<()> {
+        if self.streaming {
+            return self.write_row(out, &row);
+        }
+        if self.numeric.is_empty() {
+            // the header is aligned like the cells of the first row.
+            self.numeric = row.iter().map(|cell| cell.numeric).collect();
+        }
+        for (width, cell) in self.widths.iter_mut().zip(&row) {
+            *width = (*width).max(cell.text.width());
+        }
+        self.rows.push(row);
+        if self.rows.len() >= self.limit {
+            self.flush(out)?;
+        }
+        Ok(())
+    }
+
+    /// Prints the header and the buffered rows, then streams the rest.
+    pub fn flush(&mut self, out: &mut dyn Write) -> io::Result<()> {
+        if self.streaming {
+            return Ok(());
+        }
+        let header: Vec<Cell> = self
+            .header
+            .iter()
+            .zip(self.numeric.iter().chain(std::iter::repeat(&false)))
+            .map(|(name, numeric)| Cell::new(name.clone(), *numeric))
+            .collect();
+        self.write_row(out, &header)?;
+        for row in std::mem::take(&mut self.rows) {
+            self.write_row(out, &row)?;
+        }
+        self.streaming = true;
+        Ok(())
+    }
+
+    fn write_row(&self, out: &mut dyn Write, row: &[Cell]) -> io::Result<()> {
+        let mut line = String::new();
+        for (i, cell) in row.iter().enumerate() {
+            let width = self.widths.get(i).copied().unwrap_or_default();
+            let padding = " ".repeat(width.saturating_sub(cell.text.width()));
+            if i > 0 {
+                line.push_str(GAP);
+            }
+            if cell.numeric {
+                line.push_str(&padding);
+                line.push_str(&cell.text);
+            } else {
+                line.push_str(&cell.text);
+                // the last column is not padded, not to print trailing spaces.
+                if i + 1 < row.len() {
+                    line.push_str(&padding);
+                }
+            }
+        }
+        writeln!(out, "{}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::table::{Cell, Table};
+
+    fn row(timestamp: &str, datetime: &str) -> Vec<Cell> {
+        vec![
+            Cell::new(timestamp.to_string(), true),
+            Cell::new(datetime.to_string(), false),
+        ]
+    }
+
+    #[test]
+    fn align() {
+        let mut out = Vec::new();
+        let mut table = Table::new(&["timestamp", "datetime"], 10);
+        table.push(row("0", "1970-01-01"), &mut out).unwrap();
+        table
+            .push(row("1560762129", "2019-06-17"), &mut out)
+            .unwrap();
+        table.push(row("-1", "1969-12-31"), &mut out).unwrap();
+        assert!(out.is_empty());
+
+        table.flush(&mut out).unwrap();
+        table.flush(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            " timestamp  datetime\n\
+             \x20        0  1970-01-01\n\
+             1560762129  2019-06-17\n\
+             \x20       -1  1969-12-31\n"
+        );
+    }
+
+    #[test]
+    fn unicode_width() {
+        // full-width characters take two columns of a terminal.
+        let mut out = Vec::new();
+        let mut table = Table::new(&["month", "days"], 10);
+        for month in ["2月", "février", "Feb"].iter() {
+            let row = vec![
+                Cell::new(month.to_string(), false),
+                Cell::new("28".to_string(), true),
+            ];
+            table.push(row, &mut out).unwrap();
+        }
+        table.flush(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "month    days\n\
+             2月        28\n\
+             février    28\n\
+             Feb        28\n"
+        );
+    }
+
+    #[test]
+    fn unicode_width_in_middle_column() {
+        // a wide cell before the last column pads the following columns by its display width.
+        let mut out = Vec::new();
+        let mut table = Table::new(&["timestamp", "month", "weekday"], 2);
+        for (timestamp, month, weekday) in [
+            ("1560762129", "6月", "月曜日"),
+            ("0", "janvier", "jeudi"),
+            ("1576800000", "12月", "金曜日"),
+        ]
+        .iter()
+        {
+            let row = vec![
+                Cell::new(timestamp.to_string(), true),
+                Cell::new(month.to_string(), false),
+                Cell::new(weekday.to_string(), false),
+            ];
+            table.push(row, &mut out).unwrap();
+        }
+        table.flush(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            " timestamp  month    weekday\n\
+             1560762129  6月      月曜日\n\
+             \x20        0  janvier  jeudi\n\
+             1576800000  12月     金曜日\n"
+        );
+    }
+
+    #[test]
+    fn stream_after_limit() {
+        let mut out = Vec::new();
+        let mut table = Table::new(&["timestamp", "datetime"], 2);
+        table.push(row("0", "1970-01-01"), &mut out).unwrap();
+        assert!(out.is_empty());
+        table.push(row("86400", "1970-01-02"), &mut out).unwrap();
+        // rows are streamed in the widths of the buffered rows, and wider cells are not truncated.
+        table
+            .push(row("1560762129", "2019-06-17"), &mut out)
+            .unwrap();
+        table
+            .push(row("1", "1970-01-01 00:00:01"), &mut out)
+            .unwrap();
+        table.flush(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "timestamp  datetime\n\
+             \x20       0  1970-01-01\n\
+             \x20   86400  1970-01-02\n\
+             1560762129  2019-06-17\n\
+             \x20       1  1970-01-01 00:00:01\n"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::str::FromStr;
+
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use ut_cli::find::{PossibleNames, PossibleValues};
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TemplateError {
+    #[error("Wrong template: '{0}'. placeholder '{1}' is unknown.")]
+    UnknownPlaceholder(String, String),
+
+    #[error("Wrong template: '{0}'. a placeholder is not closed by `}}`.")]
+    Unclosed(String),
+
+    #[error("Wrong template: '{0}'. a closing brace must be escaped as `}}}}`.")]
+    UnmatchedBrace(String),
+}
+
+impl IntoValidationError for TemplateError {
+    fn into_validation_error(self) -> String {
+        use TemplateError::*;
+        let message = match self {
+            UnknownPlaceholder(s, name) => ValidationMessage::new(
+                format!("Wrong template: unknown placeholder '{}'.", name),
+                s,
+            )
+            .possible_names(Placeholder::possible_names()),
+            Unclosed(s) => {
+                ValidationMessage::new("Wrong template: a placeholder is not closed by `}`.", s)
+            }
+            UnmatchedBrace(s) => ValidationMessage::new(
+                "Wrong template: a closing brace must be escaped as `}}`.",
+                s,
+            ),
+        };
+        message
+            .accepted(
+                "texts with placeholders like `{timestamp}\\t{iso}`, and `{{` or `}}` for braces",
+            )
+            .to_string()
+    }
+}
+
+/// A value of a parsed timestamp to fill a template with.
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum Placeholder {
+    /// The timestamp in the precision. A fractional one is truncated.
+    #[strum(serialize = "timestamp")]
+    Timestamp,
+
+    /// The datetime in RFC3339, like `2019-06-17T18:02:09+09:00`.
+    #[strum(serialize = "iso")]
+    Iso,
+
+    /// Same as `iso`.
+    #[strum(serialize = "rfc3339")]
+    Rfc3339,
+
+    /// The datetime in the format of `-f` or `--style`.
+    #[strum(serialize = "formatted")]
+    Formatted,
+
+    /// The weekday name in the locale.
+    #[strum(serialize = "weekday")]
+    Weekday,
+
+    /// The day of the year, from 1.
+    #[strum(serialize = "doy")]
+    DayOfYear,
+
+    /// The ISO week number, from 1.
+    #[strum(serialize = "week")]
+    Week,
+
+    /// The offset of the timezone, like `+09:00`.
+    #[strum(serialize = "offset")]
+    Offset,
+
+    /// The time from now, like `3 days 04:05:06 ago`.
+    #[strum(serialize = "relative")]
+    Relative,
+
+    /// The timestamp as it is read.
+    #[strum(serialize = "input")]
+    Input,
+}
+
+impl PossibleValues for Placeholder {
+    type Iterator = PlaceholderIter;
+
+    fn possible_values() -> Self::Iterator {
+        Placeholder::iter()
+    }
+}
+
+impl PossibleNames for Placeholder {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A text with placeholders in braces like `{timestamp}`, where `{{` and `}}` are braces.
+/// `\t`, `\n` and `\\` are a tab, a newline and a backslash, and other backslashes are kept as they are.
+/// Names of placeholders are validated when the template is parsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    pub fn contains(&self, placeholder: Placeholder) -> bool {
+        self.parts.contains(&Part::Placeholder(placeholder))
+    }
+
+    /// Fills the placeholders with the values, or returns the first error of them.
+    pub fn render<E, F>(&self, mut value: F) -> Result<String, E>
+    where
+        F: FnMut(Placeholder) -> Result<String, E>,
+    {
+        let mut rendered = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => rendered.push_str(text),
+                Part::Placeholder(placeholder) => rendered.push_str(&value(*placeholder)?),
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match (c, chars.peek()) {
+                ('{', Some('{')) | ('}', Some('}')) => {
+                    text.push(c);
+                    chars.next();
+                }
+                ('{', _) => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed(s.to_string())),
+                        }
+                    }
+                    let placeholder = Placeholder::from_str(&name)
+                        .map_err(|_| TemplateError::UnknownPlaceholder(s.to_string(), name))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Placeholder(placeholder));
+                }
+                ('}', _) => return Err(TemplateError::UnmatchedBrace(s.to_string())),
+                // tabs and newlines, as shells do not unescape them in single quotes.
+                ('\\', Some('t')) | ('\\', Some('n')) | ('\\', Some('\\')) => {
+                    text.push(match chars.next() {
+                        Some('t') => '\t',
+                        Some('n') => '\n',
+                        _ => '\\',
+                    });
+                }
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(Template { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use crate::template::{Placeholder, Template, TemplateError};
+    use ut_cli::validate::IntoValidationError;
+
+    fn render(s: &str) -> Result<String, TemplateError> {
+        let template = Template::from_str(s)?;
+        Ok(template
+            .render(|p| Ok::<_, ()>(format!("<{}>", p)))
+            .unwrap())
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(
+            render("{timestamp}\t{iso}\t{weekday}\t{relative}"),
+            Ok("<timestamp>\t<iso>\t<weekday>\t<relative>".to_string())
+        );
+        assert_eq!(
+            render("{input} is {doy} of week {week} at {offset}"),
+            Ok("<input> is <doy> of week <week> at <offset>".to_string())
+        );
+        assert_eq!(render(""), Ok("".to_string()));
+        assert_eq!(render("plain"), Ok("plain".to_string()));
+
+        let template = Template::from_str("{rfc3339} {formatted}").unwrap();
+        assert!(template.contains(Placeholder::Rfc3339));
+        assert!(!template.contains(Placeholder::Relative));
+    }
+
+    #[test]
+    fn escaped_braces() {
+        assert_eq!(render("{{}}"), Ok("{}".to_string()));
+        assert_eq!(
+            render("{{\"t\": {timestamp}}}"),
+            Ok("{\"t\": <timestamp>}".to_string())
+        );
+        assert_eq!(render("{{{iso}}}"), Ok("{<iso>}".to_string()));
+    }
+
+    #[test]
+    fn escaped_chars() {
+        assert_eq!(
+            render(r"{timestamp}\t{iso}\n"),
+            Ok("<timestamp>\t<iso>\n".to_string())
+        );
+        assert_eq!(render(r"a\\tb"), Ok(r"a\tb".to_string()));
+        assert_eq!(render(r"C:\dir\"), Ok(r"C:\dir\".to_string()));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            render("{time}"),
+            Err(TemplateError::UnknownPlaceholder(
+                "{time}".to_string(),
+                "time".to_string()
+            ))
+        );
+        // names are case sensitive, and spaces are not trimmed.
+        assert!(render("{ISO}").is_err());
+        assert!(render("{ iso }").is_err());
+        assert!(render("{}").is_err());
+        assert_eq!(
+            render("{iso"),
+            Err(TemplateError::Unclosed("{iso".to_string()))
+        );
+        assert_eq!(
+            render("iso}"),
+            Err(TemplateError::UnmatchedBrace("iso}".to_string()))
+        );
+
+        let message = TemplateError::UnknownPlaceholder("{time}".to_string(), "time".to_string())
+            .into_validation_error();
+        assert!(message.starts_with("Wrong template: unknown placeholder 'time'."));
+        assert!(message.contains(
+            "possible names: [timestamp, iso, rfc3339, formatted, weekday, doy, week, offset, relative, input]"
+        ));
+    }
+}
@@ -1,7 +1,62 @@
-use chrono::{DateTime, Datelike, Duration, TimeZone};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, LocalResult, NaiveDate, TimeZone};
+use thiserror::Error;
+
+use crate::datetime::resolve_local_datetime;
+use crate::delta::{apply_item, is_iso8601, parse_iso8601, split_items, DeltaItemError};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TimeDeltaError {
+    #[error("Wrong delta: '{0}'. error:{1}")]
+    WrongItem(String, DeltaItemError),
+
+    #[error("Delta overflow: {0} are out of range.")]
+    Overflow(&'static str),
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ApplyError {
+    #[error("Nonexistent date: {year:04}-{month:02}-{day:02} does not exist.")]
+    NonexistentDate { year: i32, month: u32, day: u32 },
+
+    #[error("Invalid datetime: {0} does not exist, or is ambiguous in the timezone.")]
+    InvalidComposition(String),
+
+    #[error("Cannot apply delta '{0}' to {1}.")]
+    OutOfRange(String, String),
+}
+
+/// An adjustment made by `TimeDelta::apply_datetime_saturating` to land on a valid datetime.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Adjustment {
+    /// The day does not exist in the resulting month, so it was clamped to the last day.
+    MonthEnd { day: u32, last_day: u32 },
+
+    /// The local time does not exist, e.g. by a DST transition, so it was moved to the end of the gap.
+    Gap,
+}
+
+impl fmt::Display for Adjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Adjustment::MonthEnd { day, last_day } => write!(
+                f,
+                "day {} does not exist in the month, clamped to day {}.",
+                day, last_day
+            ),
+            Adjustment::Gap => write!(
+                f,
+                "local time does not exist, moved forward to the end of the gap."
+            ),
+        }
+    }
+}
 
 pub trait ApplyDateTime<Tz: TimeZone> {
-    fn apply_datetime(&self, dt: DateTime<Tz>) -> Option<DateTime<Tz>>;
+    fn apply_datetime(&self, dt: DateTime<Tz>) -> Result<DateTime<Tz>, ApplyError>;
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -10,124 +65,452 @@ pub struct TimeDelta {
 }
 
 impl TimeDelta {
-    #[allow(dead_code)]
-    pub fn new(
-        years: i32,
-        months: i32,
-        days: i32,
-        hours: i32,
-        minutes: i32,
-        seconds: i32,
-        microseconds: i32,
-    ) -> Self {
-        // microseconds
-        let sign = sign_of(microseconds);
-        let (d, m) = div_mod(microseconds * sign, 1_000_000);
-        let seconds = seconds + d * sign;
-        let microseconds = m * sign;
-
-        // seconds
-        let sign = sign_of(seconds);
-        let (d, m) = div_mod(seconds * sign, 60);
-        let minutes = minutes + d * sign;
-        let seconds = m * sign;
-
-        // minutes
-        let sign = sign_of(minutes);
-        let (d, m) = div_mod(minutes * sign, 60);
-        let hours = hours + d * sign;
-        let minutes = m * sign;
-
-        // hours
-        let sign = sign_of(hours);
-        let (d, m) = div_mod(hours * sign, 24);
-        let days = days + d * sign;
-        let hours = m * sign;
+    /// Creates a normalized delta, whose smaller fields are carried over to larger fields.
+    /// Returns an error if a carried field exceeds the range of `i64`.
+    pub fn try_new(
+        years: i64,
+        months: i64,
+        days: i64,
+        hours: i64,
+        minutes: i64,
+        seconds: i64,
+        microseconds: i64,
+    ) -> Result<Self, TimeDeltaError> {
+        let (seconds, microseconds) =
+            carry(i128::from(seconds), i128::from(microseconds), 1_000_000);
+        let (minutes, seconds) = carry(i128::from(minutes), seconds, 60);
+        let (hours, minutes) = carry(i128::from(hours), minutes, 60);
+        let (days, hours) = carry(i128::from(days), hours, 24);
 
         // NOTE: cannot convert days to months.
 
-        // months
-        let sign = sign_of(months);
-        let (d, m) = div_mod(months * sign, 12);
-        let years = years + d * sign;
-        let months = m * sign;
+        let (years, months) = carry(i128::from(years), i128::from(months), 12);
 
-        TimeDelta {
+        Ok(TimeDelta {
             values: DeltaValues {
-                years,
-                months,
-                days,
-                hours,
-                minutes,
-                seconds,
-                microseconds,
+                years: narrow(years, "years")?,
+                months: narrow(months, "months")?,
+                days: narrow(days, "days")?,
+                hours: narrow(hours, "hours")?,
+                minutes: narrow(minutes, "minutes")?,
+                seconds: narrow(seconds, "seconds")?,
+                microseconds: narrow(microseconds, "microseconds")?,
             },
-        }
+        })
     }
 
-    pub fn years(&self) -> i32 {
+    /// Creates a normalized delta like `try_new`, for deltas known to be in range like in tests.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a carried field exceeds the range of `i64`. Use `try_new` for inputs.
+    pub fn new(
+        years: i64,
+        months: i64,
+        days: i64,
+        hours: i64,
+        minutes: i64,
+        seconds: i64,
+        microseconds: i64,
+    ) -> Self {
+        Self::try_new(years, months, days, hours, minutes, seconds, microseconds)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn years(&self) -> i64 {
         self.values.years
     }
 
-    pub fn months(&self) -> i32 {
+    pub fn months(&self) -> i64 {
         self.values.months
     }
 
-    pub fn days(&self) -> i32 {
+    pub fn days(&self) -> i64 {
         self.values.days
     }
 
-    pub fn hours(&self) -> i32 {
+    pub fn hours(&self) -> i64 {
         self.values.hours
     }
 
-    pub fn minutes(&self) -> i32 {
+    pub fn minutes(&self) -> i64 {
         self.values.minutes
     }
 
-    pub fn seconds(&self) -> i32 {
+    pub fn seconds(&self) -> i64 {
         self.values.seconds
     }
 
-    pub fn microseconds(&self) -> i32 {
+    pub fn microseconds(&self) -> i64 {
         self.values.microseconds
     }
+
+    /// Returns the delta as a fixed length duration.
+    /// Years and months are not fixed length, so this returns `None` if either of them is non-zero.
+    /// Also returns `None` if the delta exceeds the range of `Duration`.
+    pub fn as_duration(&self) -> Option<Duration> {
+        if self.years() == 0 && self.months() == 0 {
+            self.fixed_duration()
+        } else {
+            None
+        }
+    }
+
+    /// Returns the whole days of the delta, or `None` if years or months are non-zero.
+    pub fn total_days(&self) -> Option<i64> {
+        self.as_duration().map(|d| d.num_days())
+    }
+
+    /// Returns the whole seconds of the delta, or `None` if years or months are non-zero.
+    pub fn total_seconds(&self) -> Option<i64> {
+        self.as_duration().map(|d| d.num_seconds())
+    }
+
+    /// Returns the microseconds of the delta, or `None` if years or months are non-zero,
+    /// or if the result overflows `i64`.
+    pub fn total_microseconds(&self) -> Option<i64> {
+        self.as_duration().and_then(|d| d.num_microseconds())
+    }
+
+    /// Sums the fixed length fields, days and smaller, in microseconds.
+    fn fixed_microseconds(&self) -> i128 {
+        const MICROS_PER_SECOND: i128 = 1_000_000;
+
+        i128::from(self.days()) * 86_400 * MICROS_PER_SECOND
+            + i128::from(self.hours()) * 3_600 * MICROS_PER_SECOND
+            + i128::from(self.minutes()) * 60 * MICROS_PER_SECOND
+            + i128::from(self.seconds()) * MICROS_PER_SECOND
+            + i128::from(self.microseconds())
+    }
+
+    /// Sums the fixed length fields, days and smaller.
+    /// Returns `None` if the sum exceeds the range of `Duration`.
+    fn fixed_duration(&self) -> Option<Duration> {
+        let micros = self.fixed_microseconds();
+        let seconds = i64::try_from(micros.div_euclid(1_000_000)).ok()?;
+        if seconds.checked_abs()? > i64::MAX / 1_000 {
+            return None;
+        }
+        let subsec = micros.rem_euclid(1_000_000) as i64;
+        Duration::seconds(seconds).checked_add(&Duration::microseconds(subsec))
+    }
 }
 
 impl<Tz: TimeZone> ApplyDateTime<Tz> for TimeDelta {
-    fn apply_datetime(&self, target: DateTime<Tz>) -> Option<DateTime<Tz>> {
-        let duration = Duration::microseconds(i64::from(self.microseconds()))
-            + Duration::seconds(i64::from(self.seconds()))
-            + Duration::minutes(i64::from(self.minutes()))
-            + Duration::hours(i64::from(self.hours()))
-            + Duration::days(i64::from(self.days()));
+    fn apply_datetime(&self, target: DateTime<Tz>) -> Result<DateTime<Tz>, ApplyError> {
+        self.apply_datetime_with(target, false).map(|(dt, _)| dt)
+    }
+}
+
+impl TimeDelta {
+    /// Applies the delta like `apply_datetime`, but clamps the day to the last day of the resulting
+    /// month instead of failing, e.g. Jan 31 + 1 month is Feb 28, or Feb 29 in a leap year.
+    /// Unlike `apply_datetime_saturating`, a local time in a gap or an ambiguous one still fails.
+    /// The adjustment is returned along with the result, if the day is clamped.
+    pub fn apply_datetime_clamping_day<Tz: TimeZone>(
+        &self,
+        target: DateTime<Tz>,
+    ) -> Result<(DateTime<Tz>, Option<Adjustment>), ApplyError> {
+        self.apply_datetime_with(target, true)
+    }
+
+    fn apply_datetime_with<Tz: TimeZone>(
+        &self,
+        target: DateTime<Tz>,
+        clamp_day: bool,
+    ) -> Result<(DateTime<Tz>, Option<Adjustment>), ApplyError> {
+        let (applied, year, month) = self.shift(target)?;
+        if (year, month) == (applied.year(), applied.month()) {
+            // the local time is not composed again, which keeps the instant of an ambiguous time.
+            return Ok((applied, None));
+        }
+
+        let day = applied.day();
+        let last_day = last_day_of_month(year, month).unwrap_or(0);
+        let adjustment = if day <= last_day {
+            None
+        } else if clamp_day {
+            Some(Adjustment::MonthEnd { day, last_day })
+        } else {
+            return Err(ApplyError::NonexistentDate { year, month, day });
+        };
+
+        let local = NaiveDate::from_ymd_opt(year, month, day.min(last_day))
+            .ok_or(ApplyError::NonexistentDate { year, month, day })?
+            .and_time(applied.time());
+        let dt = applied
+            .timezone()
+            .from_local_datetime(&local)
+            .single()
+            .ok_or_else(|| ApplyError::InvalidComposition(local.to_string()))?;
+        Ok((dt, adjustment))
+    }
+
+    /// Applies the fixed length fields to the target, and computes the year and month
+    /// which the calendar fields move it to.
+    fn shift<Tz: TimeZone>(
+        &self,
+        target: DateTime<Tz>,
+    ) -> Result<(DateTime<Tz>, i32, u32), ApplyError> {
+        let out_of_range =
+            || ApplyError::OutOfRange(self.to_short_string(), format!("{:?}", target));
+
+        let applied = self
+            .fixed_duration()
+            .and_then(|d| target.clone().checked_add_signed(d))
+            .ok_or_else(out_of_range)?;
+
+        let (year, month) = self
+            .years()
+            .checked_mul(12)
+            .and_then(|months| months.checked_add(self.months()))
+            .and_then(|months| shift_year_month(&applied, months))
+            .filter(|(year, month)| last_day_of_month(*year, *month).is_some())
+            .ok_or_else(out_of_range)?;
+        Ok((applied, year, month))
+    }
+
+    /// Applies the delta like `apply_datetime`, but clamps the result to the nearest valid datetime
+    /// instead of failing: the day is clamped to the last day of the resulting month,
+    /// and a local time in a gap is resolved by `resolve_local_datetime`, to the first valid time after it.
+    /// An ambiguous local time is resolved to the earliest one. The adjustments made are returned along with the result.
+    /// Returns an error only if the result is out of the range of `DateTime`.
+    pub fn apply_datetime_saturating<Tz: TimeZone>(
+        &self,
+        target: DateTime<Tz>,
+    ) -> Result<(DateTime<Tz>, Vec<Adjustment>), ApplyError> {
+        let out_of_range =
+            || ApplyError::OutOfRange(self.to_short_string(), format!("{:?}", target));
+        let (applied, year, month) = self.shift(target.clone())?;
+        if (year, month) == (applied.year(), applied.month()) {
+            return Ok((applied, Vec::new()));
+        }
+
+        let mut adjustments = Vec::new();
+        let day = applied.day();
+        let last_day = last_day_of_month(year, month).ok_or_else(out_of_range)?;
+        if day > last_day {
+            adjustments.push(Adjustment::MonthEnd { day, last_day });
+        }
+        let local = NaiveDate::from_ymd_opt(year, month, day.min(last_day))
+            .ok_or_else(out_of_range)?
+            .and_time(applied.time());
 
-        let duration_applied: DateTime<Tz> = target + duration;
+        let tz = applied.timezone();
+        if let LocalResult::None = tz.from_local_datetime(&local) {
+            adjustments.push(Adjustment::Gap);
+        }
+        Ok((resolve_local_datetime(&tz, local), adjustments))
+    }
+}
+
+/// Returns the last day of the month, or `None` if the month is out of range.
+fn last_day_of_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 {
+        (year.checked_add(1)?, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+}
+
+/// Returns the year and month moved from the datetime by the months,
+/// or `None` if the year exceeds the range of `i32`.
+fn shift_year_month<Tz: TimeZone>(dt: &DateTime<Tz>, months: i64) -> Option<(i32, u32)> {
+    let month0 = i64::from(dt.month0()).checked_add(months)?;
+    let year = i32::try_from(i64::from(dt.year()) + month0.div_euclid(12)).ok()?;
+    Some((year, month0.rem_euclid(12) as u32 + 1))
+}
+
+/// Moves the datetime by the months, keeping its day of month.
+/// Returns `None` if the day does not exist in the resulting month.
+fn shift_months<Tz: TimeZone>(dt: DateTime<Tz>, months: i64) -> Option<DateTime<Tz>> {
+    let (year, month) = shift_year_month(&dt, months)?;
+    let local = NaiveDate::from_ymd_opt(year, month, dt.day())?.and_time(dt.time());
+    dt.timezone().from_local_datetime(&local).single()
+}
+
+impl TimeDelta {
+    /// Computes the calendar difference from `a` to `b`, so that applying the result to `a` yields `b`.
+    ///
+    /// The result has as many whole months as possible, and the rest in days and time.
+    /// As months are applied keeping the day of month, a month is counted only if
+    /// the day of `b` exists in the month it is counted from,
+    /// e.g. Jan 31 to Feb 28 is `28 days`, and Jan 31 to Mar 31 is `2 months`.
+    /// If `b` is before `a`, all fields of the result are zero or negative.
+    /// Sub-microsecond differences are truncated.
+    pub fn between<Tz: TimeZone>(a: &DateTime<Tz>, b: &DateTime<Tz>) -> TimeDelta {
+        let month_index = |dt: &DateTime<Tz>| i64::from(dt.year()) * 12 + i64::from(dt.month0());
+        let forward = a <= b;
+        let step = if forward { -1 } else { 1 };
+
+        // find the largest months from which the rest is a fixed length duration in the same direction.
+        let mut months = month_index(b) - month_index(a);
+        let rest = loop {
+            match shift_months(b.clone(), -months) {
+                Some(dt) if (a <= &dt) == forward || dt == *a => break dt,
+                _ => months += step,
+            }
+        };
+
+        let duration = rest.signed_duration_since(a.clone());
+        let days = duration.num_days();
+        let micros = (duration - Duration::days(days))
+            .num_microseconds()
+            .expect("less than a day must fit in microseconds");
+        TimeDelta::try_new(0, months, days, 0, 0, 0, micros)
+            .expect("months and days between datetimes must fit in the fields")
+    }
+}
 
-        let delta_months = self.years() * 12 + self.months();
-        let sum_months = duration_applied.month() as i32 + delta_months;
+impl FromStr for TimeDelta {
+    type Err = TimeDeltaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_iso8601(s) {
+            return parse_iso8601(s)
+                .map_err(|e| TimeDeltaError::WrongItem(s.to_string(), e))?
+                .into_iter()
+                .fold(TimeDeltaBuilder::default(), |b, d| {
+                    d.apply_timedelta_builder(b)
+                })
+                .try_build();
+        }
+
+        let items = split_items(s).map_err(|e| TimeDeltaError::WrongItem(s.to_string(), e))?;
+        items
+            .into_iter()
+            .try_fold(TimeDeltaBuilder::default(), |b, item| {
+                if let Some(us) = microseconds_item(item) {
+                    return Ok(b.add_microseconds(us));
+                }
+                apply_item(item, b).map_err(|e| TimeDeltaError::WrongItem(item.to_string(), e))
+            })?
+            .try_build()
+    }
+}
 
-        let delta_years = if sum_months > 0 {
-            (sum_months - 1) / 12
+/// Parses an item of microseconds like `7us`, which `to_short_string` renders.
+/// It is not a unit of deltas, since datetimes are in milliseconds at most.
+fn microseconds_item(item: &str) -> Option<i64> {
+    item.strip_suffix("us")
+        .and_then(|value| value.parse::<i64>().ok())
+}
+
+impl TimeDelta {
+    /// Renders non-zero fields in compact form like `1y2mon3d4h5min6s7ms`, which can be parsed back.
+    /// Sub-second values are rendered in `ms` if possible, otherwise in `us`.
+    pub fn to_short_string(self) -> String {
+        let (subsec, subsec_unit) = if self.microseconds() % 1000 == 0 {
+            (self.microseconds() / 1000, "ms")
         } else {
-            (sum_months / 12) - 1
+            (self.microseconds(), "us")
         };
-        let result_year = duration_applied.year() + delta_years;
+        let fields = [
+            (self.years(), "y"),
+            (self.months(), "mon"),
+            (self.days(), "d"),
+            (self.hours(), "h"),
+            (self.minutes(), "min"),
+            (self.seconds(), "s"),
+            (subsec, subsec_unit),
+        ];
+
+        let s: String = fields
+            .iter()
+            .filter(|(value, _)| *value != 0)
+            .map(|(value, unit)| format!("{}{}", value, unit))
+            .collect();
+        if s.is_empty() {
+            "0s".to_string()
+        } else {
+            s
+        }
+    }
+}
+
+/// Serialized in the compact form of `to_short_string`, including microseconds like `7us`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TimeDelta {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_short_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TimeDelta {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        TimeDelta::from_str(&s).map_err(serde::de::Error::custom)
+    }
+}
 
-        let result_month = if sum_months > 0 {
-            ((sum_months - 1) % 12) + 1
+/// Renders the delta like `1 year 2 months 3 days 04:05:06.000007`, omitting zero components.
+/// Years and months, and the fixed length fields are normalized separately,
+/// so a negative delta has a single leading minus.
+/// If the two groups have different signs, the fixed length group has its own sign,
+/// like `1 month -22:00:00`.
+impl fmt::Display for TimeDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const MICROS_PER_SECOND: i128 = 1_000_000;
+        const MICROS_PER_DAY: i128 = 86_400 * MICROS_PER_SECOND;
+
+        let months = i128::from(self.years()) * 12 + i128::from(self.months());
+        let micros = self.fixed_microseconds();
+
+        let negative = months < 0 || (months == 0 && micros < 0);
+        if negative {
+            write!(f, "-")?;
+        }
+        let months = months.abs();
+        let (micros, time_sign) = if (micros < 0) == negative || micros == 0 {
+            (micros.abs(), "")
         } else {
-            (sum_months % 12) + 12
-        } as u32;
+            (micros.abs(), "-")
+        };
+
+        let plural = |value: i128, unit: &str| {
+            format!("{} {}{}", value, unit, if value == 1 { "" } else { "s" })
+        };
+
+        let mut parts = Vec::new();
+        if months / 12 != 0 {
+            parts.push(plural(months / 12, "year"));
+        }
+        if months % 12 != 0 {
+            parts.push(plural(months % 12, "month"));
+        }
 
-        duration_applied
-            .with_year(result_year)
-            .and_then(|dt| dt.with_month(result_month))
+        let mut fixed_parts = Vec::new();
+        if micros / MICROS_PER_DAY != 0 {
+            fixed_parts.push(plural(micros / MICROS_PER_DAY, "day"));
+        }
+        let time = micros % MICROS_PER_DAY;
+        if time != 0 || (parts.is_empty() && fixed_parts.is_empty()) {
+            let secs = time / MICROS_PER_SECOND;
+            let mut s = format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+            if time % MICROS_PER_SECOND != 0 {
+                s.push_str(&format!(".{:06}", time % MICROS_PER_SECOND));
+            }
+            fixed_parts.push(s);
+        }
+        if let Some(first) = fixed_parts.first_mut() {
+            first.insert_str(0, time_sign);
+        }
+        parts.extend(fixed_parts);
+
+        write!(f, "{}", parts.join(" "))
     }
 }
 
+/// Builds a delta without normalization.
+/// `add_*` methods saturate at the range of `i64`, and `try_build` reports the overflow.
+#[derive(Debug, Copy, Clone)]
 pub struct TimeDeltaBuilder {
     values: DeltaValues,
+    overflow: Option<&'static str>,
 }
 
 impl Default for TimeDeltaBuilder {
@@ -142,125 +525,187 @@ impl Default for TimeDeltaBuilder {
                 seconds: 0,
                 microseconds: 0,
             },
+            overflow: None,
         }
     }
 }
 
 impl TimeDeltaBuilder {
-    pub fn years(mut self, value: i32) -> Self {
+    pub fn years(mut self, value: i64) -> Self {
         self.values.years = value;
         self
     }
 
-    pub fn add_years(self, value: i32) -> Self {
-        let y = self.values.years + value;
+    pub fn add_years(mut self, value: i64) -> Self {
+        let y = self.add_value("years", self.values.years, value);
         self.years(y)
     }
 
-    pub fn months(mut self, value: i32) -> Self {
+    pub fn months(mut self, value: i64) -> Self {
         self.values.months = value;
         self
     }
 
-    pub fn add_months(self, value: i32) -> Self {
-        let m = self.values.months + value;
+    pub fn add_months(mut self, value: i64) -> Self {
+        let m = self.add_value("months", self.values.months, value);
         self.months(m)
     }
 
-    pub fn days(mut self, d: i32) -> Self {
+    /// Sets the days to the weeks multiplied by 7.
+    pub fn weeks(mut self, value: i64) -> Self {
+        let d = self.weeks_to_days(value);
+        self.days(d)
+    }
+
+    /// Adds the weeks multiplied by 7 to the days.
+    pub fn add_weeks(mut self, value: i64) -> Self {
+        let d = self.weeks_to_days(value);
+        self.add_days(d)
+    }
+
+    pub fn days(mut self, d: i64) -> Self {
         self.values.days = d;
         self
     }
 
-    pub fn add_days(self, value: i32) -> Self {
-        let d = self.values.days + value;
+    pub fn add_days(mut self, value: i64) -> Self {
+        let d = self.add_value("days", self.values.days, value);
         self.days(d)
     }
 
-    pub fn hours(mut self, h: i32) -> Self {
+    pub fn hours(mut self, h: i64) -> Self {
         self.values.hours = h;
         self
     }
 
-    pub fn add_hours(self, value: i32) -> Self {
-        let h = self.values.hours + value;
+    pub fn add_hours(mut self, value: i64) -> Self {
+        let h = self.add_value("hours", self.values.hours, value);
         self.hours(h)
     }
 
-    pub fn minutes(mut self, m: i32) -> Self {
+    pub fn minutes(mut self, m: i64) -> Self {
         self.values.minutes = m;
         self
     }
 
-    pub fn add_minutes(self, value: i32) -> Self {
-        let m = self.values.minutes + value;
+    pub fn add_minutes(mut self, value: i64) -> Self {
+        let m = self.add_value("minutes", self.values.minutes, value);
         self.minutes(m)
     }
 
-    pub fn seconds(mut self, s: i32) -> Self {
+    pub fn seconds(mut self, s: i64) -> Self {
         self.values.seconds = s;
         self
     }
 
-    pub fn add_seconds(self, value: i32) -> Self {
-        let s = self.values.seconds + value;
+    pub fn add_seconds(mut self, value: i64) -> Self {
+        let s = self.add_value("seconds", self.values.seconds, value);
         self.seconds(s)
     }
 
-    #[allow(dead_code)]
-    pub fn milliseconds(self, value: i32) -> Self {
+    pub fn milliseconds(self, value: i64) -> Self {
         let s = value / 1000;
         let us = (value % 1000) * 1000;
         self.seconds(s).microseconds(us)
     }
 
-    pub fn add_milliseconds(self, value: i32) -> Self {
+    pub fn add_milliseconds(self, value: i64) -> Self {
         let s = value / 1000;
         let us = (value % 1000) * 1000;
 
         self.add_seconds(s).add_microseconds(us)
     }
 
-    pub fn microseconds(mut self, value: i32) -> Self {
+    pub fn microseconds(mut self, value: i64) -> Self {
         self.values.microseconds = value;
         self
     }
 
-    pub fn add_microseconds(self, value: i32) -> Self {
-        let us = self.values.microseconds + value;
+    pub fn add_microseconds(mut self, value: i64) -> Self {
+        let us = self.add_value("microseconds", self.values.microseconds, value);
         self.microseconds(us)
     }
 
+    /// Sets years, months and days at once.
+    pub fn ymd(self, years: i64, months: i64, days: i64) -> Self {
+        self.years(years).months(months).days(days)
+    }
+
+    /// Adds years, months and days at once.
+    pub fn add_ymd(self, years: i64, months: i64, days: i64) -> Self {
+        self.add_years(years).add_months(months).add_days(days)
+    }
+
+    /// Sets hours, minutes and seconds at once.
+    pub fn hms(self, hours: i64, minutes: i64, seconds: i64) -> Self {
+        self.hours(hours).minutes(minutes).seconds(seconds)
+    }
+
+    /// Adds hours, minutes and seconds at once.
+    pub fn add_hms(self, hours: i64, minutes: i64, seconds: i64) -> Self {
+        self.add_hours(hours)
+            .add_minutes(minutes)
+            .add_seconds(seconds)
+    }
+
+    /// Sets hours, minutes, seconds and microseconds at once.
+    pub fn hms_micro(self, hours: i64, minutes: i64, seconds: i64, microseconds: i64) -> Self {
+        self.hms(hours, minutes, seconds).microseconds(microseconds)
+    }
+
+    /// Adds hours, minutes, seconds and microseconds at once.
+    pub fn add_hms_micro(self, hours: i64, minutes: i64, seconds: i64, microseconds: i64) -> Self {
+        self.add_hms(hours, minutes, seconds)
+            .add_microseconds(microseconds)
+    }
+
     pub fn build(self) -> TimeDelta {
         TimeDelta {
             values: self.values,
         }
     }
+
+    pub fn try_build(self) -> Result<TimeDelta, TimeDeltaError> {
+        match self.overflow {
+            Some(field) => Err(TimeDeltaError::Overflow(field)),
+            None => Ok(self.build()),
+        }
+    }
+
+    fn weeks_to_days(&mut self, value: i64) -> i64 {
+        value.checked_mul(7).unwrap_or_else(|| {
+            self.overflow.get_or_insert("days");
+            value.saturating_mul(7)
+        })
+    }
+
+    fn add_value(&mut self, field: &'static str, current: i64, value: i64) -> i64 {
+        current.checked_add(value).unwrap_or_else(|| {
+            self.overflow.get_or_insert(field);
+            current.saturating_add(value)
+        })
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 struct DeltaValues {
-    years: i32,
-    months: i32,
-    days: i32,
-    hours: i32,
-    minutes: i32,
-    seconds: i32,
-    microseconds: i32,
-}
-
-#[allow(dead_code)]
-fn sign_of(x: i32) -> i32 {
-    if x > 0 {
-        1
-    } else {
-        -1
-    }
+    years: i64,
+    months: i64,
+    days: i64,
+    hours: i64,
+    minutes: i64,
+    seconds: i64,
+    microseconds: i64,
+}
+
+/// Moves whole units of `lower` into `upper`. The division truncates toward zero,
+/// so the remainder keeps the sign of `lower`, and a zero `lower` carries nothing.
+fn carry(upper: i128, lower: i128, base: i128) -> (i128, i128) {
+    (upper + lower / base, lower % base)
 }
 
-#[allow(dead_code)]
-fn div_mod(x: i32, y: i32) -> (i32, i32) {
-    (x / y, x % y)
+fn narrow(value: i128, field: &'static str) -> Result<i64, TimeDeltaError> {
+    i64::try_from(value).map_err(|_| TimeDeltaError::Overflow(field))
 }
 
 #[cfg(test)]
@@ -268,7 +713,7 @@ mod time_delta_tests {
     use chrono::offset::TimeZone;
     use chrono::Utc;
 
-    use super::{ApplyDateTime, TimeDelta, TimeDeltaBuilder};
+    use super::{ApplyDateTime, ApplyError, TimeDelta, TimeDeltaBuilder};
 
     #[test]
     fn time_delta_new_basics() {
@@ -464,7 +909,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .microseconds(111_222)
                 .build()
-                .apply_datetime(date.and_hms_micro(0, 0, 0, 12_234)),
+                .apply_datetime(date.and_hms_micro(0, 0, 0, 12_234))
+                .ok(),
             Some(date.and_hms_micro(0, 0, 0, 123_456))
         );
 
@@ -472,7 +918,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .microseconds(999_999)
                 .build()
-                .apply_datetime(date.and_hms_micro(0, 0, 0, 1)),
+                .apply_datetime(date.and_hms_micro(0, 0, 0, 1))
+                .ok(),
             Some(date.and_hms_micro(0, 0, 1, 0))
         );
 
@@ -481,7 +928,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .microseconds(-1)
                 .build()
-                .apply_datetime(date.and_hms_micro(0, 0, 0, 1)),
+                .apply_datetime(date.and_hms_micro(0, 0, 0, 1))
+                .ok(),
             Some(date.and_hms_micro(0, 0, 0, 0))
         );
 
@@ -489,7 +937,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .microseconds(-1)
                 .build()
-                .apply_datetime(date.and_hms_micro(0, 0, 0, 0)),
+                .apply_datetime(date.and_hms_micro(0, 0, 0, 0))
+                .ok(),
             Some(Utc.ymd(0, 12, 31).and_hms_micro(23, 59, 59, 999_999))
         );
 
@@ -498,11 +947,98 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .microseconds(-1)
                 .build()
-                .apply_datetime(date.and_hms_micro(0, 0, 0, 0)),
+                .apply_datetime(date.and_hms_micro(0, 0, 0, 0))
+                .ok(),
             Some(Utc.ymd(-1, 12, 31).and_hms_micro(23, 59, 59, 999_999))
         );
     }
 
+    #[test]
+    fn time_delta_apply_large_values() {
+        // 70 years in seconds, which exceeds the range of i32.
+        let seconds = 2_208_988_800;
+        let delta = TimeDeltaBuilder::default().seconds(seconds).build();
+        assert_eq!(
+            delta.apply_datetime(Utc.ymd(1970, 1, 1).and_hms(0, 0, 0)),
+            Ok(Utc.ymd(2040, 1, 1).and_hms(0, 0, 0))
+        );
+
+        let delta = TimeDeltaBuilder::default().seconds(-seconds).build();
+        assert_eq!(
+            delta.apply_datetime(Utc.ymd(2040, 1, 1).and_hms(0, 0, 0)),
+            Ok(Utc.ymd(1970, 1, 1).and_hms(0, 0, 0))
+        );
+
+        let delta = TimeDeltaBuilder::default()
+            .add_milliseconds(4_000_000_000_000)
+            .add_microseconds(5_000_000_000_000)
+            .build();
+        assert_eq!(
+            delta.apply_datetime(Utc.ymd(2000, 1, 1).and_hms(0, 0, 0)),
+            Ok(Utc.ymd(2000, 1, 1).and_hms(0, 0, 0) + chrono::Duration::seconds(4_005_000_000))
+        );
+
+        let delta = TimeDelta::new(0, 0, 0, 0, 0, 3_000_000_000, 0);
+        assert_eq!(
+            (
+                delta.days(),
+                delta.hours(),
+                delta.minutes(),
+                delta.seconds()
+            ),
+            (34_722, 5, 20, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_add_milliseconds() {
+        assert_eq!(
+            TimeDeltaBuilder::default().add_milliseconds(-500).build(),
+            TimeDelta::new(0, 0, 0, 0, 0, 0, -500_000)
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default().add_milliseconds(-1500).build(),
+            TimeDelta::new(0, 0, 0, 0, 0, 0, -1_500_000)
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default().add_milliseconds(1500).build(),
+            TimeDelta::new(0, 0, 0, 0, 0, 0, 1_500_000)
+        );
+    }
+
+    #[test]
+    fn time_delta_apply_milliseconds() {
+        let date = Utc.ymd(2019, 6, 12);
+        let apply = |ms| {
+            TimeDeltaBuilder::default()
+                .add_milliseconds(ms)
+                .build()
+                .apply_datetime(date.and_hms(0, 0, 0))
+                .ok()
+        };
+
+        assert_eq!(
+            apply(-500),
+            Some(Utc.ymd(2019, 6, 11).and_hms_milli(23, 59, 59, 500))
+        );
+        assert_eq!(
+            apply(-1500),
+            Some(Utc.ymd(2019, 6, 11).and_hms_milli(23, 59, 58, 500))
+        );
+        assert_eq!(apply(1500), Some(date.and_hms_milli(0, 0, 1, 500)));
+
+        // mixed signs
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_seconds(1)
+                .add_milliseconds(-500)
+                .build()
+                .apply_datetime(date.and_hms(0, 0, 0))
+                .ok(),
+            Some(date.and_hms_milli(0, 0, 0, 500))
+        );
+    }
+
     #[test]
     fn time_delta_apply_seconds() {
         let date = Utc.ymd(2019, 6, 12);
@@ -512,7 +1048,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .seconds(1)
                 .build()
-                .apply_datetime(date.and_hms(0, 0, 58)),
+                .apply_datetime(date.and_hms(0, 0, 58))
+                .ok(),
             Some(date.and_hms(0, 0, 59))
         );
 
@@ -520,7 +1057,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .seconds(2)
                 .build()
-                .apply_datetime(date.and_hms(0, 0, 58)),
+                .apply_datetime(date.and_hms(0, 0, 58))
+                .ok(),
             Some(date.and_hms(0, 1, 0))
         );
 
@@ -529,7 +1067,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .seconds(-1)
                 .build()
-                .apply_datetime(date.and_hms(0, 0, 1)),
+                .apply_datetime(date.and_hms(0, 0, 1))
+                .ok(),
             Some(date.and_hms(0, 0, 0))
         );
 
@@ -537,7 +1076,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .seconds(-1)
                 .build()
-                .apply_datetime(date.and_hms(0, 0, 0)),
+                .apply_datetime(date.and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 11).and_hms(23, 59, 59))
         );
     }
@@ -551,7 +1091,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .minutes(1)
                 .build()
-                .apply_datetime(date.and_hms(0, 58, 0)),
+                .apply_datetime(date.and_hms(0, 58, 0))
+                .ok(),
             Some(date.and_hms(0, 59, 0))
         );
 
@@ -559,7 +1100,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .minutes(1)
                 .build()
-                .apply_datetime(date.and_hms(0, 59, 0)),
+                .apply_datetime(date.and_hms(0, 59, 0))
+                .ok(),
             Some(date.and_hms(1, 0, 0))
         );
 
@@ -568,7 +1110,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .minutes(-1)
                 .build()
-                .apply_datetime(date.and_hms(0, 1, 0)),
+                .apply_datetime(date.and_hms(0, 1, 0))
+                .ok(),
             Some(date.and_hms(0, 0, 0))
         );
 
@@ -576,7 +1119,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .minutes(-2)
                 .build()
-                .apply_datetime(date.and_hms(0, 1, 0)),
+                .apply_datetime(date.and_hms(0, 1, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 11).and_hms(23, 59, 0))
         );
     }
@@ -590,7 +1134,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .hours(1)
                 .build()
-                .apply_datetime(date.and_hms(22, 0, 0)),
+                .apply_datetime(date.and_hms(22, 0, 0))
+                .ok(),
             Some(date.and_hms(23, 0, 0))
         );
 
@@ -598,7 +1143,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .hours(2)
                 .build()
-                .apply_datetime(date.and_hms(22, 0, 0)),
+                .apply_datetime(date.and_hms(22, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 13).and_hms(0, 0, 0))
         );
 
@@ -607,7 +1153,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .hours(-1)
                 .build()
-                .apply_datetime(date.and_hms(1, 0, 0)),
+                .apply_datetime(date.and_hms(1, 0, 0))
+                .ok(),
             Some(date.and_hms(0, 0, 0))
         );
 
@@ -615,7 +1162,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .hours(-2)
                 .build()
-                .apply_datetime(date.and_hms(1, 0, 0)),
+                .apply_datetime(date.and_hms(1, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 11).and_hms(23, 0, 0))
         );
     }
@@ -627,7 +1175,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(28)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 30).and_hms(0, 0, 0))
         );
 
@@ -635,7 +1184,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(29)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 7, 1).and_hms(0, 0, 0))
         );
 
@@ -643,7 +1193,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(28)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 3, 1).and_hms(0, 0, 0))
         );
 
@@ -652,7 +1203,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(-1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 6, 1).and_hms(0, 0, 0))
         );
 
@@ -660,7 +1212,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(-2)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 6, 2).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 5, 31).and_hms(0, 0, 0))
         );
 
@@ -668,7 +1221,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .days(-1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 3, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 3, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 2, 28).and_hms(0, 0, 0))
         );
     }
@@ -680,7 +1234,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 11, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 11, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 12, 1).and_hms(0, 0, 0))
         );
 
@@ -688,7 +1243,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(2)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 11, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 11, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0))
         );
 
@@ -696,7 +1252,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(2)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 10, 31).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 10, 31).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 12, 31).and_hms(0, 0, 0))
         );
 
@@ -705,7 +1262,11 @@ mod time_delta_tests {
                 .months(1)
                 .build()
                 .apply_datetime(Utc.ymd(2019, 10, 31).and_hms(0, 0, 0)),
-            None
+            Err(ApplyError::NonexistentDate {
+                year: 2019,
+                month: 11,
+                day: 31
+            })
         );
 
         // minus
@@ -713,7 +1274,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(-1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0))
         );
 
@@ -721,7 +1283,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(-2)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 2, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2018, 12, 1).and_hms(0, 0, 0))
         );
 
@@ -729,7 +1292,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .months(-1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 1, 31).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 1, 31).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2018, 12, 31).and_hms(0, 0, 0))
         );
 
@@ -738,7 +1302,30 @@ mod time_delta_tests {
                 .months(-2)
                 .build()
                 .apply_datetime(Utc.ymd(2019, 1, 31).and_hms(0, 0, 0)),
-            None
+            Err(ApplyError::NonexistentDate {
+                year: 2018,
+                month: 11,
+                day: 31
+            })
+        );
+
+        // leap day
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .months(11)
+                .build()
+                .apply_datetime(Utc.ymd(2020, 2, 29).and_hms(0, 0, 0))
+                .ok(),
+            Some(Utc.ymd(2021, 1, 29).and_hms(0, 0, 0))
+        );
+
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .months(-2)
+                .build()
+                .apply_datetime(Utc.ymd(2020, 2, 29).and_hms(0, 0, 0))
+                .ok(),
+            Some(Utc.ymd(2019, 12, 29).and_hms(0, 0, 0))
         );
     }
 
@@ -749,7 +1336,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .years(1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2020, 1, 1).and_hms(0, 0, 0))
         );
 
@@ -758,7 +1346,11 @@ mod time_delta_tests {
                 .years(1)
                 .build()
                 .apply_datetime(Utc.ymd(2020, 2, 29).and_hms(0, 0, 0)),
-            None
+            Err(ApplyError::NonexistentDate {
+                year: 2021,
+                month: 2,
+                day: 29
+            })
         );
 
         // minus
@@ -766,7 +1358,8 @@ mod time_delta_tests {
             TimeDeltaBuilder::default()
                 .years(-1)
                 .build()
-                .apply_datetime(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)),
+                .apply_datetime(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0))
+                .ok(),
             Some(Utc.ymd(2018, 1, 1).and_hms(0, 0, 0))
         );
 
@@ -775,28 +1368,1130 @@ mod time_delta_tests {
                 .years(-1)
                 .build()
                 .apply_datetime(Utc.ymd(2020, 2, 29).and_hms(0, 0, 0)),
-            None
+            Err(ApplyError::NonexistentDate {
+                year: 2019,
+                month: 2,
+                day: 29
+            })
         );
     }
 }
 
 #[cfg(test)]
-mod builder_tests {
-    use super::{TimeDelta, TimeDeltaBuilder};
+mod overflow_tests {
+    use chrono::offset::TimeZone;
+    use chrono::Utc;
+
+    use super::{ApplyDateTime, ApplyError, TimeDelta, TimeDeltaBuilder, TimeDeltaError};
+
+    const MAX: i64 = i64::MAX;
+    const MIN: i64 = i64::MIN;
+
+    type AddFn = fn(TimeDeltaBuilder, i64) -> TimeDeltaBuilder;
 
     #[test]
-    fn time_delta_builder() {
+    fn time_delta_try_new_boundaries() {
+        // fields below days are remainders, so they never overflow.
+        let delta = TimeDelta::try_new(0, 0, 0, MAX, MAX, MAX, MAX).unwrap();
+        assert_eq!(delta.microseconds(), 775_807);
+        let delta = TimeDelta::try_new(0, 0, 0, MIN, MIN, MIN, MIN).unwrap();
+        assert_eq!(delta.microseconds(), -775_808);
+
+        assert!(TimeDelta::try_new(0, 0, MAX, 23, 59, 59, 999_999).is_ok());
         assert_eq!(
-            TimeDeltaBuilder::default()
-                .years(2019)
-                .months(6)
-                .days(10)
-                .hours(20)
-                .minutes(12)
-                .seconds(34)
-                .microseconds(56)
-                .build(),
-            TimeDelta::new(2019, 6, 10, 20, 12, 34, 56)
+            TimeDelta::try_new(0, 0, MAX, 23, 59, 59, 1_000_000),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+        assert!(TimeDelta::try_new(0, 0, MIN, -23, -59, -59, -999_999).is_ok());
+        assert_eq!(
+            TimeDelta::try_new(0, 0, MIN, -24, 0, 0, 0),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+
+        assert!(TimeDelta::try_new(MAX, 11, 0, 0, 0, 0, 0).is_ok());
+        assert_eq!(
+            TimeDelta::try_new(MAX, 12, 0, 0, 0, 0, 0),
+            Err(TimeDeltaError::Overflow("years"))
+        );
+        assert!(TimeDelta::try_new(MIN, -11, 0, 0, 0, 0, 0).is_ok());
+        assert_eq!(
+            TimeDelta::try_new(MIN, MIN, 0, 0, 0, 0, 0),
+            Err(TimeDeltaError::Overflow("years"))
+        );
+    }
+
+    #[test]
+    fn time_delta_try_new_i32_carries() {
+        let (max, min) = (i64::from(i32::MAX), i64::from(i32::MIN));
+        let fields = |d: TimeDelta| {
+            (
+                d.days(),
+                d.hours(),
+                d.minutes(),
+                d.seconds(),
+                d.microseconds(),
+            )
+        };
+
+        // microseconds carry into seconds and minutes.
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, 0, max).unwrap();
+        assert_eq!(fields(delta), (0, 0, 35, 47, 483_647));
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, 0, min).unwrap();
+        assert_eq!(fields(delta), (0, 0, -35, -47, -483_648));
+
+        // seconds carry up to days.
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, max, 0).unwrap();
+        assert_eq!(fields(delta), (24_855, 3, 14, 7, 0));
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, min, 0).unwrap();
+        assert_eq!(fields(delta), (-24_855, -3, -14, -8, 0));
+
+        // the carried amount is added with its sign, and a zero carry leaves the field as it is.
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, max, min).unwrap();
+        assert_eq!(fields(delta), (24_855, 2, 38, 20, -483_648));
+        let delta = TimeDelta::try_new(0, 0, 0, 0, 0, 0, -1).unwrap();
+        assert_eq!(fields(delta), (0, 0, 0, 0, -1));
+
+        // the same carries next to the bounds of days.
+        let delta = TimeDelta::try_new(0, 0, MAX - 24_855, 0, 0, max, min).unwrap();
+        assert_eq!(delta.days(), MAX);
+        assert_eq!(
+            TimeDelta::try_new(0, 0, MAX - 24_854, 0, 0, max, 0),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+        assert_eq!(
+            TimeDelta::try_new(0, 0, MIN + 24_854, 0, 0, min, min),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+    }
+
+    #[test]
+    fn time_delta_builder_boundaries() {
+        let builders: Vec<(&str, AddFn)> = vec![
+            ("years", TimeDeltaBuilder::add_years),
+            ("months", TimeDeltaBuilder::add_months),
+            ("days", TimeDeltaBuilder::add_days),
+            ("hours", TimeDeltaBuilder::add_hours),
+            ("minutes", TimeDeltaBuilder::add_minutes),
+            ("seconds", TimeDeltaBuilder::add_seconds),
+            ("microseconds", TimeDeltaBuilder::add_microseconds),
+        ];
+
+        for (field, add) in builders {
+            assert!(add(add(TimeDeltaBuilder::default(), MAX), 0)
+                .try_build()
+                .is_ok());
+            assert!(add(add(TimeDeltaBuilder::default(), MIN), 0)
+                .try_build()
+                .is_ok());
+            assert_eq!(
+                add(add(TimeDeltaBuilder::default(), MAX), 1).try_build(),
+                Err(TimeDeltaError::Overflow(field))
+            );
+            assert_eq!(
+                add(add(TimeDeltaBuilder::default(), MIN), -1).try_build(),
+                Err(TimeDeltaError::Overflow(field))
+            );
+        }
+
+        // saturates on build.
+        let delta = TimeDeltaBuilder::default()
+            .add_days(MAX)
+            .add_days(MAX)
+            .build();
+        assert_eq!(delta.days(), MAX);
+
+        let delta = TimeDeltaBuilder::default()
+            .add_milliseconds(MIN)
+            .try_build()
+            .unwrap();
+        assert_eq!(delta.seconds(), -9_223_372_036_854_775);
+        assert_eq!(delta.microseconds(), -808_000);
+    }
+
+    #[test]
+    fn time_delta_apply_out_of_range() {
+        let dt = Utc.ymd(2019, 6, 12).and_hms(0, 0, 0);
+        let apply = |delta: TimeDelta| delta.apply_datetime(dt);
+
+        for delta in [
+            TimeDelta::new(MAX, 0, 0, 0, 0, 0, 0),
+            TimeDelta::new(MIN, 0, 0, 0, 0, 0, 0),
+            TimeDelta::new(0, MAX, 0, 0, 0, 0, 0),
+            TimeDelta::new(0, 0, MAX, 0, 0, 0, 0),
+            TimeDelta::new(0, 0, MIN, 0, 0, 0, 0),
+            TimeDelta::new(0, 0, 0, MAX, MAX, MAX, MAX),
+            TimeDelta::new(0, 0, 0, 0, 0, MIN, 0),
+        ] {
+            match apply(delta) {
+                Err(ApplyError::OutOfRange(s, _)) => assert_eq!(s, delta.to_short_string()),
+                r => panic!("unexpected result: {:?}", r),
+            }
+        }
+
+        let (max, min) = (i64::from(i32::MAX), i64::from(i32::MIN));
+        assert!(apply(TimeDelta::new(0, 0, 0, max, max, max, max)).is_ok());
+        assert!(apply(TimeDelta::new(0, 0, 0, min, min, min, min)).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod saturating_tests {
+    use chrono::offset::TimeZone;
+    use chrono::{NaiveDate, Utc};
+
+    use super::{Adjustment, ApplyDateTime, ApplyError, TimeDelta, TimeDeltaBuilder};
+    use crate::datetime::test_zones::{FallBack, SpringForward};
+
+    fn months(value: i64) -> TimeDelta {
+        TimeDeltaBuilder::default().months(value).build()
+    }
+
+    fn years(value: i64) -> TimeDelta {
+        TimeDeltaBuilder::default().years(value).build()
+    }
+
+    #[test]
+    fn time_delta_apply_saturating_months() {
+        let dt = Utc.ymd(2019, 10, 31).and_hms(12, 0, 0);
+        assert!(months(1).apply_datetime(dt).is_err());
+        assert_eq!(
+            months(1).apply_datetime_saturating(dt),
+            Ok((
+                Utc.ymd(2019, 11, 30).and_hms(12, 0, 0),
+                vec![Adjustment::MonthEnd {
+                    day: 31,
+                    last_day: 30
+                }]
+            ))
+        );
+
+        let dt = Utc.ymd(2019, 1, 31).and_hms(0, 0, 0);
+        assert!(months(-2).apply_datetime(dt).is_err());
+        assert_eq!(
+            months(-2).apply_datetime_saturating(dt),
+            Ok((
+                Utc.ymd(2018, 11, 30).and_hms(0, 0, 0),
+                vec![Adjustment::MonthEnd {
+                    day: 31,
+                    last_day: 30
+                }]
+            ))
+        );
+        assert_eq!(
+            months(1).apply_datetime_saturating(dt),
+            Ok((
+                Utc.ymd(2019, 2, 28).and_hms(0, 0, 0),
+                vec![Adjustment::MonthEnd {
+                    day: 31,
+                    last_day: 28
+                }]
+            ))
+        );
+
+        // no adjustments for valid results.
+        assert_eq!(
+            months(2).apply_datetime_saturating(Utc.ymd(2019, 10, 31).and_hms(0, 0, 0)),
+            Ok((Utc.ymd(2019, 12, 31).and_hms(0, 0, 0), vec![]))
+        );
+    }
+
+    #[test]
+    fn time_delta_apply_saturating_years() {
+        let dt = Utc.ymd(2020, 2, 29).and_hms(0, 0, 0);
+        let feb_28 = vec![Adjustment::MonthEnd {
+            day: 29,
+            last_day: 28,
+        }];
+
+        assert!(years(1).apply_datetime(dt).is_err());
+        assert_eq!(
+            years(1).apply_datetime_saturating(dt),
+            Ok((Utc.ymd(2021, 2, 28).and_hms(0, 0, 0), feb_28.clone()))
+        );
+
+        assert!(years(-1).apply_datetime(dt).is_err());
+        assert_eq!(
+            years(-1).apply_datetime_saturating(dt),
+            Ok((Utc.ymd(2019, 2, 28).and_hms(0, 0, 0), feb_28))
+        );
+
+        assert_eq!(
+            years(4).apply_datetime_saturating(dt),
+            Ok((Utc.ymd(2024, 2, 29).and_hms(0, 0, 0), vec![]))
+        );
+    }
+
+    #[test]
+    fn time_delta_apply_clamping_day() {
+        let cases = [
+            // (date, months, expected date)
+            ((2019, 1, 31), 1, (2019, 2, 28)),
+            ((2020, 1, 31), 1, (2020, 2, 29)),
+            ((2020, 3, 31), -1, (2020, 2, 29)),
+            ((2100, 1, 30), 1, (2100, 2, 28)),
+            ((2000, 1, 30), 1, (2000, 2, 29)),
+            ((2019, 10, 31), 1, (2019, 11, 30)),
+            ((2020, 2, 29), 12, (2021, 2, 28)),
+            ((2020, 2, 29), 48, (2024, 2, 29)),
+        ];
+        for ((y, m, d), delta, (ey, em, ed)) in cases.iter().copied() {
+            let dt = Utc.ymd(y, m, d).and_hms(9, 2, 9);
+            let (applied, adjustment) = months(delta).apply_datetime_clamping_day(dt).unwrap();
+            assert_eq!(applied, Utc.ymd(ey, em, ed).and_hms(9, 2, 9));
+            assert_eq!(
+                adjustment,
+                Some(Adjustment::MonthEnd {
+                    day: d,
+                    last_day: ed
+                })
+                .filter(|_| d != ed)
+            );
+            // the strict one fails, unless the day exists.
+            assert_eq!(months(delta).apply_datetime(dt).is_ok(), d == ed);
+        }
+
+        assert_eq!(
+            months(1).apply_datetime(Utc.ymd(2020, 1, 31).and_hms(0, 0, 0)),
+            Err(ApplyError::NonexistentDate {
+                year: 2020,
+                month: 2,
+                day: 31
+            })
+        );
+
+        // only the day is clamped, and a gap still fails.
+        let dt = SpringForward::new().ymd(2019, 2, 10).and_hms(2, 30, 0);
+        assert_eq!(
+            months(1).apply_datetime_clamping_day(dt),
+            Err(ApplyError::InvalidComposition(
+                "2019-03-10 02:30:00".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn time_delta_apply_saturating_gap() {
+        let dt = SpringForward::new().ymd(2019, 2, 10).and_hms(2, 30, 0);
+        assert_eq!(
+            months(1).apply_datetime(dt),
+            Err(ApplyError::InvalidComposition(
+                "2019-03-10 02:30:00".to_string()
+            ))
+        );
+
+        // resolved to the first valid time after the gap.
+        let (applied, adjustments) = months(1).apply_datetime_saturating(dt).unwrap();
+        assert_eq!(
+            applied.naive_local(),
+            NaiveDate::from_ymd(2019, 3, 10).and_hms(3, 0, 0)
+        );
+        assert_eq!(applied.offset(), &SpringForward::offsets()[1]);
+        assert_eq!(adjustments, vec![Adjustment::Gap]);
+    }
+
+    #[test]
+    fn time_delta_apply_saturating_ambiguous() {
+        let dt = FallBack::new().ymd(2019, 10, 3).and_hms(1, 30, 0);
+        assert_eq!(
+            months(1).apply_datetime(dt),
+            Err(ApplyError::InvalidComposition(
+                "2019-11-03 01:30:00".to_string()
+            ))
+        );
+
+        let (applied, adjustments) = months(1).apply_datetime_saturating(dt).unwrap();
+        assert_eq!(
+            applied.naive_local(),
+            NaiveDate::from_ymd(2019, 11, 3).and_hms(1, 30, 0)
+        );
+        assert_eq!(applied.offset(), &FallBack::offsets()[0]);
+        assert!(adjustments.is_empty());
+    }
+
+    #[test]
+    fn time_delta_apply_saturating_out_of_range() {
+        let dt = Utc.ymd(2019, 6, 12).and_hms(0, 0, 0);
+        assert!(years(i64::MAX).apply_datetime_saturating(dt).is_err());
+        assert!(TimeDelta::new(0, 0, i64::MAX, 0, 0, 0, 0)
+            .apply_datetime_saturating(dt)
+            .is_err());
+    }
+
+    #[test]
+    fn adjustment_message() {
+        assert_eq!(
+            Adjustment::MonthEnd {
+                day: 31,
+                last_day: 30
+            }
+            .to_string(),
+            "day 31 does not exist in the month, clamped to day 30."
+        );
+    }
+}
+
+#[cfg(test)]
+mod duration_tests {
+    use chrono::Duration;
+
+    use super::TimeDelta;
+
+    #[test]
+    fn time_delta_as_duration() {
+        let delta = TimeDelta::new(0, 0, 1, 2, 3, 4, 5);
+        let expected = Duration::days(1)
+            + Duration::hours(2)
+            + Duration::minutes(3)
+            + Duration::seconds(4)
+            + Duration::microseconds(5);
+        assert_eq!(delta.as_duration(), Some(expected));
+        assert_eq!(delta.total_days(), Some(1));
+        assert_eq!(delta.total_seconds(), Some(93_784));
+        assert_eq!(delta.total_microseconds(), Some(93_784_000_005));
+
+        let delta = TimeDelta::new(0, 0, -1, -2, 0, 0, -500_000);
+        assert_eq!(delta.total_days(), Some(-1));
+        assert_eq!(delta.total_seconds(), Some(-93_600));
+        assert_eq!(delta.total_microseconds(), Some(-93_600_500_000));
+
+        assert_eq!(
+            TimeDelta::new(0, 0, 0, 0, 0, 0, 0).as_duration(),
+            Some(Duration::zero())
+        );
+    }
+
+    #[test]
+    fn time_delta_as_duration_calendar_fields() {
+        for delta in [
+            TimeDelta::new(0, 1, 0, 0, 0, 0, 0),
+            TimeDelta::new(1, 0, 0, 0, 0, 0, 0),
+            TimeDelta::new(0, -1, 3, 0, 0, 0, 0),
+        ] {
+            assert_eq!(delta.as_duration(), None);
+            assert_eq!(delta.total_days(), None);
+            assert_eq!(delta.total_seconds(), None);
+            assert_eq!(delta.total_microseconds(), None);
+        }
+    }
+
+    #[test]
+    fn time_delta_total_microseconds_overflow() {
+        let delta = TimeDelta::new(0, 0, i64::from(i32::MAX), 0, 0, 0, 0);
+        assert_eq!(delta.total_seconds(), Some(i64::from(i32::MAX) * 86_400));
+        assert_eq!(delta.total_microseconds(), None);
+    }
+}
+
+#[cfg(test)]
+mod between_tests {
+    use chrono::offset::TimeZone;
+    use chrono::{DateTime, Datelike, Duration, FixedOffset, Utc};
+
+    use super::{ApplyDateTime, TimeDelta};
+
+    fn between(a: (i32, u32, u32), b: (i32, u32, u32)) -> TimeDelta {
+        let a = Utc.ymd(a.0, a.1, a.2).and_hms(0, 0, 0);
+        let b = Utc.ymd(b.0, b.1, b.2).and_hms(0, 0, 0);
+        TimeDelta::between(&a, &b)
+    }
+
+    fn assert_round_trip<Tz: TimeZone>(a: &DateTime<Tz>, b: &DateTime<Tz>) {
+        let delta = TimeDelta::between(a, b);
+        assert_eq!(
+            delta.apply_datetime(a.clone()).as_ref(),
+            Ok(b),
+            "{:?} -> {:?}: {}",
+            a,
+            b,
+            delta
+        );
+
+        let fields = [
+            delta.years(),
+            delta.months(),
+            delta.days(),
+            delta.hours(),
+            delta.minutes(),
+            delta.seconds(),
+            delta.microseconds(),
+        ];
+        if a <= b {
+            assert!(
+                fields.iter().all(|x| *x >= 0),
+                "{:?} -> {:?}: {}",
+                a,
+                b,
+                delta
+            );
+        } else {
+            assert!(
+                fields.iter().all(|x| *x <= 0),
+                "{:?} -> {:?}: {}",
+                a,
+                b,
+                delta
+            );
+        }
+    }
+
+    #[test]
+    fn time_delta_between_basics() {
+        assert_eq!(
+            between((2019, 6, 12), (2019, 6, 12)),
+            TimeDelta::new(0, 0, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 6, 12), (2019, 6, 20)),
+            TimeDelta::new(0, 0, 8, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 6, 12), (2019, 8, 20)),
+            TimeDelta::new(0, 2, 8, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 6, 12), (2021, 7, 12)),
+            TimeDelta::new(2, 1, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 6, 20), (2019, 8, 12)),
+            TimeDelta::new(0, 1, 22, 0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_between_negative() {
+        assert_eq!(
+            between((2019, 6, 20), (2019, 6, 12)),
+            TimeDelta::new(0, 0, -8, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2021, 7, 12), (2019, 6, 12)),
+            TimeDelta::new(-2, -1, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 8, 12), (2019, 6, 20)),
+            TimeDelta::new(0, -1, -23, 0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_between_month_ends() {
+        assert_eq!(
+            between((2019, 1, 31), (2019, 2, 28)),
+            TimeDelta::new(0, 0, 28, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 1, 31), (2019, 3, 31)),
+            TimeDelta::new(0, 2, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 1, 31), (2019, 3, 1)),
+            TimeDelta::new(0, 1, 1, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 1, 28), (2019, 2, 28)),
+            TimeDelta::new(0, 1, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 3, 31), (2019, 2, 28)),
+            TimeDelta::new(0, -1, -3, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 3, 31), (2019, 1, 31)),
+            TimeDelta::new(0, -2, 0, 0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_between_leap_days() {
+        assert_eq!(
+            between((2020, 2, 29), (2021, 2, 28)),
+            TimeDelta::new(0, 11, 28, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2020, 2, 29), (2024, 2, 29)),
+            TimeDelta::new(4, 0, 0, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2019, 2, 28), (2020, 2, 29)),
+            TimeDelta::new(0, 11, 29, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2020, 1, 31), (2020, 2, 29)),
+            TimeDelta::new(0, 0, 29, 0, 0, 0, 0)
+        );
+        assert_eq!(
+            between((2024, 2, 29), (2020, 2, 29)),
+            TimeDelta::new(-4, 0, 0, 0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_between_sub_seconds() {
+        let a = Utc.ymd(2019, 6, 12).and_hms_micro(23, 59, 59, 999_999);
+        let b = Utc.ymd(2019, 7, 13).and_hms_micro(0, 0, 0, 1);
+        assert_eq!(
+            TimeDelta::between(&a, &b),
+            TimeDelta::new(0, 1, 0, 0, 0, 0, 2)
+        );
+        assert_eq!(
+            TimeDelta::between(&b, &a),
+            TimeDelta::new(0, -1, 0, 0, 0, 0, -2)
+        );
+
+        let a = Utc.ymd(2019, 6, 12).and_hms_micro(12, 30, 15, 250_000);
+        let b = Utc.ymd(2019, 6, 14).and_hms_micro(10, 0, 0, 0);
+        assert_eq!(
+            TimeDelta::between(&a, &b),
+            TimeDelta::new(0, 0, 1, 21, 29, 44, 750_000)
+        );
+        assert_eq!(
+            TimeDelta::between(&b, &a),
+            TimeDelta::new(0, 0, -1, -21, -29, -44, -750_000)
+        );
+
+        let a = Utc.ymd(2019, 6, 12).and_hms_nano(0, 0, 0, 999);
+        let b = Utc.ymd(2019, 6, 12).and_hms_nano(0, 0, 0, 2_001);
+        assert_eq!(
+            TimeDelta::between(&a, &b),
+            TimeDelta::new(0, 0, 0, 0, 0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn time_delta_between_round_trip() {
+        let base = Utc.ymd(2019, 12, 20).and_hms(0, 0, 0);
+        let times = [
+            Duration::zero(),
+            Duration::hours(13) + Duration::microseconds(1),
+            Duration::hours(23) + Duration::minutes(59) + Duration::seconds(59),
+        ];
+        let datetimes: Vec<_> = (0..450)
+            .step_by(5)
+            .flat_map(|days| times.iter().map(move |t| Duration::days(days) + *t))
+            .map(|d| base + d)
+            .collect();
+
+        for a in &datetimes {
+            for b in &datetimes {
+                assert_round_trip(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn time_delta_between_month_ends_round_trip() {
+        let days = |y: i32, m: u32| (1..=31).filter_map(move |d| Utc.ymd_opt(y, m, d).single());
+        let dates: Vec<_> = [
+            (2019, 1),
+            (2019, 2),
+            (2019, 3),
+            (2020, 2),
+            (2020, 3),
+            (2020, 4),
+        ]
+        .iter()
+        .flat_map(|(y, m)| days(*y, *m).filter(|d| d.day0() < 3 || d.succ().day() <= 3))
+        .map(|d| d.and_hms(6, 0, 0))
+        .collect();
+
+        for a in &dates {
+            for b in &dates {
+                assert_round_trip(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn time_delta_between_offset() {
+        let tz = FixedOffset::east(9 * 3600);
+        let a = tz.ymd(2019, 1, 31).and_hms(23, 0, 0);
+        let b = tz.ymd(2019, 3, 1).and_hms(1, 0, 0);
+        assert_eq!(
+            TimeDelta::between(&a, &b),
+            TimeDelta::new(0, 1, 0, 2, 0, 0, 0)
+        );
+        assert_round_trip(&a, &b);
+        assert_round_trip(&b, &a);
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::{TimeDelta, TimeDeltaBuilder, TimeDeltaError};
+
+    #[test]
+    fn time_delta_builder_large_milliseconds() {
+        // the builder doesn't normalize, so seconds hold the whole value.
+        let delta = TimeDeltaBuilder::default()
+            .milliseconds(i64::MAX)
+            .try_build()
+            .unwrap();
+        assert_eq!(delta.seconds(), i64::MAX / 1000);
+        assert_eq!(delta.microseconds(), 807_000);
+
+        let delta = TimeDeltaBuilder::default()
+            .add_milliseconds(i64::from(i32::MAX))
+            .add_milliseconds(i64::from(i32::MAX))
+            .try_build()
+            .unwrap();
+        assert_eq!(delta.seconds(), 4_294_966);
+        assert_eq!(delta.microseconds(), 1_294_000);
+
+        let delta = TimeDeltaBuilder::default()
+            .add_milliseconds(i64::from(i32::MIN))
+            .add_milliseconds(i64::from(i32::MIN))
+            .try_build()
+            .unwrap();
+        assert_eq!(delta.seconds(), -4_294_966);
+        assert_eq!(delta.microseconds(), -1_296_000);
+    }
+
+    #[test]
+    fn time_delta_builder() {
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .years(2019)
+                .months(6)
+                .days(10)
+                .hours(20)
+                .minutes(12)
+                .seconds(34)
+                .microseconds(56)
+                .build(),
+            TimeDelta::new(2019, 6, 10, 20, 12, 34, 56)
+        );
+    }
+
+    #[test]
+    fn time_delta_builder_composite() {
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .ymd(1, 2, 3)
+                .hms(4, 5, 6)
+                .build(),
+            TimeDeltaBuilder::default()
+                .years(1)
+                .months(2)
+                .days(3)
+                .hours(4)
+                .minutes(5)
+                .seconds(6)
+                .build()
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .ymd(-1, -2, -3)
+                .hms_micro(-4, -5, -6, -7)
+                .build(),
+            TimeDeltaBuilder::default()
+                .years(-1)
+                .months(-2)
+                .days(-3)
+                .hours(-4)
+                .minutes(-5)
+                .seconds(-6)
+                .microseconds(-7)
+                .build()
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .ymd(1, -2, 3)
+                .hms_micro(-4, 5, -6, 7)
+                .build(),
+            TimeDeltaBuilder::default()
+                .years(1)
+                .months(-2)
+                .days(3)
+                .hours(-4)
+                .minutes(5)
+                .seconds(-6)
+                .microseconds(7)
+                .build()
+        );
+
+        // setters overwrite, and adders accumulate.
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .ymd(1, 2, 3)
+                .ymd(4, 5, 6)
+                .build(),
+            TimeDeltaBuilder::default().ymd(4, 5, 6).build()
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_ymd(1, 2, 3)
+                .add_ymd(-2, 1, -1)
+                .add_hms(1, 2, 3)
+                .add_hms_micro(-1, 0, 1, 500)
+                .build(),
+            TimeDeltaBuilder::default()
+                .years(-1)
+                .months(3)
+                .days(2)
+                .minutes(2)
+                .seconds(4)
+                .microseconds(500)
+                .build()
+        );
+    }
+
+    #[test]
+    fn time_delta_builder_composite_normalization() {
+        // normalization happens in new(), not in the builder.
+        let delta = TimeDeltaBuilder::default().hms(25, 61, 61).build();
+        assert_eq!(delta.hours(), 25);
+        assert_eq!(
+            TimeDelta::new(
+                delta.years(),
+                delta.months(),
+                delta.days(),
+                delta.hours(),
+                delta.minutes(),
+                delta.seconds(),
+                delta.microseconds()
+            ),
+            TimeDelta::new(0, 0, 1, 2, 2, 1, 0)
+        );
+        assert_eq!(
+            TimeDelta::new(1, 14, 3, 4, 5, 6, 0),
+            TimeDelta::new(2, 2, 3, 4, 5, 6, 0)
+        );
+    }
+
+    #[test]
+    fn time_delta_builder_weeks() {
+        let days = |d: i64| TimeDeltaBuilder::default().days(d).build();
+
+        assert_eq!(TimeDeltaBuilder::default().weeks(2).build(), days(14));
+        assert_eq!(TimeDeltaBuilder::default().weeks(-3).build(), days(-21));
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_weeks(1)
+                .add_weeks(-3)
+                .build(),
+            days(-14)
+        );
+
+        // weeks and days are summed up.
+        assert_eq!(
+            TimeDeltaBuilder::default().add_weeks(2).add_days(3).build(),
+            days(17)
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_days(3)
+                .add_weeks(-1)
+                .build(),
+            days(-4)
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_weeks(-1)
+                .add_hours(25)
+                .build(),
+            TimeDeltaBuilder::default()
+                .add_days(-7)
+                .add_hours(25)
+                .build()
+        );
+
+        // setters overwrite the days.
+        assert_eq!(
+            TimeDeltaBuilder::default().days(3).weeks(1).build(),
+            days(7)
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default().weeks(1).days(3).build(),
+            days(3)
+        );
+    }
+
+    #[test]
+    fn time_delta_builder_weeks_overflow() {
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .weeks(i64::MAX / 7 + 1)
+                .try_build(),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .add_days(7)
+                .add_weeks(i64::MAX / 7)
+                .try_build(),
+            Err(TimeDeltaError::Overflow("days"))
+        );
+        assert!(TimeDeltaBuilder::default()
+            .add_weeks(i64::MIN / 7)
+            .try_build()
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod from_str_tests {
+    use std::str::FromStr;
+
+    use super::{TimeDelta, TimeDeltaBuilder, TimeDeltaError};
+    use crate::delta::DeltaItemError;
+
+    #[test]
+    fn time_delta_from_str_single() {
+        assert_eq!(
+            TimeDelta::from_str("3d"),
+            Ok(TimeDeltaBuilder::default().days(3).build())
+        );
+        assert_eq!(
+            "-10h".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default().hours(-10).build())
+        );
+    }
+
+    #[test]
+    fn time_delta_from_str_compound() {
+        assert_eq!(
+            "1y2mon3d".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default()
+                .years(1)
+                .months(2)
+                .days(3)
+                .build())
+        );
+        assert_eq!(
+            "1d-12h+30min1d".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default()
+                .days(2)
+                .hours(-12)
+                .minutes(30)
+                .build())
+        );
+        assert_eq!(
+            "1s500ms".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default()
+                .seconds(1)
+                .microseconds(500_000)
+                .build())
+        );
+    }
+
+    #[test]
+    fn time_delta_from_str_iso8601() {
+        assert_eq!(
+            "P1Y2M10DT2H30M".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default()
+                .years(1)
+                .months(2)
+                .days(10)
+                .hours(2)
+                .minutes(30)
+                .build())
+        );
+        assert_eq!(
+            "-P2W".parse::<TimeDelta>(),
+            Ok(TimeDeltaBuilder::default().days(-14).build())
+        );
+        assert_eq!(
+            "P1W2D".parse::<TimeDelta>(),
+            Err(TimeDeltaError::WrongItem(
+                "P1W2D".to_string(),
+                DeltaItemError::WrongFormat("P1W2D".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn time_delta_from_str_invalid() {
+        match "1y2b3d".parse::<TimeDelta>() {
+            Err(TimeDeltaError::WrongItem(item, e)) => {
+                assert_eq!(item, "2b");
+                assert!(e.is_wrong_unit());
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+
+        assert_eq!(
+            "1y 3d".parse::<TimeDelta>(),
+            Err(TimeDeltaError::WrongItem(
+                "1y 3d".to_string(),
+                DeltaItemError::WrongFormat("1y 3d".to_string())
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod display_tests {
+    use super::{TimeDelta, TimeDeltaBuilder};
+
+    fn round_trip(s: &str) -> String {
+        s.parse::<TimeDelta>().unwrap().to_short_string()
+    }
+
+    #[test]
+    fn time_delta_short_string() {
+        assert_eq!(TimeDeltaBuilder::default().build().to_short_string(), "0s");
+        assert_eq!(
+            TimeDelta::new(1, 2, 3, 4, 5, 6, 7_000).to_short_string(),
+            "1y2mon3d4h5min6s7ms"
+        );
+        assert_eq!(TimeDelta::new(0, 0, 0, 0, 0, 0, 7).to_short_string(), "7us");
+        assert_eq!(
+            TimeDelta::new(0, 0, -1, 12, 0, 0, 0).to_short_string(),
+            "-1d12h"
+        );
+    }
+
+    #[test]
+    fn time_delta_display() {
+        assert_eq!(
+            TimeDelta::new(1, 2, 3, 4, 5, 6, 7).to_string(),
+            "1 year 2 months 3 days 04:05:06.000007"
+        );
+        assert_eq!(
+            TimeDelta::new(2, 1, 1, 0, 0, 0, 0).to_string(),
+            "2 years 1 month 1 day"
+        );
+        assert_eq!(TimeDelta::new(0, 0, 0, 1, 0, 0, 0).to_string(), "01:00:00");
+        assert_eq!(
+            TimeDelta::new(0, 13, 0, 0, 0, 30, 0).to_string(),
+            "1 year 1 month 00:00:30"
+        );
+    }
+
+    #[test]
+    fn time_delta_display_zero() {
+        assert_eq!(TimeDeltaBuilder::default().build().to_string(), "00:00:00");
+    }
+
+    #[test]
+    fn time_delta_display_subsec() {
+        assert_eq!(
+            TimeDelta::new(0, 0, 0, 0, 0, 0, 7).to_string(),
+            "00:00:00.000007"
+        );
+        assert_eq!(
+            TimeDelta::new(0, 0, 0, 0, 0, 0, -500_000).to_string(),
+            "-00:00:00.500000"
+        );
+    }
+
+    #[test]
+    fn time_delta_display_negative() {
+        assert_eq!(
+            TimeDelta::new(-1, -2, -3, -4, -5, -6, -7).to_string(),
+            "-1 year 2 months 3 days 04:05:06.000007"
+        );
+        assert_eq!(TimeDelta::new(0, 0, -2, 0, 0, 0, 0).to_string(), "-2 days");
+    }
+
+    #[test]
+    fn time_delta_display_mixed_signs() {
+        // fixed length fields are normalized.
+        assert_eq!(
+            TimeDelta::new(0, 0, -1, 12, 0, 0, 0).to_string(),
+            "-12:00:00"
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .hours(1)
+                .minutes(-90)
+                .build()
+                .to_string(),
+            "-00:30:00"
+        );
+        assert_eq!(
+            TimeDeltaBuilder::default()
+                .years(1)
+                .months(-1)
+                .build()
+                .to_string(),
+            "11 months"
+        );
+
+        // months and fixed length fields cannot be normalized together.
+        assert_eq!(
+            TimeDelta::new(0, 1, -1, -2, 0, 0, 0).to_string(),
+            "1 month -1 day 02:00:00"
+        );
+        assert_eq!(
+            TimeDelta::new(0, 1, -1, 0, 0, 0, 0).to_string(),
+            "1 month -1 day"
+        );
+        assert_eq!(
+            TimeDelta::new(0, -1, 0, 2, 0, 0, 0).to_string(),
+            "-1 month -02:00:00"
+        );
+    }
+
+    #[test]
+    fn time_delta_display_round_trip() {
+        assert_eq!(round_trip("1y2mon3d4h5min6s7ms"), "1y2mon3d4h5min6s7ms");
+        assert_eq!(round_trip("3d"), "3d");
+        assert_eq!(round_trip("1s7us"), "1s7us");
+        assert_eq!(round_trip("-7us"), "-7us");
+        assert!("7ux".parse::<TimeDelta>().is_err());
+        assert_eq!(round_trip("3000000000s"), "3000000000s");
+        assert_eq!(round_trip("3000000000d"), "3000000000d");
+        assert_eq!(round_trip("-3000000000000us"), "-3000000000ms");
+        assert!("922337203685477581c".parse::<TimeDelta>().is_err());
+        assert_eq!(round_trip("+3day"), "3d");
+        assert_eq!(round_trip("0s"), "0s");
+        assert_eq!(round_trip("1d-1d"), "0s");
+        assert_eq!(round_trip("-10hour30minute"), "-10h30min");
+        assert_eq!(round_trip("1500ms"), "1s500ms");
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{TimeDelta, TimeDeltaBuilder};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Deltas {
+        deltas: Vec<TimeDelta>,
+    }
+
+    #[test]
+    fn time_delta_serde() {
+        let deltas = Deltas {
+            deltas: vec![
+                TimeDelta::new(1, 2, 3, 4, 5, 6, 7_000),
+                TimeDelta::new(0, 0, -1, 12, 0, 0, 0),
+                TimeDelta::new(0, 0, 0, 0, 0, 0, 0),
+                TimeDelta::new(0, 0, 0, 0, 0, 1, -7),
+            ],
+        };
+        let s = toml::to_string(&deltas).unwrap();
+        assert_eq!(
+            s,
+            "deltas = [\"1y2mon3d4h5min6s7ms\", \"-1d12h\", \"0s\", \"1s-7us\"]\n"
+        );
+        assert_eq!(toml::from_str::<Deltas>(&s).unwrap(), deltas);
+
+        let r = toml::from_str::<Deltas>("deltas = [\"1d\", \"1x\"]");
+        assert!(r.is_err());
+    }
+
+    #[test]
+    fn time_delta_serde_wide_fields() {
+        // fields wider than i32 are parsed back as they are serialized.
+        let deltas = Deltas {
+            deltas: vec![
+                TimeDeltaBuilder::default().seconds(3_000_000_000).build(),
+                TimeDeltaBuilder::default().days(-3_000_000_000).build(),
+                TimeDeltaBuilder::default()
+                    .microseconds(3_000_000_007)
+                    .build(),
+            ],
+        };
+        let s = toml::to_string(&deltas).unwrap();
+        assert_eq!(
+            s,
+            "deltas = [\"3000000000s\", \"-3000000000d\", \"3000000007us\"]\n"
         );
+        assert_eq!(toml::from_str::<Deltas>(&s).unwrap(), deltas);
     }
 }
@@ -0,0 +1,103 @@
+use chrono::{DateTime, TimeZone};
+
+use crate::precision::{Precision, PrecisionError};
+
+/// A unix timestamp with its precision, like `1560762129000` in milliseconds.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Timestamp {
+    value: i64,
+    precision: Precision,
+}
+
+impl Timestamp {
+    pub fn new(value: i64, precision: Precision) -> Timestamp {
+        Timestamp { value, precision }
+    }
+
+    /// Converts the datetime into a timestamp. Fractions finer than the precision are dropped.
+    pub fn from_datetime<Tz: TimeZone>(
+        dt: DateTime<Tz>,
+        precision: Precision,
+    ) -> Result<Timestamp, PrecisionError> {
+        precision
+            .to_timestamp(dt)
+            .map(|value| Timestamp::new(value, precision))
+    }
+
+    pub fn value(self) -> i64 {
+        self.value
+    }
+
+    pub fn to_datetime<Tz: TimeZone>(self, tz: Tz) -> Result<DateTime<Tz>, PrecisionError> {
+        self.precision.parse_timestamp(tz, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, NaiveDate, TimeZone, Utc};
+
+    use crate::precision::{Precision, PrecisionError};
+    use crate::timestamp::Timestamp;
+
+    #[test]
+    fn new() {
+        let t = Timestamp::new(1_560_762_129_123, Precision::MilliSecond);
+        assert_eq!(t.value(), 1_560_762_129_123);
+        assert_eq!(t, Timestamp::new(1_560_762_129_123, Precision::MilliSecond));
+        assert_ne!(t, Timestamp::new(1_560_762_129_123, Precision::MicroSecond));
+    }
+
+    #[test]
+    fn round_trip() {
+        let dt = Utc.from_utc_datetime(
+            &NaiveDate::from_ymd_opt(2019, 6, 17)
+                .unwrap()
+                .and_hms_nano_opt(9, 2, 9, 123_456_789)
+                .unwrap(),
+        );
+        let cases = [
+            (Precision::Second, 1_560_762_129, 0),
+            (Precision::MilliSecond, 1_560_762_129_123, 123_000_000),
+            (Precision::MicroSecond, 1_560_762_129_123_456, 123_456_000),
+            (
+                Precision::NanoSecond,
+                1_560_762_129_123_456_789,
+                123_456_789,
+            ),
+        ];
+        for (precision, value, nanos) in cases.iter().copied() {
+            let t = Timestamp::from_datetime(dt, precision).unwrap();
+            assert_eq!(t, Timestamp::new(value, precision));
+
+            let back = t.to_datetime(Utc).unwrap();
+            assert_eq!(back.timestamp(), 1_560_762_129);
+            assert_eq!(back.timestamp_subsec_nanos(), nanos);
+            assert_eq!(Timestamp::from_datetime(back, precision), Ok(t));
+        }
+    }
+
+    #[test]
+    fn to_datetime_in_timezone() {
+        let jst = FixedOffset::east_opt(9 * 3600).unwrap();
+        let dt = Timestamp::new(0, Precision::Second)
+            .to_datetime(jst)
+            .unwrap();
+        assert_eq!(dt.to_rfc3339(), "1970-01-01T09:00:00+09:00");
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert!(matches!(
+            Timestamp::new(i64::MAX, Precision::Second).to_datetime(Utc),
+            Err(PrecisionError::OutOfRange(..))
+        ));
+        let max = Timestamp::new(i64::MAX, Precision::NanoSecond)
+            .to_datetime(Utc)
+            .unwrap();
+        assert!(matches!(
+            Timestamp::from_datetime(max + chrono::Duration::seconds(1), Precision::NanoSecond),
+            Err(PrecisionError::Unrepresentable(..))
+        ));
+    }
+}
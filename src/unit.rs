@@ -1,10 +1,11 @@
-use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Timelike};
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
+use crate::datetime::resolve_local_datetime;
 use crate::find::{FindByName, FindError, PossibleNames, PossibleValues};
-use crate::validate::IntoValidationError;
+use crate::validate::{IntoValidationError, ValidationMessage};
 
 #[derive(Error, Debug, PartialEq)]
 pub enum TimeUnitError {
@@ -21,27 +22,38 @@ impl From<FindError> for TimeUnitError {
 impl IntoValidationError for TimeUnitError {
     fn into_validation_error(self) -> String {
         use TimeUnitError::*;
-        match &self {
-            WrongName(e) => match e {
-                FindError::NotFound => {
-                    let names = TimeUnit::possible_names();
-                    format!("{} possible names: [{}]", self, names.join(", "))
-                }
-                FindError::Ambiguous(_) => format!("{}", self),
-            },
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("unit", &e, TimeUnit::possible_names())
+                    .to_string()
+            }
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+#[derive(Error, Debug, PartialEq)]
+pub enum TruncateError {
+    #[error("Cannot truncate {0} to the {1}: the start of the {1} is out of range.")]
+    OutOfRange(String, TimeUnit),
+}
+
+/// Units are ordered from coarser to finer.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, EnumIter, EnumString, Display)]
 pub enum TimeUnit {
+    #[strum(serialize = "century", serialize = "c")]
+    Century,
+
+    #[strum(serialize = "decade", serialize = "dec")]
+    Decade,
+
     #[strum(serialize = "year")]
     Year,
 
     #[strum(serialize = "month")]
     Month,
 
-    #[strum(serialize = "day")]
+    // `d` is kept as an exact name, since it is also a prefix of `decade`.
+    #[strum(serialize = "day", serialize = "d")]
     Day,
 
     #[strum(serialize = "hour")]
@@ -58,25 +70,53 @@ pub enum TimeUnit {
 }
 
 impl TimeUnit {
-    pub fn truncate<Tz: TimeZone>(self, dt: DateTime<Tz>) -> DateTime<Tz> {
+    /// Returns the next larger unit which contains this unit.
+    /// Years, decades and centuries are counted from year 0, so they have no enclosing unit.
+    pub fn enclosing(self) -> Option<TimeUnit> {
+        match self {
+            TimeUnit::Century | TimeUnit::Decade | TimeUnit::Year => None,
+            TimeUnit::Month => Some(TimeUnit::Year),
+            TimeUnit::Day => Some(TimeUnit::Month),
+            TimeUnit::Hour => Some(TimeUnit::Day),
+            TimeUnit::Minute => Some(TimeUnit::Hour),
+            TimeUnit::Second => Some(TimeUnit::Minute),
+            TimeUnit::MilliSecond => Some(TimeUnit::Second),
+        }
+    }
+
+    /// Returns the start of the unit which contains the datetime.
+    /// Fails if the start of a decade or a century is before the minimum year of chrono.
+    pub fn truncate<Tz: TimeZone>(self, dt: DateTime<Tz>) -> Result<DateTime<Tz>, TruncateError> {
+        let local = dt.naive_local();
+        let start_of_year = |year: i32| {
+            NaiveDate::from_ymd_opt(year, 1, 1)
+                .ok_or_else(|| TruncateError::OutOfRange(local.to_string(), self))
+        };
         let d = match self {
-            TimeUnit::Year => dt.date().with_month(1).unwrap().with_day(1).unwrap(),
-            TimeUnit::Month => dt.date().with_day(1).unwrap(),
-            _ => dt.date(),
+            TimeUnit::Century => start_of_year(local.year() - local.year().rem_euclid(100))?,
+            TimeUnit::Decade => start_of_year(local.year() - local.year().rem_euclid(10))?,
+            TimeUnit::Year => local.date().with_month(1).unwrap().with_day(1).unwrap(),
+            TimeUnit::Month => local.date().with_day(1).unwrap(),
+            _ => local.date(),
         };
 
-        match self {
-            TimeUnit::Hour => d.and_hms(dt.hour(), 0, 0),
-            TimeUnit::Minute => d.and_hms(dt.hour(), dt.minute(), 0),
-            TimeUnit::Second => d.and_hms(dt.hour(), dt.minute(), dt.second()),
-            TimeUnit::MilliSecond => d.and_hms_milli(
-                dt.hour(),
-                dt.minute(),
-                dt.second(),
+        let t = match self {
+            TimeUnit::Hour => NaiveTime::from_hms_opt(local.hour(), 0, 0),
+            TimeUnit::Minute => NaiveTime::from_hms_opt(local.hour(), local.minute(), 0),
+            TimeUnit::Second => {
+                NaiveTime::from_hms_opt(local.hour(), local.minute(), local.second())
+            }
+            TimeUnit::MilliSecond => NaiveTime::from_hms_milli_opt(
+                local.hour(),
+                local.minute(),
+                local.second(),
                 dt.timestamp_subsec_millis(),
             ),
-            _ => d.and_hms(0, 0, 0),
+            _ => NaiveTime::from_hms_opt(0, 0, 0),
         }
+        .unwrap();
+
+        Ok(resolve_local_datetime(&dt.timezone(), d.and_time(t)))
     }
 }
 
@@ -96,7 +136,7 @@ impl FindByName for TimeUnit {
 
 #[cfg(test)]
 mod find_tests {
-    use crate::find::{FindByName, FindError};
+    use crate::find::{FindByName, FindError, FindOptions};
     use crate::unit::{TimeUnit, TimeUnitError};
 
     #[test]
@@ -113,11 +153,14 @@ mod find_tests {
 
         assert_eq!(
             TimeUnit::find_by_name("m"),
-            Err(TimeUnitError::WrongName(FindError::Ambiguous(vec![
-                "month".to_string(),
-                "minute".to_string(),
-                "millisecond".to_string()
-            ])))
+            Err(TimeUnitError::WrongName(FindError::Ambiguous(
+                "m".to_string(),
+                vec![
+                    "month".to_string(),
+                    "minute".to_string(),
+                    "millisecond".to_string()
+                ]
+            )))
         );
     }
 
@@ -125,6 +168,22 @@ mod find_tests {
     fn find_by_name_day() {
         assert_eq!(TimeUnit::find_by_name("day"), Ok(TimeUnit::Day));
         assert_eq!(TimeUnit::find_by_name("d"), Ok(TimeUnit::Day));
+        assert_eq!(TimeUnit::find_by_name("D"), Ok(TimeUnit::Day));
+        assert_eq!(TimeUnit::find_by_name("da"), Ok(TimeUnit::Day));
+    }
+
+    #[test]
+    fn find_by_name_decade() {
+        assert_eq!(TimeUnit::find_by_name("decade"), Ok(TimeUnit::Decade));
+        assert_eq!(TimeUnit::find_by_name("dec"), Ok(TimeUnit::Decade));
+        assert_eq!(TimeUnit::find_by_name("de"), Ok(TimeUnit::Decade));
+    }
+
+    #[test]
+    fn find_by_name_century() {
+        assert_eq!(TimeUnit::find_by_name("century"), Ok(TimeUnit::Century));
+        assert_eq!(TimeUnit::find_by_name("c"), Ok(TimeUnit::Century));
+        assert_eq!(TimeUnit::find_by_name("cent"), Ok(TimeUnit::Century));
     }
 
     #[test]
@@ -140,10 +199,10 @@ mod find_tests {
 
         assert_eq!(
             TimeUnit::find_by_name("mi"),
-            Err(TimeUnitError::WrongName(FindError::Ambiguous(vec![
-                "minute".to_string(),
-                "millisecond".to_string()
-            ])))
+            Err(TimeUnitError::WrongName(FindError::Ambiguous(
+                "mi".to_string(),
+                vec!["minute".to_string(), "millisecond".to_string()]
+            )))
         );
     }
 
@@ -163,18 +222,63 @@ mod find_tests {
         assert_eq!(TimeUnit::find_by_name("ms"), Ok(TimeUnit::MilliSecond));
     }
 
+    #[test]
+    fn find_by_name_exact_ignore_case() {
+        assert_eq!(TimeUnit::find_by_name("MS"), Ok(TimeUnit::MilliSecond));
+        assert_eq!(TimeUnit::find_by_name("Ms"), Ok(TimeUnit::MilliSecond));
+        assert_eq!(TimeUnit::find_by_name("SECOND"), Ok(TimeUnit::Second));
+        assert_eq!(TimeUnit::find_by_name("Minute"), Ok(TimeUnit::Minute));
+    }
+
+    #[test]
+    fn find_by_name_strict() {
+        let strict = FindOptions { strict: true };
+        assert_eq!(
+            TimeUnit::find_by_name_with("ms", strict),
+            Ok(TimeUnit::MilliSecond)
+        );
+        assert_eq!(
+            TimeUnit::find_by_name_with("millisecond", strict),
+            Ok(TimeUnit::MilliSecond)
+        );
+        assert!(TimeUnit::find_by_name_with("mil", strict).is_err());
+        assert!(TimeUnit::find_by_name_with("h", strict).is_err());
+        assert_eq!(TimeUnit::find_by_name_with("d", strict), Ok(TimeUnit::Day));
+        assert!(TimeUnit::find_by_name_with("de", strict).is_err());
+    }
+
     #[test]
     fn find_by_name_not_supported() {
         assert_eq!(
             TimeUnit::find_by_name("b"),
-            Err(TimeUnitError::WrongName(FindError::NotFound))
+            Err(TimeUnitError::WrongName(FindError::NotFound(
+                "b".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn find_by_name_suggestion() {
+        assert_eq!(
+            TimeUnit::find_by_name("huor"),
+            Err(TimeUnitError::WrongName(FindError::Suggestion(
+                "huor".to_string(),
+                vec!["hour".to_string()]
+            )))
+        );
+        assert_eq!(
+            TimeUnit::find_by_name("sec0nd"),
+            Err(TimeUnitError::WrongName(FindError::Suggestion(
+                "sec0nd".to_string(),
+                vec!["second".to_string()]
+            )))
         );
     }
 }
 
 #[cfg(test)]
 mod truncate_tests {
-    use crate::unit::TimeUnit;
+    use crate::unit::{TimeUnit, TruncateError};
 
     use chrono::offset::TimeZone;
     use chrono::{DateTime, Utc};
@@ -183,15 +287,83 @@ mod truncate_tests {
         Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 444)
     }
 
+    #[test]
+    fn truncate_century() {
+        assert_eq!(
+            TimeUnit::Century.truncate(base_date()).unwrap(),
+            Utc.ymd(2000, 1, 1).and_hms(0, 0, 0)
+        );
+
+        assert_eq!(
+            TimeUnit::Century
+                .truncate(Utc.ymd(2000, 1, 1).and_hms(0, 0, 0))
+                .unwrap(),
+            Utc.ymd(2000, 1, 1).and_hms(0, 0, 0)
+        );
+
+        assert_eq!(
+            TimeUnit::Century
+                .truncate(Utc.ymd(-1, 6, 17).and_hms(0, 0, 0))
+                .unwrap(),
+            Utc.ymd(-100, 1, 1).and_hms(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn truncate_decade() {
+        assert_eq!(
+            TimeUnit::Decade.truncate(base_date()).unwrap(),
+            Utc.ymd(2010, 1, 1).and_hms(0, 0, 0)
+        );
+
+        assert_eq!(
+            TimeUnit::Decade
+                .truncate(Utc.ymd(2010, 1, 1).and_hms(0, 0, 0))
+                .unwrap(),
+            Utc.ymd(2010, 1, 1).and_hms(0, 0, 0)
+        );
+
+        assert_eq!(
+            TimeUnit::Decade
+                .truncate(Utc.ymd(-1, 6, 17).and_hms(0, 0, 0))
+                .unwrap(),
+            Utc.ymd(-10, 1, 1).and_hms(0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn truncate_out_of_range() {
+        // the minimum year of chrono is -262144, so the start of its century or decade does not exist.
+        let min = Utc.ymd(-262_144, 6, 17).and_hms(0, 0, 0);
+        assert_eq!(
+            TimeUnit::Century.truncate(min),
+            Err(TruncateError::OutOfRange(
+                "-262144-06-17 00:00:00".to_string(),
+                TimeUnit::Century
+            ))
+        );
+        assert!(TimeUnit::Decade.truncate(min).is_err());
+        assert_eq!(
+            TimeUnit::Year.truncate(min),
+            Ok(Utc.ymd(-262_144, 1, 1).and_hms(0, 0, 0))
+        );
+        assert_eq!(
+            TimeUnit::Century.truncate(Utc.ymd(-262_100, 1, 1).and_hms(0, 0, 0)),
+            Ok(Utc.ymd(-262_100, 1, 1).and_hms(0, 0, 0))
+        );
+    }
+
     #[test]
     fn truncate_year() {
         assert_eq!(
-            TimeUnit::Year.truncate(base_date()),
+            TimeUnit::Year.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)
         );
 
         assert_eq!(
-            TimeUnit::Year.truncate(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)),
+            TimeUnit::Year
+                .truncate(Utc.ymd(2019, 1, 1).and_hms(0, 0, 0))
+                .unwrap(),
             Utc.ymd(2019, 1, 1).and_hms(0, 0, 0)
         );
     }
@@ -199,12 +371,14 @@ mod truncate_tests {
     #[test]
     fn truncate_month() {
         assert_eq!(
-            TimeUnit::Month.truncate(base_date()),
+            TimeUnit::Month.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 1).and_hms(0, 0, 0)
         );
 
         assert_eq!(
-            TimeUnit::Month.truncate(Utc.ymd(2019, 6, 1).and_hms(0, 0, 0)),
+            TimeUnit::Month
+                .truncate(Utc.ymd(2019, 6, 1).and_hms(0, 0, 0))
+                .unwrap(),
             Utc.ymd(2019, 6, 1).and_hms(0, 0, 0)
         );
     }
@@ -212,12 +386,14 @@ mod truncate_tests {
     #[test]
     fn truncate_day() {
         assert_eq!(
-            TimeUnit::Day.truncate(base_date()),
+            TimeUnit::Day.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(0, 0, 0)
         );
 
         assert_eq!(
-            TimeUnit::Day.truncate(Utc.ymd(2019, 6, 17).and_hms(0, 0, 0)),
+            TimeUnit::Day
+                .truncate(Utc.ymd(2019, 6, 17).and_hms(0, 0, 0))
+                .unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(0, 0, 0)
         );
     }
@@ -225,12 +401,14 @@ mod truncate_tests {
     #[test]
     fn truncate_hour() {
         assert_eq!(
-            TimeUnit::Hour.truncate(base_date()),
+            TimeUnit::Hour.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 0, 0)
         );
 
         assert_eq!(
-            TimeUnit::Hour.truncate(Utc.ymd(2019, 6, 17).and_hms(11, 0, 0)),
+            TimeUnit::Hour
+                .truncate(Utc.ymd(2019, 6, 17).and_hms(11, 0, 0))
+                .unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 0, 0)
         );
     }
@@ -238,12 +416,14 @@ mod truncate_tests {
     #[test]
     fn truncate_minute() {
         assert_eq!(
-            TimeUnit::Minute.truncate(base_date()),
+            TimeUnit::Minute.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 22, 0)
         );
 
         assert_eq!(
-            TimeUnit::Minute.truncate(Utc.ymd(2019, 6, 17).and_hms(11, 22, 0)),
+            TimeUnit::Minute
+                .truncate(Utc.ymd(2019, 6, 17).and_hms(11, 22, 0))
+                .unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 22, 0)
         );
     }
@@ -251,25 +431,50 @@ mod truncate_tests {
     #[test]
     fn truncate_second() {
         assert_eq!(
-            TimeUnit::Second.truncate(base_date()),
+            TimeUnit::Second.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 22, 33)
         );
 
         assert_eq!(
-            TimeUnit::Second.truncate(Utc.ymd(2019, 6, 17).and_hms(11, 22, 33)),
+            TimeUnit::Second
+                .truncate(Utc.ymd(2019, 6, 17).and_hms(11, 22, 33))
+                .unwrap(),
             Utc.ymd(2019, 6, 17).and_hms(11, 22, 33)
         );
     }
 
+    #[test]
+    fn truncate_day_skipped_midnight() {
+        use chrono::NaiveDate;
+
+        use crate::datetime::test_zones::SkippedMidnight;
+
+        let local = |h, m| {
+            NaiveDate::from_ymd_opt(2018, 11, 4)
+                .unwrap()
+                .and_hms_opt(h, m, 0)
+                .unwrap()
+        };
+        let dt = SkippedMidnight::new()
+            .from_local_datetime(&local(12, 34))
+            .unwrap();
+
+        let truncated = TimeUnit::Day.truncate(dt).unwrap();
+        assert_eq!(truncated.naive_local(), local(1, 0));
+        assert_eq!(truncated.naive_utc(), local(3, 0));
+    }
+
     #[test]
     fn truncate_millisecond() {
         assert_eq!(
-            TimeUnit::MilliSecond.truncate(base_date()),
+            TimeUnit::MilliSecond.truncate(base_date()).unwrap(),
             Utc.ymd(2019, 6, 17).and_hms_micro(11, 22, 33, 444_000)
         );
 
         assert_eq!(
-            TimeUnit::MilliSecond.truncate(Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 444)),
+            TimeUnit::MilliSecond
+                .truncate(Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 444))
+                .unwrap(),
             Utc.ymd(2019, 6, 17).and_hms_milli(11, 22, 33, 444)
         );
     }
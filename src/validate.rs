@@ -1,6 +1,10 @@
+use std::fmt;
 use std::str::FromStr;
 
-use crate::find::{FindByName, FindError};
+use chrono::{DateTime, Weekday};
+use thiserror::Error;
+
+use crate::find::{did_you_mean, FindByName, FindError, FindOptions};
 
 pub fn validate_number<T: PartialOrd, E, F: Fn() -> E>(
     n: T,
@@ -15,10 +19,166 @@ pub fn validate_number<T: PartialOrd, E, F: Fn() -> E>(
     }
 }
 
+/// The accepted forms of a name, by prefix matching or by `--strict-names`.
+const ACCEPTED_NAME: &str = "a possible name, or its unique prefix";
+const ACCEPTED_EXACT_NAME: &str = "a possible name, exactly as it is";
+
 pub trait IntoValidationError {
     fn into_validation_error(self) -> String;
 }
 
+/// A validation error message rendered in a fixed layout: the problem,
+/// the given input, the accepted forms and the possible names.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationMessage {
+    problem: String,
+    input: String,
+    accepted: Option<String>,
+    possible_names: Vec<String>,
+}
+
+impl ValidationMessage {
+    pub fn new<P: Into<String>, I: Into<String>>(problem: P, input: I) -> ValidationMessage {
+        ValidationMessage {
+            problem: problem.into(),
+            input: input.into(),
+            accepted: None,
+            possible_names: Vec::new(),
+        }
+    }
+
+    /// Creates a message for a name which cannot be found in `possible_names`.
+    pub fn from_find_error(
+        subject: &str,
+        e: &FindError,
+        possible_names: Vec<String>,
+    ) -> ValidationMessage {
+        let (problem, input) = match e {
+            FindError::NotFound(name) => (format!("Wrong {}: no matching name.", subject), name),
+            FindError::Suggestion(name, suggestions) => (
+                format!(
+                    "Wrong {}: no matching name. did you mean {}?",
+                    subject,
+                    did_you_mean(suggestions)
+                ),
+                name,
+            ),
+            FindError::Ambiguous(name, candidates) => (
+                format!(
+                    "Wrong {}: ambiguous name. it could be {}.",
+                    subject,
+                    did_you_mean(candidates)
+                ),
+                name,
+            ),
+        };
+
+        ValidationMessage::new(problem, input.as_str())
+            .accepted(ACCEPTED_NAME)
+            .possible_names(possible_names)
+    }
+
+    pub fn input<S: Into<String>>(mut self, input: S) -> ValidationMessage {
+        self.input = input.into();
+        self
+    }
+
+    pub fn accepted<S: Into<String>>(mut self, accepted: S) -> ValidationMessage {
+        self.accepted = Some(accepted.into());
+        self
+    }
+
+    pub fn possible_names(mut self, names: Vec<String>) -> ValidationMessage {
+        self.possible_names = names;
+        self
+    }
+}
+
+impl fmt::Display for ValidationMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.problem)?;
+        write!(f, "\n    input: '{}'", self.input)?;
+        if let Some(accepted) = &self.accepted {
+            write!(f, "\n    accepted: {}", accepted)?;
+        }
+        if !self.possible_names.is_empty() {
+            write!(
+                f,
+                "\n    possible names: [{}]",
+                self.possible_names.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum TimestampError {
+    #[error("Wrong timestamp: '{0}'. timestamp must be an integer.")]
+    WrongFormat(String),
+
+    #[error(
+        "Wrong timestamp: '{0}'. timestamp must not have leading zeros with --strict-numeric."
+    )]
+    LeadingZeros(String),
+}
+
+/// Parses a timestamp in decimal, or in hexadecimal with `0x` prefix like `0x5D0775D1`.
+/// In strict mode, leading zeros like `00123` are rejected,
+/// as they are likely to be an ID rather than a timestamp.
+pub fn parse_timestamp(s: &str, strict: bool) -> Result<i64, TimestampError> {
+    let digits = s.trim_start_matches(['+', '-']);
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        return parse_hex_timestamp(s, hex);
+    }
+    if strict && digits.len() > 1 && digits.starts_with('0') {
+        return Err(TimestampError::LeadingZeros(s.to_string()));
+    }
+    s.parse()
+        .map_err(|_| TimestampError::WrongFormat(s.to_string()))
+}
+
+fn parse_hex_timestamp(s: &str, hex: &str) -> Result<i64, TimestampError> {
+    let wrong_format = || TimestampError::WrongFormat(s.to_string());
+    if hex.is_empty() || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(wrong_format());
+    }
+    // parse with the sign, so that i64::MIN can be represented.
+    let sign = &s[..s.len() - hex.len() - 2];
+    i64::from_str_radix(&format!("{}{}", sign, hex), 16).map_err(|_| wrong_format())
+}
+
+pub fn validate_timestamp(s: String) -> Result<(), String> {
+    parse_timestamp(&s, false).map(|_| ()).map_err(|_| {
+        ValidationMessage::new("Wrong timestamp: timestamp must be an integer.", s)
+            .accepted("seconds or milliseconds since the epoch, like `1561302000` or `0x5D0775D1`")
+            .to_string()
+    })
+}
+
+pub fn validate_instant(s: String) -> Result<(), String> {
+    if parse_timestamp(&s, false).is_ok() || DateTime::parse_from_rfc3339(&s).is_ok() {
+        return Ok(());
+    }
+    Err(ValidationMessage::new(
+        "Wrong instant: it must be a timestamp or an RFC 3339 datetime.",
+        s,
+    )
+    .accepted("like `1561302000` or `2019-06-24T00:00:00+09:00`")
+    .to_string())
+}
+
+pub fn validate_weekday(s: String) -> Result<(), String> {
+    Weekday::from_str(&s).map(|_| ()).map_err(|_| {
+        ValidationMessage::new("Wrong weekday: text is not a weekday name.", s)
+            .accepted("a weekday name or its abbreviation, like `monday` or `mon`")
+            .to_string()
+    })
+}
+
 pub fn validate_argv<T, E>(s: String) -> Result<(), String>
 where
     T: FromStr<Err = E>,
@@ -29,12 +189,139 @@ where
         .map_err(|e| e.into_validation_error())
 }
 
-pub fn validate_argv_by_name<T, E>(s: String) -> Result<(), String>
+/// Validates the name of an argument. With `--strict-names`, prefixes are not accepted, and the message says so.
+pub fn validate_argv_by_name<T, E>(options: FindOptions) -> impl Fn(String) -> Result<(), String>
 where
     T: FindByName<Error = E>,
-    E: From<FindError> + IntoValidationError + IntoValidationError,
+    E: From<FindError> + IntoValidationError,
 {
-    T::find_by_name(s.as_ref())
-        .map(|_| ())
-        .map_err(|e| e.into_validation_error())
+    move |s| {
+        T::find_by_name_with(s.as_ref(), options)
+            .map(|_| ())
+            .map_err(|e| {
+                let message = e.into_validation_error();
+                if options.strict {
+                    message.replace(ACCEPTED_NAME, ACCEPTED_EXACT_NAME)
+                } else {
+                    message
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_layout() {
+        let m = ValidationMessage::new("Wrong thing: it is broken.", "abc");
+        assert_eq!(
+            m.to_string(),
+            "Wrong thing: it is broken.\n    input: 'abc'"
+        );
+
+        let m = m
+            .accepted("`xyz`")
+            .possible_names(vec!["x".to_string(), "y".to_string()]);
+        assert_eq!(
+            m.to_string(),
+            "Wrong thing: it is broken.
+    input: 'abc'
+    accepted: `xyz`
+    possible names: [x, y]"
+        );
+    }
+
+    #[test]
+    fn message_from_find_error() {
+        let names = || vec!["hour".to_string(), "minute".to_string()];
+
+        let e = FindError::Suggestion("huor".to_string(), vec!["hour".to_string()]);
+        assert_eq!(
+            ValidationMessage::from_find_error("unit", &e, names()).to_string(),
+            "Wrong unit: no matching name. did you mean 'hour'?
+    input: 'huor'
+    accepted: a possible name, or its unique prefix
+    possible names: [hour, minute]"
+        );
+
+        let e = FindError::Ambiguous("m".to_string(), names());
+        assert_eq!(
+            ValidationMessage::from_find_error("unit", &e, names()).to_string(),
+            "Wrong unit: ambiguous name. it could be 'hour' or 'minute'.
+    input: 'm'
+    accepted: a possible name, or its unique prefix
+    possible names: [hour, minute]"
+        );
+    }
+
+    #[test]
+    fn strict_timestamp() {
+        assert_eq!(parse_timestamp("00123", false), Ok(123));
+        assert_eq!(
+            parse_timestamp("00123", true),
+            Err(TimestampError::LeadingZeros("00123".to_string()))
+        );
+        assert_eq!(
+            parse_timestamp("-0123", true),
+            Err(TimestampError::LeadingZeros("-0123".to_string()))
+        );
+        assert_eq!(parse_timestamp("0", true), Ok(0));
+        assert_eq!(parse_timestamp("-0", true), Ok(0));
+        assert_eq!(parse_timestamp("-100", true), Ok(-100));
+        assert_eq!(parse_timestamp("1561302000", true), Ok(1561302000));
+        assert_eq!(
+            parse_timestamp("1.5", true),
+            Err(TimestampError::WrongFormat("1.5".to_string()))
+        );
+    }
+
+    #[test]
+    fn hex_timestamp() {
+        assert_eq!(parse_timestamp("0x5D0775D1", true), Ok(1560770001));
+        assert_eq!(parse_timestamp("0x5d0775d1", false), Ok(1560770001));
+        assert_eq!(parse_timestamp("0X00ff", true), Ok(255));
+        assert_eq!(parse_timestamp("-0xFF", true), Ok(-255));
+        assert_eq!(parse_timestamp("+0xFF", true), Ok(255));
+
+        for s in &["0x", "0xG1", "0x-1", "0x+1", "--0x1", "0x8000000000000000"] {
+            assert_eq!(
+                parse_timestamp(s, false),
+                Err(TimestampError::WrongFormat(s.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn instant() {
+        assert!(validate_instant("1561302000".to_string()).is_ok());
+        assert!(validate_instant("0x5D0775D1".to_string()).is_ok());
+        assert!(validate_instant("2019-06-24T00:00:00+09:00".to_string()).is_ok());
+        assert!(validate_instant("2019-06-24T00:00:00Z".to_string()).is_ok());
+        assert!(validate_instant("2019-06-24".to_string()).is_err());
+        assert!(validate_instant("now".to_string()).is_err());
+    }
+
+    #[test]
+    fn weekday() {
+        assert!(validate_weekday("monday".to_string()).is_ok());
+        assert!(validate_weekday("Mon".to_string()).is_ok());
+        assert_eq!(
+            validate_weekday("mo".to_string()).unwrap_err(),
+            "Wrong weekday: text is not a weekday name.\n    input: 'mo'\n    accepted: a weekday name or its abbreviation, like `monday` or `mon`"
+        );
+    }
+
+    #[test]
+    fn timestamp() {
+        assert!(validate_timestamp("-86400".to_string()).is_ok());
+        assert_eq!(
+            validate_timestamp("1.5".to_string()),
+            Err("Wrong timestamp: timestamp must be an integer.
+    input: '1.5'
+    accepted: seconds or milliseconds since the epoch, like `1561302000` or `0x5D0775D1`"
+                .to_string())
+        );
+    }
 }
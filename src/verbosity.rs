@@ -0,0 +1,40 @@
+/// How much a command prints to stderr, by `-q` and the number of `-v`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only warnings about inputs, like skipped lines.
+    Quiet,
+    /// Notices like deprecations, hints and summaries.
+    #[default]
+    Normal,
+    /// Details of the resolution, like the timezone, the precision and the parsed options.
+    Verbose,
+    /// Traces of each step, like deltas as they are applied.
+    Trace,
+}
+
+impl Verbosity {
+    /// Quiet takes precedence over verbose.
+    pub fn new(verbose: u64, quiet: bool) -> Verbosity {
+        match (quiet, verbose) {
+            (true, _) => Verbosity::Quiet,
+            (false, 0) => Verbosity::Normal,
+            (false, 1) => Verbosity::Verbose,
+            (false, _) => Verbosity::Trace,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::verbosity::Verbosity;
+
+    #[test]
+    fn new() {
+        assert_eq!(Verbosity::new(0, false), Verbosity::Normal);
+        assert_eq!(Verbosity::new(1, false), Verbosity::Verbose);
+        assert_eq!(Verbosity::new(2, false), Verbosity::Trace);
+        assert_eq!(Verbosity::new(3, false), Verbosity::Trace);
+        assert_eq!(Verbosity::new(2, true), Verbosity::Quiet);
+        assert!(Verbosity::Quiet < Verbosity::Normal);
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use strum::IntoEnumIterator;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+use crate::find::{FindByName, FindError, PossibleNames, PossibleValues};
+use crate::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum WeekStartError {
+    #[error("Wrong week start. error:{0}")]
+    WrongName(FindError),
+}
+
+impl From<FindError> for WeekStartError {
+    fn from(e: FindError) -> Self {
+        WeekStartError::WrongName(e)
+    }
+}
+
+impl IntoValidationError for WeekStartError {
+    fn into_validation_error(self) -> String {
+        use WeekStartError::*;
+        match self {
+            WrongName(e) => {
+                ValidationMessage::from_find_error("week start", &e, WeekStart::possible_names())
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// The first day of a week.
+#[derive(Debug, Copy, Clone, PartialEq, EnumIter, EnumString, Display)]
+pub enum WeekStart {
+    #[strum(serialize = "monday")]
+    Monday,
+
+    #[strum(serialize = "sunday")]
+    Sunday,
+}
+
+impl WeekStart {
+    /// Returns the number of days from the start of the week to the weekday.
+    pub fn days_from(self, weekday: Weekday) -> u32 {
+        match self {
+            WeekStart::Monday => weekday.num_days_from_monday(),
+            WeekStart::Sunday => weekday.num_days_from_sunday(),
+        }
+    }
+}
+
+impl PossibleValues for WeekStart {
+    type Iterator = WeekStartIter;
+
+    fn possible_values() -> Self::Iterator {
+        WeekStart::iter()
+    }
+}
+
+impl PossibleNames for WeekStart {}
+
+impl FindByName for WeekStart {
+    type Error = WeekStartError;
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NthWeekdayError {
+    #[error(
+        "Wrong nth weekday: '{0}'. text must be a number and a weekday, like `3monday` or `-1fri`."
+    )]
+    WrongFormat(String),
+
+    #[error("Wrong nth weekday: '{0}'. the number must be between 1 and 5, or -5 and -1.")]
+    WrongNumber(String),
+
+    #[error("Nonexistent date: there is no {0} in {1:04}-{2:02}.")]
+    Nonexistent(NthWeekday, i32, u32),
+}
+
+impl IntoValidationError for NthWeekdayError {
+    fn into_validation_error(self) -> String {
+        use NthWeekdayError::*;
+        let (problem, input) = match self {
+            WrongFormat(s) => ("Wrong nth weekday: text is not a number and a weekday.", s),
+            WrongNumber(s) => (
+                "Wrong nth weekday: the number must be between 1 and 5, or -5 and -1.",
+                s,
+            ),
+            Nonexistent(nth, ..) => (
+                "Wrong nth weekday: the date does not exist.",
+                nth.to_string(),
+            ),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted(
+                "a number and a weekday, like `3monday`, `2tue` or `-1friday` for the last one",
+            )
+            .to_string()
+    }
+}
+
+/// The nth weekday of a month, like the 3rd monday. Negative numbers count from the end of the month.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NthWeekday {
+    n: i8,
+    weekday: Weekday,
+}
+
+impl NthWeekday {
+    /// Returns the date in the month, if the month has the nth weekday.
+    pub fn date_in(self, year: i32, month: u32) -> Result<NaiveDate, NthWeekdayError> {
+        let nonexistent = || NthWeekdayError::Nonexistent(self, year, month);
+        let first = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(nonexistent)?;
+        let date = if self.n > 0 {
+            let days = (7 + self.weekday.num_days_from_monday()
+                - first.weekday().num_days_from_monday())
+                % 7;
+            first + Duration::days(i64::from(days) + 7 * i64::from(self.n - 1))
+        } else {
+            let last = first
+                .with_day(days_in_month(year, month))
+                .ok_or_else(nonexistent)?;
+            let days = (7 + last.weekday().num_days_from_monday()
+                - self.weekday.num_days_from_monday())
+                % 7;
+            last - Duration::days(i64::from(days) + 7 * i64::from(-self.n - 1))
+        };
+        if date.month() == month {
+            Ok(date)
+        } else {
+            Err(nonexistent())
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    next.and_then(|d| d.pred_opt()).map_or(31, |d| d.day())
+}
+
+impl fmt::Display for NthWeekday {
+    /// Writes it like `3rd monday`, or `last friday` for -1.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.weekday {
+            Weekday::Mon => "monday",
+            Weekday::Tue => "tuesday",
+            Weekday::Wed => "wednesday",
+            Weekday::Thu => "thursday",
+            Weekday::Fri => "friday",
+            Weekday::Sat => "saturday",
+            Weekday::Sun => "sunday",
+        };
+        let ordinal = |n: i8| match n {
+            1 => "1st".to_string(),
+            2 => "2nd".to_string(),
+            3 => "3rd".to_string(),
+            n => format!("{}th", n),
+        };
+        match self.n {
+            -1 => write!(f, "last {}", name),
+            n if n < 0 => write!(f, "{} last {}", ordinal(-n), name),
+            n => write!(f, "{} {}", ordinal(n), name),
+        }
+    }
+}
+
+impl FromStr for NthWeekday {
+    type Err = NthWeekdayError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wrong_format = || NthWeekdayError::WrongFormat(s.to_string());
+        let i = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or_else(wrong_format)?;
+        let (number, name) = s.split_at(i);
+        let n = number
+            .trim_start_matches('+')
+            .parse::<i8>()
+            .map_err(|_| wrong_format())?;
+        let weekday = Weekday::from_str(name).map_err(|_| wrong_format())?;
+        if n == 0 || !(-5..=5).contains(&n) {
+            return Err(NthWeekdayError::WrongNumber(s.to_string()));
+        }
+        Ok(NthWeekday { n, weekday })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{NaiveDate, Weekday};
+
+    use crate::find::FindByName;
+    use crate::week::{NthWeekday, NthWeekdayError, WeekStart};
+
+    #[test]
+    fn find_by_name() {
+        assert_eq!(WeekStart::find_by_name("monday"), Ok(WeekStart::Monday));
+        assert_eq!(WeekStart::find_by_name("sun"), Ok(WeekStart::Sunday));
+        assert!(WeekStart::find_by_name("saturday").is_err());
+    }
+
+    #[test]
+    fn days_from() {
+        assert_eq!(WeekStart::Monday.days_from(Weekday::Mon), 0);
+        assert_eq!(WeekStart::Monday.days_from(Weekday::Sat), 5);
+        assert_eq!(WeekStart::Monday.days_from(Weekday::Sun), 6);
+        assert_eq!(WeekStart::Sunday.days_from(Weekday::Sun), 0);
+        assert_eq!(WeekStart::Sunday.days_from(Weekday::Mon), 1);
+        assert_eq!(WeekStart::Sunday.days_from(Weekday::Sat), 6);
+    }
+
+    #[test]
+    fn nth_weekday_from_str() {
+        let nth = |n, weekday| NthWeekday { n, weekday };
+        assert_eq!(NthWeekday::from_str("3monday"), Ok(nth(3, Weekday::Mon)));
+        assert_eq!(NthWeekday::from_str("+2Tue"), Ok(nth(2, Weekday::Tue)));
+        assert_eq!(NthWeekday::from_str("-1fri"), Ok(nth(-1, Weekday::Fri)));
+
+        let wrong_format = |s: &str| Err(NthWeekdayError::WrongFormat(s.to_string()));
+        assert_eq!(NthWeekday::from_str("monday"), wrong_format("monday"));
+        assert_eq!(NthWeekday::from_str("3"), wrong_format("3"));
+        assert_eq!(NthWeekday::from_str("3moon"), wrong_format("3moon"));
+        assert_eq!(NthWeekday::from_str("3 monday"), wrong_format("3 monday"));
+        assert_eq!(
+            NthWeekday::from_str("0mon"),
+            Err(NthWeekdayError::WrongNumber("0mon".to_string()))
+        );
+        assert_eq!(
+            NthWeekday::from_str("-6mon"),
+            Err(NthWeekdayError::WrongNumber("-6mon".to_string()))
+        );
+    }
+
+    #[test]
+    fn nth_weekday_date_in() {
+        let date_in = |s: &str, year, month| NthWeekday::from_str(s).unwrap().date_in(year, month);
+        let ymd = |y, m, d| Ok(NaiveDate::from_ymd_opt(y, m, d).unwrap());
+
+        // 2019-06-01 is a saturday, and 2019-06-30 is a sunday.
+        assert_eq!(date_in("3monday", 2019, 6), ymd(2019, 6, 17));
+        assert_eq!(date_in("1sat", 2019, 6), ymd(2019, 6, 1));
+        assert_eq!(date_in("5sun", 2019, 6), ymd(2019, 6, 30));
+        assert_eq!(date_in("-1friday", 2019, 6), ymd(2019, 6, 28));
+        assert_eq!(date_in("-1sun", 2019, 6), ymd(2019, 6, 30));
+        assert_eq!(date_in("-5sat", 2019, 6), ymd(2019, 6, 1));
+        assert_eq!(date_in("-1fri", 2019, 12), ymd(2019, 12, 27));
+        assert_eq!(date_in("-1thu", 2020, 2), ymd(2020, 2, 27));
+
+        // 2019-02 has 4 fridays.
+        let fifth_friday = NthWeekday::from_str("5fri").unwrap();
+        assert_eq!(
+            fifth_friday.date_in(2019, 2),
+            Err(NthWeekdayError::Nonexistent(fifth_friday, 2019, 2))
+        );
+        assert_eq!(
+            fifth_friday.date_in(2019, 2).unwrap_err().to_string(),
+            "Nonexistent date: there is no 5th friday in 2019-02."
+        );
+        assert_eq!(
+            NthWeekday::from_str("-2mon").unwrap().to_string(),
+            "2nd last monday"
+        );
+        assert_eq!(
+            NthWeekday::from_str("-1mon").unwrap().to_string(),
+            "last monday"
+        );
+    }
+}
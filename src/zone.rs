@@ -0,0 +1,145 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use thiserror::Error;
+
+use ut_cli::offset::Offset;
+use ut_cli::validate::{IntoValidationError, ValidationMessage};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ZoneError {
+    #[error("Wrong zone: '{0}'. zone must be `UTC`, `local` or an offset like `+09:00`.")]
+    WrongZone(String),
+
+    #[error("Wrong zones: '{0}'. zones must not be empty.")]
+    Empty(String),
+}
+
+impl IntoValidationError for ZoneError {
+    fn into_validation_error(self) -> String {
+        use ZoneError::*;
+        let (problem, input) = match self {
+            // IANA names need a timezone database, which is not bundled.
+            WrongZone(s) => (
+                "Wrong zone: it must be `UTC`, `local` or an offset. IANA names are not supported.",
+                s,
+            ),
+            Empty(s) => ("Wrong zones: no zone is given.", s),
+        };
+        ValidationMessage::new(problem, input)
+            .accepted("comma-separated zones, like `UTC,+09:00,local`")
+            .to_string()
+    }
+}
+
+/// A timezone to print an instant in.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Zone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Formats the instant in the zone.
+    pub fn format<Tz: TimeZone>(self, dt: &DateTime<Tz>, fmt: &str) -> String {
+        match self {
+            Zone::Utc => dt.with_timezone(&Utc).format(fmt).to_string(),
+            Zone::Local => dt.with_timezone(&Local).format(fmt).to_string(),
+            Zone::Fixed(offset) => dt.with_timezone(&offset).format(fmt).to_string(),
+        }
+    }
+}
+
+impl FromStr for Zone {
+    type Err = ZoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" | "z" => Ok(Zone::Utc),
+            "local" => Ok(Zone::Local),
+            _ => Offset::from_str(s)
+                .map(|offset| Zone::Fixed(offset.into()))
+                .map_err(|_| ZoneError::WrongZone(s.to_string())),
+        }
+    }
+}
+
+/// Zones with their names as given, like `UTC,+09:00`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zones(Vec<(String, Zone)>);
+
+impl Zones {
+    pub fn iter(&self) -> impl Iterator<Item = &(String, Zone)> {
+        self.0.iter()
+    }
+}
+
+impl FromStr for Zones {
+    type Err = ZoneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let zones = s
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| Zone::from_str(name).map(|zone| (name.to_string(), zone)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if zones.is_empty() {
+            Err(ZoneError::Empty(s.to_string()))
+        } else {
+            Ok(Zones(zones))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use chrono::{FixedOffset, TimeZone, Utc};
+
+    use crate::zone::{Zone, ZoneError, Zones};
+
+    #[test]
+    fn zone_from_str() {
+        assert_eq!(Zone::from_str("UTC"), Ok(Zone::Utc));
+        assert_eq!(Zone::from_str("z"), Ok(Zone::Utc));
+        assert_eq!(Zone::from_str("Local"), Ok(Zone::Local));
+        assert_eq!(
+            Zone::from_str("+09:00"),
+            Ok(Zone::Fixed(FixedOffset::east_opt(9 * 3600).unwrap()))
+        );
+        assert_eq!(
+            Zone::from_str("America/New_York"),
+            Err(ZoneError::WrongZone("America/New_York".to_string()))
+        );
+    }
+
+    #[test]
+    fn zones_from_str() {
+        let zones = Zones::from_str("UTC, +09:00,-5").unwrap();
+        let names: Vec<&str> = zones.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["UTC", "+09:00", "-5"]);
+
+        assert_eq!(
+            Zones::from_str("UTC,Asia/Tokyo,+09:00"),
+            Err(ZoneError::WrongZone("Asia/Tokyo".to_string()))
+        );
+        assert_eq!(
+            Zones::from_str(" , "),
+            Err(ZoneError::Empty(" , ".to_string()))
+        );
+    }
+
+    #[test]
+    fn format() {
+        let dt = Utc.timestamp_opt(1_560_762_129, 0).unwrap();
+        let fmt = "%Y-%m-%d %H:%M:%S (%Z)";
+        assert_eq!(Zone::Utc.format(&dt, fmt), "2019-06-17 09:02:09 (UTC)");
+        assert_eq!(
+            Zone::from_str("+09:00").unwrap().format(&dt, fmt),
+            "2019-06-17 18:02:09 (+09:00)"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::process::{Command, Output};
+
+fn ut(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("SOURCE_DATE_EPOCH")
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn reproducible_presets() {
+    let args = [
+        "--at",
+        "2019-06-17T20:00:00Z",
+        "-o",
+        "+09:00",
+        "g",
+        "-b",
+        "today",
+    ];
+    assert_eq!(stdout(ut(&args)), stdout(ut(&args)));
+    // today is taken in the timezone of the provider.
+    assert_eq!(stdout(ut(&args)).trim(), "1560783600");
+
+    assert_eq!(
+        stdout(ut(&["--at", "1560762129", "-u", "g", "-b", "yesterday"])).trim(),
+        "1560643200"
+    );
+    assert_eq!(
+        // the timestamp is read in the precision.
+        stdout(ut(&["--at", "1560762129000", "-p", "ms", "--utc-now"])).trim(),
+        "1560762129000"
+    );
+}
+
+#[test]
+fn reproducible_relative() {
+    let args = ["--at", "1560800000", "-u", "p", "--pretty", "1560762129"];
+    let output = stdout(ut(&args));
+    assert_eq!(output, stdout(ut(&args)));
+    assert!(output.contains("Relative: 10:31:11 ago"), "{}", output);
+}
+
+#[test]
+fn env_and_precedence() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env("UT_NOW", "2019-06-17T09:02:09Z")
+        .env("SOURCE_DATE_EPOCH", "0")
+        .args(["-u", "g"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(output).trim(), "1560762129");
+
+    // the flag takes precedence over the environment.
+    let output = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env("UT_NOW", "0")
+        .args(["--at", "100", "-u", "g"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(output).trim(), "100");
+}
+
+#[test]
+fn week_presets() {
+    let week = |week_start: &str, at: &str, preset: &str| {
+        let output = Command::new(env!("CARGO_BIN_EXE_ut"))
+            .env("UT_WEEK_START", week_start)
+            .args(["--at", at, "-u", "g", "-b", preset])
+            .output()
+            .unwrap();
+        stdout(output).trim().to_string()
+    };
+
+    // 2019-06-16 is a sunday.
+    let sunday = "2019-06-16T12:00:00Z";
+    assert_eq!(week("monday", sunday, "start-of-week"), "1560124800");
+    assert_eq!(week("sunday", sunday, "start-of-week"), "1560643200");
+    assert_eq!(week("sunday", sunday, "end-of-week"), "1561161600");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env("UT_WEEK_START", "saturday")
+        .args(["-u", "g", "-b", "today"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Wrong week start in the config or UT_WEEK_START."));
+}
+
+#[test]
+fn wrong_at() {
+    let output = ut(&["--at", "yesterday", "g"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Wrong instant"));
+}
+
+#[test]
+fn presets_out_of_range() {
+    // +262143-12-31 and -262144-01-01 are the last and the first representable dates.
+    let output = ut(&["--at", "8210298412799", "-u", "g", "-b", "tomorrow"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: Can't get tomorrow of +262143-12-31. the date is out of range."
+    );
+
+    let output = ut(&["--at", "-8334632851200", "-u", "g", "-b", "yesterday"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr).trim(),
+        "error: Can't get yesterday of -262144-01-01. the date is out of range."
+    );
+}
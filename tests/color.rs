@@ -0,0 +1,73 @@
+use std::process::{Command, Output};
+
+fn ut(args: &[&str], envs: &[(&str, &str)]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("NO_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .env_remove("TERM")
+        .envs(envs.iter().copied())
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn has_escapes(bytes: &[u8]) -> bool {
+    bytes.contains(&0x1b)
+}
+
+#[test]
+fn help() {
+    let output = ut(&["--color", "always", "--help"], &[]);
+    assert!(output.status.success());
+    assert!(has_escapes(&output.stdout));
+
+    for args in [
+        &["--color", "never", "--help"][..],
+        &["--color=never", "p", "--help"],
+        &["--help"],
+    ] {
+        let output = ut(args, &[]);
+        assert!(output.status.success());
+        assert!(!has_escapes(&output.stdout), "{:?}", args);
+    }
+}
+
+#[test]
+fn help_by_env() {
+    let output = ut(&["--help"], &[("NO_COLOR", "1")]);
+    assert!(!has_escapes(&output.stdout));
+    let output = ut(
+        &["g", "--help"],
+        &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")],
+    );
+    assert!(!has_escapes(&output.stdout));
+
+    let output = ut(&["--help"], &[("CLICOLOR_FORCE", "1")]);
+    assert!(has_escapes(&output.stdout));
+    // the option takes precedence over the variables.
+    let output = ut(&["--color", "never", "--help"], &[("CLICOLOR_FORCE", "1")]);
+    assert!(!has_escapes(&output.stdout));
+    let output = ut(&["--color", "always", "--help"], &[("NO_COLOR", "1")]);
+    assert!(has_escapes(&output.stdout));
+}
+
+#[test]
+fn errors() {
+    let output = ut(&["--color", "always", "-u", "p", "--", "x"], &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("\u{1b}[1;31merror:\u{1b}[0m "));
+
+    let output = ut(&["-u", "p", "--", "x"], &[("CLICOLOR_FORCE", "1")]);
+    assert!(has_escapes(&output.stderr));
+    let output = ut(&["--color", "never", "-u", "p", "--", "x"], &[]);
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: "));
+
+    // a wrong choice is reported by the validator.
+    let output = ut(&["--color", "sometimes", "-u", "g"], &[]);
+    assert!(!output.status.success());
+    assert!(!has_escapes(&output.stderr));
+}
@@ -0,0 +1,141 @@
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn generate(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .arg("--utc")
+        .arg("generate")
+        .args(args)
+        .output()
+        .expect("failed to run ut");
+    assert!(
+        output.status.success(),
+        "ut generate {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn signed_deltas() {
+    assert_eq!(generate(&["--zero-base", "-d", "+10h"]), "36000");
+    assert_eq!(generate(&["--zero-base", "-d", "-10h"]), "-36000");
+    assert_eq!(generate(&["--zero-base", "-d", "10h"]), "36000");
+}
+
+#[test]
+fn iso8601_deltas() {
+    let base = ["--ymd", "2019-06-17"];
+    assert_eq!(
+        generate(&[&base[..], &["-d", "P1Y2M10DT2H30M"]].concat()),
+        generate(
+            &[
+                &base[..],
+                &["-d", "1y", "-d", "2mon", "-d", "10d", "-d", "2h", "-d", "30min"]
+            ]
+            .concat()
+        )
+    );
+    assert_eq!(
+        generate(&["--zero-base", "-d", "-PT1H", "-d", "2h"]),
+        "3600"
+    );
+}
+
+#[test]
+fn signed_deltas_in_any_position() {
+    for delta in &["+10h", "-10h", "10h"] {
+        let expected = if delta.starts_with('-') {
+            "-36000"
+        } else {
+            "36000"
+        };
+        assert_eq!(generate(&["-d", delta, "--zero-base"]), expected);
+        assert_eq!(generate(&["-q", "-d", delta, "--zero-base"]), expected);
+        assert_eq!(
+            generate(&["--zero-base", "-d", delta, "-t", "hour"]),
+            expected
+        );
+        assert_eq!(generate(&["-d", delta, "0"]), expected);
+        assert_eq!(generate(&["0", "-d", delta]), expected);
+        assert_eq!(generate(&["--zero-base", "--delta", delta]), expected);
+        assert_eq!(
+            generate(&["--zero-base", &format!("--delta={}", delta)]),
+            expected
+        );
+        assert_eq!(
+            generate(&["--zero-base", &format!("-d{}", delta)]),
+            expected
+        );
+    }
+}
+
+#[test]
+fn mixed_signed_deltas() {
+    assert_eq!(
+        generate(&["--zero-base", "-d", "+1d", "-d", "-10h", "-d", "2h"]),
+        "57600"
+    );
+    assert_eq!(generate(&["-d", "-1h", "-d", "+30min", "3600"]), "1800");
+}
+
+#[test]
+fn hex_round_trip() {
+    assert_eq!(generate(&["--radix", "hex", "1560770001"]), "0x5D0775D1");
+    assert_eq!(generate(&["--radix", "hex", "-d", "-1s", "0"]), "-0x1");
+    assert_eq!(generate(&["0x5D0775D1"]), "1560770001");
+
+    let hex = generate(&["--radix", "hex", "--ymd", "20190617", "--hms", "112233"]);
+    assert_eq!(generate(&["--radix", "dec", &hex]), "1560770553");
+}
+
+fn generate_stdin(args: &[&str]) -> Output {
+    let input = fs::read("tests/fixtures/datetimes.txt").unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .args(["--utc", "generate", "--stdin"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    child.stdin.take().unwrap().write_all(&input).unwrap();
+    child.wait_with_output().expect("failed to wait ut")
+}
+
+#[test]
+fn lines_from_stdin() {
+    let output = generate_stdin(&["--skip-invalid", "-d", "1h"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1560774153\n1560819600\n1560906000\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("line 4: Wrong date."), "{}", stderr);
+    assert!(
+        stderr.ends_with("converted 3 timestamps (1 error)\n"),
+        "{}",
+        stderr
+    );
+
+    let output = generate_stdin(&["--skip-invalid", "-q"]);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("converted"), "{}", stderr);
+
+    // without --skip-invalid, lines before the invalid one are still printed.
+    let output = generate_stdin(&["-d", "1h"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1560774153\n1560819600\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.starts_with("error: line 4: Wrong date."),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,128 @@
+use std::process::{Command, Output};
+
+/// Runs ut with the local timezone set by `TZ`, and the current time frozen with `--at`.
+fn ut(tz: &str, at: &str, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env("TZ", tz)
+        .env_remove("UT_OFFSET")
+        .env_remove("UT_NOW")
+        .args(["--at", at])
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn preset(tz: &str, at: &str, preset: &str) -> String {
+    let output = ut(tz, at, &["g", "-b", preset]);
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn sao_paulo() {
+    // 2018-02-17 is 25 hours long, DST ends at the midnight of 2018-02-18.
+    let tz = "America/Sao_Paulo";
+    assert_eq!(
+        preset(tz, "2018-02-17T12:00:00-02:00", "today"),
+        "1518832800"
+    );
+    assert_eq!(
+        preset(tz, "2018-02-18T12:00:00-03:00", "yesterday"),
+        "1518832800"
+    );
+}
+
+#[test]
+fn lord_howe() {
+    // DST shifts by 30 minutes, so 2019-04-07 is 24.5 hours and 2019-10-06 is 23.5 hours long.
+    let tz = "Australia/Lord_Howe";
+    assert_eq!(
+        preset(tz, "2019-04-07T12:00:00+10:30", "tomorrow"),
+        "1554643800"
+    );
+    assert_eq!(
+        preset(tz, "2019-04-08T12:00:00+10:30", "yesterday"),
+        "1554555600"
+    );
+    assert_eq!(
+        preset(tz, "2019-10-05T12:00:00+10:30", "tomorrow"),
+        "1570282200"
+    );
+    assert_eq!(
+        preset(tz, "2019-10-07T12:00:00+11:00", "yesterday"),
+        "1570282200"
+    );
+}
+
+#[test]
+fn santiago() {
+    // DST starts at the midnight of 2019-09-08, so the day starts at 01:00.
+    let tz = "America/Santiago";
+    assert_eq!(
+        preset(tz, "2019-09-08T12:00:00-03:00", "today"),
+        "1567915200"
+    );
+    assert_eq!(
+        preset(tz, "2019-09-09T12:00:00-03:00", "yesterday"),
+        "1567915200"
+    );
+    assert_eq!(
+        preset(tz, "2019-09-07T12:00:00-04:00", "tomorrow"),
+        "1567915200"
+    );
+}
+
+#[test]
+fn assume_local() {
+    // the wall-clock of 2019-03-10T02:30:00Z is skipped in New York, so the time after the gap is taken.
+    let parse = |timestamp: &str| {
+        let output = ut(
+            "America/New_York",
+            "2019-01-01T00:00:00Z",
+            &["p", "--assume-local", "-f", "%FT%T%:z", timestamp],
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    assert_eq!(parse("1547510400"), "2019-01-15T00:00:00-05:00");
+    assert_eq!(parse("1563148800"), "2019-07-15T00:00:00-04:00");
+    assert_eq!(parse("1552185000"), "2019-03-10T03:00:00-04:00");
+}
+
+#[test]
+fn fold() {
+    // 01:30 occurs twice on 2019-11-03 in New York, first in EDT and then in EST.
+    let generate = |args: &[&str]| {
+        let mut all = vec!["g", "--ymd", "2019-11-03", "--hms", "01:30:00"];
+        all.extend_from_slice(args);
+        let output = ut("America/New_York", "2019-11-01T00:00:00Z", &all);
+        assert!(
+            output.status.success(),
+            "ut failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap().trim().to_string()
+    };
+    assert_eq!(generate(&[]), "1572759000");
+    assert_eq!(generate(&["--fold", "earlier"]), "1572759000");
+    assert_eq!(generate(&["--fold", "later"]), "1572762600");
+
+    // a time in the gap does not exist, whichever fold is selected.
+    let output = ut(
+        "America/New_York",
+        "2019-03-01T00:00:00Z",
+        &[
+            "g",
+            "--ymd",
+            "2019-03-10",
+            "--hms",
+            "02:30:00",
+            "--fold",
+            "later",
+        ],
+    );
+    assert!(!output.status.success());
+}
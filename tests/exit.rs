@@ -0,0 +1,73 @@
+use std::ffi::OsStr;
+use std::process::Command;
+
+fn exit_code<S: AsRef<OsStr>>(envs: &[(&str, &str)], args: &[S]) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("UT_OFFSET")
+        .env_remove("UT_PRECISION")
+        .env_remove("SOURCE_DATE_EPOCH")
+        .envs(envs.iter().copied())
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+        .status
+        .code()
+}
+
+#[test]
+fn success() {
+    assert_eq!(exit_code(&[], &["-u", "p", "0"]), Some(0));
+    assert_eq!(exit_code(&[], &["--utc-now"]), Some(0));
+}
+
+#[test]
+fn input_error() {
+    assert_eq!(exit_code(&[], &["p", "abc"]), Some(1));
+    assert_eq!(exit_code(&[], &["-o", "+25:00", "p", "0"]), Some(1));
+    assert_eq!(
+        exit_code(&[], &["-u", "g", "--ymd", "20190131", "-d", "1mon"]),
+        Some(1)
+    );
+    assert_eq!(exit_code(&[], &["--utc-now", "parse", "0"]), Some(1));
+    assert_eq!(exit_code(&[], &["--utc-now", "g", "-d", "1d"]), Some(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn non_utf8_argument() {
+    use std::os::unix::ffi::OsStrExt;
+
+    // clap reports it as a wrong argument, instead of panicking.
+    let arg = OsStr::from_bytes(b"\xff");
+    assert_eq!(exit_code(&[], &[OsStr::new("p"), arg]), Some(1));
+    assert_eq!(
+        exit_code(&[], &[OsStr::new("--strict-names"), arg]),
+        Some(1)
+    );
+}
+
+#[test]
+fn environment_error() {
+    assert_eq!(exit_code(&[("UT_OFFSET", "bad")], &["p", "0"]), Some(2));
+    assert_eq!(exit_code(&[("UT_PRECISION", "bad")], &["p", "0"]), Some(2));
+    assert_eq!(exit_code(&[("SOURCE_DATE_EPOCH", "x")], &["g"]), Some(2));
+    assert_eq!(exit_code(&[("UT_CONFIG", "/")], &["p", "0"]), Some(2));
+}
+
+#[test]
+fn overflow_error() {
+    assert_eq!(exit_code(&[], &["-u", "p", "9223372036854775807"]), Some(3));
+    assert_eq!(
+        exit_code(&[], &["-u", "g", "--ymd", "20190131", "-d", "10000000y"]),
+        Some(3)
+    );
+    assert_eq!(
+        exit_code(&[], &["p", "--epoch-day", "9223372036854775807"]),
+        Some(3)
+    );
+    assert_eq!(
+        exit_code(&[], &["-u", "g", "-t", "century", "--", "-8334632851200"]),
+        Some(3)
+    );
+}
@@ -0,0 +1,65 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn ut(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("UT_OFFSET")
+        .env_remove("UT_OUTPUT")
+        .env_remove("SOURCE_DATE_EPOCH")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn timestamps(seed: &str) -> Vec<i64> {
+    let lines = "2019-06-17\n".repeat(20);
+    let args = ["-u", "g", "--stdin", "--jitter", "1h", "--seed", seed];
+    let output = ut(&args, &lines);
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn seed() {
+    let first = timestamps("42");
+    assert_eq!(first.len(), 20);
+    assert!(first
+        .iter()
+        .all(|t| (1_560_729_600 - 3600..=1_560_729_600 + 3600).contains(t)));
+    // each line has its own offset.
+    assert!(first.iter().any(|t| *t != first[0]));
+
+    assert_eq!(timestamps("42"), first);
+    assert_ne!(timestamps("43"), first);
+}
+
+#[test]
+fn wrong_jitter() {
+    let output = ut(&["-u", "g", "--jitter", "1mon"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Wrong jitter: '1mon'."));
+
+    let output = ut(&["-u", "g", "--seed", "1"], "");
+    assert!(!output.status.success());
+    let output = ut(&["-u", "g", "--jitter", "1h", "--seed", "-1"], "");
+    assert!(!output.status.success());
+}
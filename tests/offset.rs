@@ -0,0 +1,69 @@
+use std::process::{Command, Output};
+
+fn ut(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("UT_OFFSET")
+        .env("TZ", "America/New_York")
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn stderr(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn print_offset() {
+    let output = ut(&["-o", "+9", "--print-offset", "p", "0"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "1970-01-01 09:00:00 (+09:00)"
+    );
+    assert_eq!(stderr(output), "offset: +09:00\n");
+
+    assert_eq!(
+        stderr(ut(&["-u", "--print-offset", "g", "-b", "today"])),
+        "offset: +00:00\n"
+    );
+    assert_eq!(
+        stderr(ut(&["--print-offset", "--utc-now"])),
+        "offset: +00:00\n"
+    );
+    assert_eq!(stderr(ut(&["-o", "+9", "p", "0"])), "");
+}
+
+#[test]
+fn print_local_offset_at_frozen_time() {
+    // the local offset is taken at the frozen time, in or out of DST.
+    assert_eq!(
+        stderr(ut(&["--at", "2019-01-15T00:00:00Z", "--print-offset", "g"])),
+        "offset: -05:00\n"
+    );
+    assert_eq!(
+        stderr(ut(&["--at", "2019-07-15T00:00:00Z", "--print-offset", "g"])),
+        "offset: -04:00\n"
+    );
+}
+
+#[test]
+fn decimal_hours() {
+    let output = ut(&["-o", "+5.5", "p", "0"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "1970-01-01 05:30:00 (+05:30)"
+    );
+    let output = ut(&["-o", "-9.5", "--print-offset", "p", "0"]);
+    assert_eq!(stderr(output), "offset: -09:30\n");
+
+    let output = ut(&["-o", "+5.01", "p", "0"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Wrong offset: fraction of hours must be a whole number of minutes."));
+}
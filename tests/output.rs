@@ -0,0 +1,226 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn ut(args: &[&str], envs: &[(&str, &str)], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("UT_OFFSET")
+        .env_remove("UT_OUTPUT")
+        .envs(envs.iter().copied())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn generate() {
+    let args = |format: &'static str| {
+        vec![
+            "-u",
+            "--output",
+            format,
+            "g",
+            "--ymd",
+            "2019-06-17",
+            "--hms",
+            "09:02:09",
+        ]
+    };
+    assert_eq!(stdout(ut(&args("plain"), &[], "")), "1560762129\n");
+    assert_eq!(
+        stdout(ut(&args("json"), &[], "")),
+        "{\"timestamp\":1560762129,\"precision\":\"second\"}\n"
+    );
+    assert_eq!(
+        stdout(ut(&args("csv"), &[], "")),
+        "timestamp,precision\n1560762129,second\n"
+    );
+}
+
+#[test]
+fn generate_lines() {
+    // records of lines are JSON lines, and CSV has a single header.
+    let lines = "2019-06-17\n2019-06-18\n";
+    let args = |format: &'static str| vec!["-u", "--output", format, "g", "--stdin"];
+    assert_eq!(
+        stdout(ut(&args("json"), &[], lines)),
+        "{\"timestamp\":1560729600,\"precision\":\"second\"}\n\
+         {\"timestamp\":1560816000,\"precision\":\"second\"}\n"
+    );
+    assert_eq!(
+        stdout(ut(&args("csv"), &[], lines)),
+        "timestamp,precision\n1560729600,second\n1560816000,second\n"
+    );
+}
+
+#[test]
+fn parse() {
+    let args = |format: &'static str| {
+        vec![
+            "-u",
+            "--output",
+            format,
+            "p",
+            "-f",
+            "%Y-%m-%d, %H:%M:%S",
+            "1560762129",
+        ]
+    };
+    assert_eq!(
+        stdout(ut(&args("json"), &[], "")),
+        "{\"timestamp\":1560762129,\"datetime\":\"2019-06-17, 09:02:09\"}\n"
+    );
+    assert_eq!(
+        stdout(ut(&args("csv"), &[], "")),
+        "timestamp,datetime\n1560762129,\"2019-06-17, 09:02:09\"\n"
+    );
+
+    let output = ut(&["-u", "--output", "csv", "p", "--pretty", "0"], &[], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("--pretty cannot be used with --output csv."));
+}
+
+#[test]
+fn output_from_env() {
+    let envs = [("UT_OUTPUT", "json")];
+    assert_eq!(
+        stdout(ut(&["-u", "p", "-f", "%F", "0"], &envs, "")),
+        "{\"timestamp\":0,\"datetime\":\"1970-01-01\"}\n"
+    );
+    // the option takes precedence over the environment.
+    assert_eq!(
+        stdout(ut(
+            &["-u", "--output", "plain", "p", "-f", "%F", "0"],
+            &envs,
+            ""
+        )),
+        "1970-01-01\n"
+    );
+
+    let output = ut(&["-u", "p", "0"], &[("UT_OUTPUT", "xml")], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn no_newline() {
+    let output = ut(&["-n", "-u", "g", "--ymd", "2019-06-17"], &[], "");
+    assert_eq!(output.stdout, b"1560729600");
+    let output = ut(&["--no-newline", "-u", "--utc-now", "--at", "0"], &[], "");
+    assert_eq!(output.stdout, b"0");
+    let output = ut(&["-n", "-u", "p", "-f", "%F", "0"], &[], "");
+    assert_eq!(output.stdout, b"1970-01-01");
+
+    // only the newline of the last line is not printed.
+    let lines = "2019-06-17\n2019-06-18\n";
+    let output = ut(&["-n", "-u", "g", "--stdin"], &[], lines);
+    assert_eq!(output.stdout, b"1560729600\n1560816000");
+    let output = ut(
+        &["-n", "-u", "p", "--extract"],
+        &[],
+        "a 1560762129\nb 1560816000\n",
+    );
+    assert_eq!(
+        output.stdout,
+        b"a 2019-06-17 09:02:09 (UTC)\nb 2019-06-18 00:00:00 (UTC)"
+    );
+    let output = ut(&["-n", "-u", "--output", "csv", "g", "--stdin"], &[], lines);
+    assert_eq!(
+        output.stdout,
+        b"timestamp,precision\n1560729600,second\n1560816000,second"
+    );
+}
+
+#[test]
+fn table() {
+    // columns are aligned over the whole input, and numbers are aligned to the right.
+    let lines = "1970-01-01\n2019-06-17\n1960-01-01\n1970-01-02\n";
+    assert_eq!(
+        stdout(ut(&["-u", "--output", "table", "g", "--stdin"], &[], lines)),
+        " timestamp  precision\n\
+         \x20        0  second\n\
+         1560729600  second\n\
+         -315619200  second\n\
+         \x20    86400  second\n"
+    );
+    assert_eq!(
+        stdout(ut(
+            &[
+                "-u",
+                "--output",
+                "table",
+                "-p",
+                "ms",
+                "g",
+                "--ymd",
+                "2019-06-17"
+            ],
+            &[],
+            ""
+        )),
+        "    timestamp  precision\n1560729600000  millisecond\n"
+    );
+
+    // localized datetimes are cells as they are, without trailing spaces.
+    let parse = |locale: &'static str| {
+        let args = [
+            "-u",
+            "--output",
+            "table",
+            "p",
+            "--locale",
+            locale,
+            "-f",
+            "%B|",
+            "1560762129",
+        ];
+        stdout(ut(&args, &[], ""))
+    };
+    assert_eq!(parse("ja"), " timestamp  datetime\n1560762129  6月|\n");
+    assert_eq!(parse("fr"), " timestamp  datetime\n1560762129  juin|\n");
+
+    // rows after the first 1000 are streamed in the widths of them, so a wider cell is ragged.
+    let lines = format!("{}2019-06-17\n", "1970-01-01\n".repeat(1000));
+    let out = stdout(ut(
+        &["-u", "--output", "table", "g", "--stdin"],
+        &[],
+        &lines,
+    ));
+    let rows: Vec<&str> = out.lines().collect();
+    assert_eq!(rows.len(), 1002);
+    assert_eq!(rows[0], "timestamp  precision");
+    assert_eq!(rows[1000], "        0  second");
+    assert_eq!(rows[1001], "1560729600  second");
+
+    // rows before an error are printed.
+    let output = ut(
+        &["-u", "--output", "table", "g", "--stdin"],
+        &[],
+        "1970-01-01\nxx\n2019-06-17\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"timestamp  precision\n        0  second\n");
+
+    let output = ut(&["-u", "--output", "table", "p", "--pretty", "0"], &[], "");
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("--pretty cannot be used with --output table."));
+}
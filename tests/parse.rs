@@ -0,0 +1,317 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn parse_stdin(stdin: Stdio, input: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .args(["--utc", "parse"])
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    if let Some(input) = input {
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+    }
+    child.wait_with_output().expect("failed to wait ut")
+}
+
+#[test]
+fn piped_stdin() {
+    let output = parse_stdin(Stdio::piped(), Some("1560762129\n"));
+    assert!(
+        output.status.success(),
+        "ut parse failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        "2019-06-17 09:02:09 (UTC)"
+    );
+}
+
+#[test]
+fn fractional_stdin() {
+    let output = parse_stdin(Stdio::piped(), Some("1560762129.5\n1560762130\n"));
+    assert!(
+        output.status.success(),
+        "ut parse failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        "2019-06-17 09:02:09 (UTC)"
+    );
+}
+
+#[test]
+fn hex_stdin() {
+    let output = parse_stdin(Stdio::piped(), Some("0x5D0775D1\n"));
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        "2019-06-17 11:13:21 (UTC)"
+    );
+}
+
+#[test]
+fn redirected_stdin() {
+    // /dev/null is not a terminal, so it is read as an empty input.
+    let output = parse_stdin(Stdio::null(), None);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap().trim(),
+        "error: no timestamp provided: pass TIMESTAMP as an argument or pipe a value on stdin"
+    );
+}
+
+#[test]
+fn json_lines() {
+    let input = "{\"ts\": 1560762129}\n\n{\"ts\": 1560765729, \"id\": 2}\n";
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .args([
+            "--utc",
+            "parse",
+            "--json-input",
+            "--key",
+            "ts",
+            "--inject",
+            "at",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().expect("failed to wait ut");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"ts\": 1560762129,\"at\":\"2019-06-17 09:02:09 (UTC)\"}\n\
+         {\"ts\": 1560765729, \"id\": 2,\"at\":\"2019-06-17 10:02:09 (UTC)\"}\n"
+    );
+}
+
+#[test]
+fn null_delimited() {
+    let run = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+            .args(["--utc", "parse"])
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("failed to run ut");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().expect("failed to wait ut");
+        assert!(
+            output.status.success(),
+            "ut parse {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(run(&["-0"], "1560762129\0"), "2019-06-17 09:02:09 (UTC)\0");
+    assert_eq!(
+        run(&["--null", "--extract"], "a=1560762129\nb\x001560765729\0"),
+        "a=2019-06-17 09:02:09 (UTC)\nb\x002019-06-17 10:02:09 (UTC)\0"
+    );
+    assert_eq!(
+        run(
+            &["-0", "--json-input", "--key", "ts"],
+            "{\"ts\": 0}\0{\n\"ts\": 1}"
+        ),
+        "1970-01-01 00:00:00 (UTC)\x001970-01-01 00:00:01 (UTC)\0"
+    );
+}
+
+#[test]
+fn closed_stdout() {
+    use std::io::{BufRead, BufReader};
+    use std::thread;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .args(["--utc", "parse", "--extract"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+
+    // ut may stop reading before the end of the input, so the result of writing is ignored.
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || {
+        for _ in 0..100_000 {
+            if stdin.write_all(b"1560762129\n").is_err() {
+                break;
+            }
+        }
+    });
+
+    // read a few lines like `head -n 3`, then close the pipe.
+    let lines = BufReader::new(child.stdout.take().unwrap())
+        .lines()
+        .take(3)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(lines, vec!["2019-06-17 09:02:09 (UTC)"; 3]);
+
+    let output = child.wait_with_output().expect("failed to wait ut");
+    writer.join().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
+
+fn parse_input(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ut"))
+        .args(["--utc", "parse"])
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run ut");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().expect("failed to wait ut")
+}
+
+#[test]
+fn json_array() {
+    let input = "{\"ts\": 0}\n{\"ts\": 1560762129, \"id\": 2}\n\n{\"ts\": -1}\n";
+    let output = parse_input(&["--json-input", "--key", "ts", "--json-array"], input);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        r#"["1970-01-01 00:00:00 (UTC)","2019-06-17 09:02:09 (UTC)","1969-12-31 23:59:59 (UTC)"]
+"#
+    );
+
+    let output = parse_input(
+        &[
+            "--json-input",
+            "--key",
+            "ts",
+            "--inject",
+            "at",
+            "--json-array",
+        ],
+        input,
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        r#"[{"ts": 0,"at":"1970-01-01 00:00:00 (UTC)"},{"ts": 1560762129, "id": 2,"at":"2019-06-17 09:02:09 (UTC)"},{"ts": -1,"at":"1969-12-31 23:59:59 (UTC)"}]
+"#
+    );
+
+    let output = parse_input(&["--extract", "--json-array"], "a=1560762129\n\"b\"\n");
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "[\"a=2019-06-17 09:02:09 (UTC)\",\"\\\"b\\\"\"]\n"
+    );
+
+    // the array is printed even if there are no inputs.
+    let output = parse_input(&["--json-input", "--key", "ts", "--json-array"], "");
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "[]\n");
+
+    // a single timestamp is not a batch.
+    let output = parse_input(&["--json-array", "0"], "");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn summary() {
+    // the timestamp in the last line is out of range, so it is kept as it is.
+    let input = "a=1560762129 b=1560765729\nno timestamps\nc=0 d=999999999999999999\n";
+    let output = parse_input(&["--extract", "--digits", "1-19"], input);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "converted 3 timestamps (1 error)\n"
+    );
+
+    let output = parse_input(&["--extract", "--digits", "1-19", "-q"], input);
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+
+    let output = parse_input(
+        &["--json-input", "--key", "ts"],
+        "{\"ts\": 0}\n\n{\"ts\": 1}\n",
+    );
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "converted 2 timestamps (0 errors)\n"
+    );
+
+    // nothing is printed if no timestamps are found.
+    let output = parse_input(&["--extract"], "no timestamps\n");
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+    let output = parse_input(&["--json-input", "--key", "ts"], "");
+    assert_eq!(String::from_utf8(output.stderr).unwrap(), "");
+}
+
+#[test]
+fn template() {
+    // each timestamp of a batch is filled in the template.
+    let output = parse_input(
+        &["--extract", "--template", "{timestamp}/{weekday}"],
+        "a=0 b=1560762129\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "a=0 b=1560762129/Monday\n"
+    );
+    let output = parse_input(
+        &[
+            "--json-input",
+            "--key",
+            "ts",
+            "--inject",
+            "day",
+            "--template",
+            "{doy}",
+        ],
+        "{\"ts\": 0}\n{\"ts\": 1560762129}\n",
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "{\"ts\": 0,\"day\":\"1\"}\n{\"ts\": 1560762129,\"day\":\"168\"}\n"
+    );
+
+    // a fractional timestamp from stdin is the input as it is.
+    let output = parse_input(
+        &["--template", r"{input}\t{timestamp}\t{iso}"],
+        "1560762129.5\n",
+    );
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1560762129.5\t1560762129\t2019-06-17T09:02:09.500+00:00\n"
+    );
+
+    let output = parse_input(&["--template", "{iso} {when}", "0"], "");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Wrong template: unknown placeholder 'when'."));
+    assert!(stderr.contains("possible names: [timestamp, iso, rfc3339,"));
+}
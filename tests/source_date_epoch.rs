@@ -0,0 +1,59 @@
+use std::process::{Command, Output};
+
+fn ut(args: &[&str], source_date_epoch: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env("SOURCE_DATE_EPOCH", source_date_epoch)
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn stdout(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+#[test]
+fn base_from_env() {
+    assert_eq!(stdout(ut(&["-u", "g"], "1560762129")), "1560762129");
+    assert_eq!(
+        stdout(ut(&["-u", "g", "-d", "1d"], "1560762129")),
+        "1560848529"
+    );
+    assert_eq!(
+        stdout(ut(&["-u", "g", "-b", "today"], "1560762129")),
+        "1560729600"
+    );
+    assert_eq!(
+        stdout(ut(&["-p", "ms", "--utc-now"], "1560762129")),
+        "1560762129000"
+    );
+
+    // an explicit base takes precedence.
+    assert_eq!(stdout(ut(&["-u", "g", "100"], "1560762129")), "100");
+
+    // an empty value is ignored, as other tools do.
+    assert_ne!(stdout(ut(&["-u", "g"], "")), "1560762129");
+}
+
+#[test]
+fn wrong_source_date_epoch() {
+    let output = ut(&["-u", "g"], "yesterday");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Wrong SOURCE_DATE_EPOCH: 'yesterday'")
+    );
+    let output = ut(&["-u", "--utc-now"], "yesterday");
+    assert!(!output.status.success());
+
+    // parse does not use the current time, so the value is not validated.
+    assert_eq!(
+        stdout(ut(&["-u", "p", "0"], "yesterday")),
+        "1970-01-01 00:00:00 (UTC)"
+    );
+    assert_eq!(stdout(ut(&["-u", "--at", "0", "g"], "yesterday")), "0");
+}
@@ -0,0 +1,104 @@
+use std::process::{Command, Output};
+
+fn ut(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ut"))
+        .env_remove("UT_NOW")
+        .env_remove("UT_OFFSET")
+        .args(args)
+        .output()
+        .expect("failed to run ut")
+}
+
+fn stderr(output: Output) -> String {
+    assert!(
+        output.status.success(),
+        "ut failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn quiet() {
+    let deprecated = "-p PRECISION option is deprecated.\n";
+    assert_eq!(
+        stderr(ut(&["-u", "g", "-p", "ms", "-b", "today"])),
+        deprecated
+    );
+    assert_eq!(
+        stderr(ut(&["-u", "-q", "g", "-p", "ms", "-b", "today"])),
+        ""
+    );
+    assert_eq!(stderr(ut(&["-u", "p", "-p", "ms", "0"])), deprecated);
+    assert_eq!(stderr(ut(&["-u", "--quiet", "p", "-p", "ms", "0"])), "");
+
+    assert!(!ut(&["-q", "-v", "g"]).status.success());
+}
+
+#[test]
+fn quiet_after_subcommand() {
+    // a 13-digit base is read in milliseconds, and the detected precision is a notice.
+    let args = ["-u", "g", "--detect-precision", "1560762129000"];
+    let output = ut(&args);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1560762129\n");
+    assert_eq!(stderr(output), "Detected precision: millisecond\n");
+
+    let output = ut(&[&args[..], &["-q"]].concat());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1560762129\n");
+    assert_eq!(stderr(output), "");
+    assert_eq!(stderr(ut(&["-u", "p", "-p", "ms", "0", "--quiet"])), "");
+}
+
+#[test]
+fn verbose() {
+    let args = ["-u", "g", "--ymd", "20190131", "-d", "1mon", "-d", "1d"];
+    assert_eq!(stderr(ut(&args)), "");
+
+    let verbose = stderr(ut(&[&["-v"], &args[..]].concat()));
+    assert!(verbose.starts_with("precision: second\ntimezone: UTC\noffset: +00:00\n"));
+    assert!(verbose.contains("selected base: date 2019-01-31\ndeltas: 1mon, 1d\n"));
+    assert!(!verbose.contains("delta: "));
+
+    let trace = stderr(ut(&[&["-vv"], &args[..]].concat()));
+    assert!(trace.ends_with(
+        "delta: 1mon -> Nonexistent date: 2019-02-31 does not exist.\n\
+         delta: 1d -> 2019-03-01T00:00:00Z\n\
+         applied: 2019-01-31T00:00:00Z -> 2019-03-01T00:00:00Z\n\
+         timestamp: 1551398400\n"
+    ));
+}
+
+#[test]
+fn debug() {
+    let args = [
+        "-u", "--at", "0", "--debug", "g", "--ymd", "20190617", "--hms", "09:02:09", "-t", "hour",
+        "-t", "day", "-d", "1d", "-d", "-2h",
+    ];
+    let debug = stderr(ut(&args));
+    // the same as `-vv`.
+    assert_eq!(debug, stderr(ut(&[&["-vv"], &args[..]].concat())));
+
+    let steps: Vec<&str> = debug
+        .lines()
+        .skip_while(|line| !line.starts_with("now: "))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            "now: 1970-01-01T00:00:00Z",
+            "date: 2019-06-17",
+            "base: 2019-06-17T09:02:09Z",
+            "truncate to hour: 2019-06-17T09:00:00Z",
+            "truncate to day: 2019-06-17T00:00:00Z",
+            "delta: 1d -> 2019-06-18T00:00:00Z",
+            "delta: -2h -> 2019-06-17T22:00:00Z",
+            "applied: 2019-06-17T00:00:00Z -> 2019-06-17T22:00:00Z",
+            "timestamp: 1560808800",
+        ]
+    );
+
+    let debug = stderr(ut(&["-u", "--debug", "g", "-d", "1d", "1560762129"]));
+    assert!(debug.contains("base: timestamp 1560762129 in second\nbase: 2019-06-17T09:02:09Z\n"));
+
+    assert!(!ut(&["-q", "--debug", "g"]).status.success());
+}